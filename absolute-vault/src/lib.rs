@@ -1,7 +1,16 @@
+// Anchor's generated IDL instructions still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke_signed;
-use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_lang::system_program;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use miko_transfer_hook::{HolderCheckpoint, CHECKPOINT_SEED};
+use smart_dial::{DialState, DIAL_STATE_LEN, DIAL_STATE_SEED};
 use spl_token_2022::{
     extension::{
         transfer_fee::{
@@ -13,6 +22,7 @@ use spl_token_2022::{
         },
         BaseStateWithExtensions, StateWithExtensions,
     },
+    onchain::invoke_transfer_checked,
     state::Mint as MintState,
 };
 
@@ -21,12 +31,48 @@ declare_id!("7eJ8NmvhSDcmqCP1HhSjuzvAacTWRqyFDjHoQXzHHghS");
 
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const POOL_REGISTRY_SEED: &[u8] = b"pool_registry";
+pub const DISTRIBUTION_SEED: &[u8] = b"distribution";
+pub const REWARD_INDEX_SEED: &[u8] = b"reward_index";
+pub const HOLDER_POSITION_SEED: &[u8] = b"holder_position";
+pub const REVENUE_SPLIT_SEED: &[u8] = b"revenue_split";
+pub const KEEPER_LOG_SEED: &[u8] = b"keeper_log";
+pub const KEEPER_RESERVE_SEED: &[u8] = b"keeper_reserve";
+pub const KEEPER_ROLE_SEED: &[u8] = b"keeper_role";
+pub const ASSET_LEDGER_SEED: &[u8] = b"asset_ledger";
+pub const MAX_LEDGER_ENTRIES: usize = 32;
+pub const REWARD_POSITION_SEED: &[u8] = b"reward_position";
+pub const OWNER_SHARE_SEED: &[u8] = b"owner_share";
+pub const VAULT_TOMBSTONE_SEED: &[u8] = b"vault_tombstone";
+// Layout versions; unversioned accounts are the legacy layouts migrated by `migrate_*`
+pub const VAULT_STATE_VERSION: u8 = 2;
+pub const POOL_REGISTRY_VERSION: u8 = 1;
+pub const KEEPER_WORK_LOG_VERSION: u8 = 2;
+pub const ASSET_LEDGER_VERSION: u8 = 1;
+// Legacy layouts are told apart by the size they were allocated with
+pub const VAULT_LAYOUT_V0_LEN: usize = 8 + VaultStateLayoutV0::INIT_SPACE;
+pub const VAULT_LAYOUT_V1_LEN: usize = 8 + VaultStateLayoutV1::INIT_SPACE;
+pub const POOL_REGISTRY_LAYOUT_V0_LEN: usize = 8 + PoolRegistryLayoutV0::INIT_SPACE;
+pub const KEEPER_WORK_LOG_LAYOUT_V0_LEN: usize = 8 + KeeperWorkLogLayoutV0::INIT_SPACE;
+pub const KEEPER_WORK_LOG_LAYOUT_V1_LEN: usize = 8 + KeeperWorkLogLayoutV1::INIT_SPACE;
+pub const KEEPER_WORK_LOG_LEN: usize = 8 + std::mem::size_of::<KeeperWorkLog>();
+pub const ASSET_LEDGER_LAYOUT_V0_LEN: usize = 8 + AssetLedgerLayoutV0::INIT_SPACE;
+pub const ASSET_LEDGER_LEN: usize = 8 + std::mem::size_of::<AssetLedger>();
+pub const MAX_KEEPER_WORK_ENTRIES: usize = 50;
+pub const MAX_WORK_DETAILS_LEN: usize = 100;
 pub const MAX_EXCLUSIONS: usize = 100;
 pub const MAX_POOLS: usize = 50;
+pub const MAX_REVENUE_PAYEES: usize = 5;
+pub const MAX_PAYEE_LABEL_LEN: usize = 16;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const CONFIG_TIMELOCK: i64 = 48 * 60 * 60; // 48 hours between proposal and apply
+pub const MAX_EMERGENCY_DESTINATIONS: usize = 5;
+pub const EMERGENCY_COOLDOWN: i64 = 24 * 60 * 60; // Default 24 hours between emergency withdrawals
 pub const HARVEST_THRESHOLD: u64 = 100_000_000_000_000; // 500k MIKO with 9 decimals (100k for
                                                         // test)
-pub const OWNER_TAX_SHARE: u64 = 25; // 25% to owner
-pub const HOLDERS_TAX_SHARE: u64 = 75; // 75% to holders
+pub const OWNER_TAX_SHARE: u64 = 25; // Default 25% to owner
+pub const HOLDERS_TAX_SHARE: u64 = 75; // Default 75% to holders
+pub const REWARD_PER_SHARE_PRECISION: u128 = 1_000_000_000_000;
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
 
 #[program]
 pub mod absolute_vault {
@@ -53,12 +99,35 @@ pub mod absolute_vault {
         ];
         vault.harvest_threshold = HARVEST_THRESHOLD;
         vault.total_fees_harvested = 0;
+        vault.total_burned = 0;
         vault.total_rewards_distributed = 0;
         vault.pending_withheld = 0;
         vault.last_harvest_time = 0;
         vault.last_distribution_time = 0;
         vault.launch_timestamp = 0;
         vault.distribution_id = 0;
+        vault.unsettled_distribution_id = 0;
+        vault.unacknowledged_shortfall_id = 0;
+        vault.burn_share_bps = 0;
+        vault.pending_burn_amount = 0;
+        vault.min_distribution_interval = 0;
+        vault.withdrawal_window = 0;
+        vault.max_withdrawal_per_window = 0;
+        vault.max_threshold_multiple = 0;
+        vault.window_start = 0;
+        vault.window_withdrawn = 0;
+        vault.last_keeper_heartbeat = Clock::get()?.unix_timestamp;
+        vault.keeper_inactivity_period = 0;
+        vault.crank_bounty_lamports = 0;
+        vault.guardian = Pubkey::default();
+        vault.emergency_destinations = Vec::new();
+        vault.emergency_max_bps = BPS_DENOMINATOR as u16;
+        vault.emergency_cooldown = EMERGENCY_COOLDOWN;
+        vault.last_emergency_withdrawal = 0;
+        vault.total_emergency_withdrawn = 0;
+        vault.last_reconciliation = ReconciliationReport::default();
+        vault.version = VAULT_STATE_VERSION;
+        vault.reserved = [0; 128];
 
         msg!("Vault initialized");
         msg!("Authority: {}", vault.authority);
@@ -72,8 +141,10 @@ pub mod absolute_vault {
     pub fn initialize_pool_registry(ctx: Context<InitializePoolRegistry>) -> Result<()> {
         let registry = &mut ctx.accounts.pool_registry;
 
+        registry.version = POOL_REGISTRY_VERSION;
         registry.vault = ctx.accounts.vault.key();
         registry.pools = Vec::new();
+        registry.reserved = [0; 64];
 
         msg!("Pool registry initialized");
         Ok(())
@@ -94,7 +165,7 @@ pub mod absolute_vault {
         Ok(())
     }
 
-    /// Update pool registry with detected pools (keeper only)
+    /// Update pool registry with detected pools (keeper or pool reporter)
     pub fn update_pool_registry(
         ctx: Context<UpdatePoolRegistry>,
         pools_to_add: Vec<Pubkey>,
@@ -114,24 +185,29 @@ pub mod absolute_vault {
         Ok(())
     }
 
-    /// Harvest fees from token accounts to mint (keeper only)
+    /// Harvest fees from token accounts to mint (keeper or harvester, permissionless once the
+    /// keeper is inactive)
     pub fn harvest_fees<'info>(
         ctx: Context<'_, '_, '_, 'info, HarvestFees<'info>>,
         accounts: Vec<Pubkey>,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let caller = ctx.accounts.keeper_authority.key();
+        let is_crank = authorize_crank(
+            &mut ctx.accounts.vault,
+            caller,
+            &ctx.accounts.keeper_role,
+            now,
+        )?;
+
         let vault = &mut ctx.accounts.vault;
 
         require!(
-            accounts.len() > 0 && accounts.len() <= 20,
+            !accounts.is_empty() && accounts.len() <= 20,
             VaultError::InvalidBatchSize
         );
 
-        let token_mint_info = &ctx.accounts.token_mint;
-        let mint_data = token_mint_info.try_borrow_data()?;
-        let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
-        let transfer_fee_extension = mint_state.get_extension::<TransferFeeConfig>()?;
-        let accumulated_fees = u64::from(transfer_fee_extension.withheld_amount);
-        drop(mint_data);
+        let accumulated_fees = mint_withheld_amount(&ctx.accounts.token_mint)?;
 
         require!(
             accumulated_fees >= vault.harvest_threshold,
@@ -156,15 +232,44 @@ pub mod absolute_vault {
 
         invoke_signed(&ix, &account_infos, signer_seeds)?;
 
-        vault.last_harvest_time = Clock::get()?.unix_timestamp;
+        vault.last_harvest_time = now;
+
+        let harvested = mint_withheld_amount(&ctx.accounts.token_mint)?
+            .checked_sub(accumulated_fees)
+            .ok_or(VaultError::MathOverflow)?;
+        msg!(
+            "Harvested {} fees from {} accounts",
+            harvested,
+            accounts.len()
+        );
 
-        msg!("Harvested fees from {} accounts", accounts.len());
+        // A crank is only paid for moving fees; the mint's withheld amount stays above the
+        // threshold until withdrawn, so an empty harvest could otherwise be repeated
+        if is_crank {
+            require!(harvested > 0, VaultError::NothingToHarvest);
+            pay_crank_bounty(
+                &ctx.accounts.vault,
+                ctx.accounts.keeper_reserve.as_mut(),
+                &ctx.accounts.keeper_authority,
+                now,
+            )?;
+        }
 
         Ok(())
     }
 
-    /// Withdraw fees from mint to vault PDA (keeper only)
+    /// Withdraw fees from mint to vault PDA (keeper or harvester, permissionless once the keeper
+    /// is inactive)
     pub fn withdraw_fees_from_mint(ctx: Context<WithdrawFeesFromMint>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let caller = ctx.accounts.keeper_authority.key();
+        let is_crank = authorize_crank(
+            &mut ctx.accounts.vault,
+            caller,
+            &ctx.accounts.keeper_role,
+            now,
+        )?;
+
         // Get current vault balance before withdrawal
         let vault_balance_before = ctx.accounts.vault_token_account.amount;
 
@@ -197,34 +302,130 @@ pub mod absolute_vault {
         let vault_balance_after = ctx.accounts.vault_token_account.amount;
 
         // Calculate withdrawn amount
-        let withdrawn_amount = vault_balance_after.saturating_sub(vault_balance_before);
+        let withdrawn_amount = vault_balance_after
+            .checked_sub(vault_balance_before)
+            .ok_or(VaultError::MathOverflow)?;
 
-        // Update vault state
-        let vault = &mut ctx.accounts.vault;
-        vault.total_fees_harvested = vault.total_fees_harvested.saturating_add(withdrawn_amount);
-        vault.last_harvest_amount = withdrawn_amount;
-        vault.last_harvest_time = Clock::get()?.unix_timestamp;
+        // Update vault state and earmark the burn bucket; it must be burned before
+        // the rest can be distributed
+        let burn_amount = ctx.accounts.vault.record_harvest(withdrawn_amount, now)?;
 
         msg!("Withdrew {} fees from mint to vault", withdrawn_amount);
+        msg!("Earmarked {} for burn", burn_amount);
+
+        // Fees are attributed to the distribution they will fund
+        let next_distribution_id = ctx.accounts.vault.next_distribution_id()?;
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_token_account.key(),
+            LedgerEntryKind::FeeHarvest,
+            withdrawn_amount,
+            next_distribution_id,
+            now,
+        )?;
+
+        if is_crank {
+            require!(withdrawn_amount > 0, VaultError::NothingToWithdraw);
+            pay_crank_bounty(
+                &ctx.accounts.vault,
+                ctx.accounts.keeper_reserve.as_mut(),
+                &ctx.accounts.keeper_authority,
+                now,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Burn the share of harvested fees earmarked for burning (permissionless)
+    pub fn burn_harvested_fees(ctx: Context<BurnHarvestedFees>) -> Result<()> {
+        let amount = ctx.accounts.vault.pending_burn_amount;
+        require!(amount > 0, VaultError::NothingToBurn);
+
+        let token_mint_key = ctx.accounts.vault.token_mint;
+        let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::burn_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::BurnChecked {
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let vault = &mut ctx.accounts.vault;
+        vault.pending_burn_amount = 0;
+        vault.total_burned = vault
+            .total_burned
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        emit!(FeesBurned {
+            timestamp,
+            amount,
+            total_burned: vault.total_burned,
+        });
+
+        let next_distribution_id = ctx.accounts.vault.next_distribution_id()?;
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_token_account.key(),
+            LedgerEntryKind::Burn,
+            amount,
+            next_distribution_id,
+            timestamp,
+        )?;
 
+        msg!("Burned {} harvested MIKO", amount);
         Ok(())
     }
 
-    /// Withdraw harvested fees and report distribution plan (keeper only)
-    pub fn withdraw_and_report_distribution_plan(
-        ctx: Context<WithdrawAndReport>,
+    /// Withdraw harvested fees and report distribution plan (keeper or distributor)
+    /// Remaining accounts: extra transfer-hook accounts when the mint has a hook
+    pub fn withdraw_and_report_distribution_plan<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawAndReport<'info>>,
         amount_to_withdraw: u64,
         expected_minimum_reward_amount: u64,
         distribution_hash: [u8; 32],
     ) -> Result<()> {
         let clock = Clock::get()?;
 
+        require!(
+            ctx.accounts.vault.unsettled_distribution_id == 0,
+            VaultError::PreviousDistributionNotSettled
+        );
+        require!(
+            ctx.accounts.vault.unacknowledged_shortfall_id == 0,
+            VaultError::UnacknowledgedShortfall
+        );
+        require!(
+            ctx.accounts.vault.pending_burn_amount == 0,
+            VaultError::BurnPending
+        );
+
         let vault_balance = ctx.accounts.vault_token_account.amount;
         require!(
             amount_to_withdraw == vault_balance,
             VaultError::MustWithdrawFullAmount
         );
 
+        let keeper = ctx.accounts.keeper_authority.key();
+        enforce_withdrawal_guards(
+            &mut ctx.accounts.vault,
+            keeper,
+            amount_to_withdraw,
+            clock.unix_timestamp,
+        )?;
+        ctx.accounts.vault.last_keeper_heartbeat = clock.unix_timestamp;
+
         let mint_data = ctx.accounts.token_mint.to_account_info();
         let mint_data_borrowed = mint_data.try_borrow_data()?;
         let mint_info = StateWithExtensions::<MintState>::unpack(&mint_data_borrowed)?;
@@ -235,19 +436,30 @@ pub mod absolute_vault {
         let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
         let signer_seeds = &[&seeds[..]];
 
-        let keeper = ctx.accounts.keeper_authority.key();
-
         let distribution_id;
         {
             let vault = &mut ctx.accounts.vault;
-            vault.distribution_id = vault.distribution_id.saturating_add(1);
-            distribution_id = vault.distribution_id;
-            vault.last_distribution_time = clock.unix_timestamp;
-            vault.total_rewards_distributed = vault
-                .total_rewards_distributed
-                .saturating_add(amount_to_withdraw);
+            distribution_id =
+                vault.record_distribution(amount_to_withdraw, clock.unix_timestamp)?;
+            vault.unsettled_distribution_id = distribution_id;
         }
 
+        // Snapshot the keeper's reward balance so settlement can measure the swap output
+        let record = &mut ctx.accounts.distribution_record;
+        record.vault = ctx.accounts.vault.key();
+        record.distribution_id = distribution_id;
+        record.keeper = keeper;
+        record.withdrawn_miko_amount = amount_to_withdraw;
+        record.expected_minimum_reward_amount = expected_minimum_reward_amount;
+        record.reward_token_account = ctx.accounts.keeper_reward_token_account.key();
+        record.reward_mint = ctx.accounts.keeper_reward_token_account.mint;
+        record.reward_balance_snapshot = ctx.accounts.keeper_reward_token_account.amount;
+        record.reward_amount_received = 0;
+        record.status = DistributionStatus::Pending;
+        record.created_at = clock.unix_timestamp;
+        record.settled_at = 0;
+        record.revenue_split = ctx.accounts.revenue_split.payees.clone();
+
         // Emit auditable report before transferring funds
         emit!(DistributionPlanReport {
             timestamp: clock.unix_timestamp,
@@ -259,19 +471,25 @@ pub mod absolute_vault {
         });
 
         // Transfer harvested fees to keeper
-        token_2022::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token_2022::TransferChecked {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    mint: ctx.accounts.token_mint.to_account_info(),
-                    to: ctx.accounts.keeper_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer_seeds,
-            ),
+        invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.vault_token_account.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.keeper_token_account.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.remaining_accounts,
             amount_to_withdraw,
             decimals,
+            signer_seeds,
+        )?;
+
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_token_account.key(),
+            LedgerEntryKind::DistributionOut,
+            amount_to_withdraw,
+            distribution_id,
+            clock.unix_timestamp,
         )?;
 
         msg!("Withdraw and report distribution plan executed");
@@ -279,413 +497,4662 @@ pub mod absolute_vault {
         Ok(())
     }
 
-    /// Log keeper work on-chain (keeper only)
-    /// Records swap and distribution activities for transparency
-    pub fn log_keeper_work(
-        ctx: Context<LogKeeperWork>,
-        work_type: KeeperWorkType,
-        amount: u64,
-        details: String,
+    /// Swap harvested fees to the reward token through a registered pool and report
+    /// the distribution plan (keeper or distributor). Reward tokens land in a vault-owned account.
+    pub fn swap_and_report_distribution_plan<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapAndReport<'info>>,
+        adapter: SwapAdapter,
+        amount_to_swap: u64,
+        expected_minimum_reward_amount: u64,
+        distribution_hash: [u8; 32],
     ) -> Result<()> {
-        let log = &mut ctx.accounts.keeper_work_log;
+        let clock = Clock::get()?;
 
-        // Initialize if new
-        if log.vault == Pubkey::default() {
-            log.vault = ctx.accounts.vault.key();
-            log.entries = Vec::new();
-        }
+        require!(
+            ctx.accounts.vault.unsettled_distribution_id == 0,
+            VaultError::PreviousDistributionNotSettled
+        );
+        require!(
+            ctx.accounts.vault.unacknowledged_shortfall_id == 0,
+            VaultError::UnacknowledgedShortfall
+        );
+        require!(
+            ctx.accounts.vault.pending_burn_amount == 0,
+            VaultError::BurnPending
+        );
+        require!(
+            amount_to_swap == ctx.accounts.vault_token_account.amount,
+            VaultError::MustWithdrawFullAmount
+        );
+        enforce_withdrawal_guards(
+            &mut ctx.accounts.vault,
+            ctx.accounts.keeper_authority.key(),
+            amount_to_swap,
+            clock.unix_timestamp,
+        )?;
+        ctx.accounts.vault.last_keeper_heartbeat = clock.unix_timestamp;
+        require!(
+            ctx.accounts.amm_program.key() == adapter.program_id(),
+            VaultError::InvalidSwapProgram
+        );
+        // AMM accounts first, then the owner share accounts of the revenue split
+        require!(
+            ctx.remaining_accounts.len() >= adapter.accounts_len(),
+            VaultError::InvalidSwapAccounts
+        );
+        let (amm_accounts, owner_shares) = ctx.remaining_accounts.split_at(adapter.accounts_len());
+        require!(
+            ctx.accounts
+                .pool_registry
+                .pools
+                .contains(adapter.pool(amm_accounts).key),
+            VaultError::PoolNotRegistered
+        );
 
-        // Add new entry (keep last 50 entries)
-        log.entries.push(KeeperWorkEntry {
-            timestamp: Clock::get()?.unix_timestamp,
-            work_type,
-            amount,
-            details: details.chars().take(100).collect(), // Limit details to 100 chars
+        let token_mint_key = ctx.accounts.vault.token_mint;
+        let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
+        let signer_seeds = &[&seeds[..]];
+
+        let reward_balance_before = ctx.accounts.vault_reward_token_account.amount;
+
+        let (ix, account_infos) = adapter.swap_instruction(
+            SwapLegs {
+                amm_program: ctx.accounts.amm_program.to_account_info(),
+                payer: ctx.accounts.vault.to_account_info(),
+                input_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                output_token_account: ctx.accounts.vault_reward_token_account.to_account_info(),
+                input_token_program: ctx.accounts.token_program.to_account_info(),
+                input_token_mint: ctx.accounts.token_mint.to_account_info(),
+            },
+            amm_accounts,
+            amount_to_swap,
+            expected_minimum_reward_amount,
+        );
+
+        invoke_signed(&ix, &account_infos, signer_seeds)?;
+
+        // Enforce the minimum on the vault side regardless of the AMM's own checks
+        ctx.accounts.vault_reward_token_account.reload()?;
+        let reward_amount_received = ctx
+            .accounts
+            .vault_reward_token_account
+            .amount
+            .checked_sub(reward_balance_before)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            reward_amount_received >= expected_minimum_reward_amount,
+            VaultError::SlippageExceeded
+        );
+
+        let keeper = ctx.accounts.keeper_authority.key();
+        let reward_mint = ctx.accounts.vault_reward_token_account.mint;
+        let payees = ctx.accounts.revenue_split.payees.clone();
+
+        let distribution_id = ctx
+            .accounts
+            .vault
+            .record_distribution(amount_to_swap, clock.unix_timestamp)?;
+
+        // Shares routed to the vault itself stay in custody for holder reward indexes
+        let owner_shares_paid = pay_owner_shares(
+            &payees,
+            &ctx.accounts.vault.key(),
+            reward_amount_received,
+            owner_shares,
+            RewardSource {
+                token_program: ctx.accounts.reward_token_program.to_account_info(),
+                token_account: ctx.accounts.vault_reward_token_account.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                decimals: ctx.accounts.reward_mint.decimals,
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        )?;
+
+        // Swap output is measured immediately, so the record is settled on creation
+        let record = &mut ctx.accounts.distribution_record;
+        record.vault = ctx.accounts.vault.key();
+        record.distribution_id = distribution_id;
+        record.keeper = keeper;
+        record.withdrawn_miko_amount = amount_to_swap;
+        record.expected_minimum_reward_amount = expected_minimum_reward_amount;
+        record.reward_token_account = ctx.accounts.vault_reward_token_account.key();
+        record.reward_mint = reward_mint;
+        record.reward_balance_snapshot = reward_balance_before;
+        record.reward_amount_received = reward_amount_received;
+        record.status = DistributionStatus::Settled;
+        record.created_at = clock.unix_timestamp;
+        record.settled_at = clock.unix_timestamp;
+        record.revenue_split = payees;
+
+        emit!(DistributionPlanReport {
+            timestamp: clock.unix_timestamp,
+            distribution_id,
+            withdrawn_miko_amount: amount_to_swap,
+            expected_minimum_reward_amount,
+            distribution_hash,
+            keeper,
+        });
+
+        emit!(DistributionSettled {
+            timestamp: clock.unix_timestamp,
+            distribution_id,
+            expected_minimum_reward_amount,
+            reward_amount_received,
+            shortfall: false,
         });
 
-        if log.entries.len() > 50 {
-            log.entries.remove(0);
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_token_account.key(),
+            LedgerEntryKind::SwapOut,
+            amount_to_swap,
+            distribution_id,
+            clock.unix_timestamp,
+        )?;
+        record_ledger_entry(
+            &ctx.accounts.reward_ledger,
+            &ctx.accounts.vault_reward_token_account.key(),
+            LedgerEntryKind::SwapIn,
+            reward_amount_received,
+            distribution_id,
+            clock.unix_timestamp,
+        )?;
+        if owner_shares_paid > 0 {
+            record_ledger_entry(
+                &ctx.accounts.reward_ledger,
+                &ctx.accounts.vault_reward_token_account.key(),
+                LedgerEntryKind::PayeeShare,
+                owner_shares_paid,
+                distribution_id,
+                clock.unix_timestamp,
+            )?;
         }
 
-        msg!("Logged keeper work: {:?}", work_type);
+        msg!(
+            "Swapped {} MIKO for {} reward tokens via {:?}",
+            amount_to_swap,
+            reward_amount_received,
+            adapter
+        );
 
         Ok(())
     }
 
-    /// Manage reward exclusions (admin only)
-    pub fn manage_exclusions(
-        ctx: Context<ManageExclusions>,
-        action: ExclusionAction,
-        wallet: Pubkey,
+    /// Settle a distribution against its announced minimum reward (keeper or distributor)
+    /// Shortfalls block further withdrawals until acknowledged by the authority.
+    /// The recorded revenue split is applied here: payee shares of the reward received
+    /// move from the signer's reward account into the owner share accounts, passed as
+    /// remaining accounts in split order.
+    pub fn settle_distribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleDistribution<'info>>,
     ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+        ctx.accounts.vault.last_keeper_heartbeat = clock.unix_timestamp;
+        let record = &mut ctx.accounts.distribution_record;
 
-        match action {
-            ExclusionAction::Add => {
-                require!(
-                    vault.reward_exclusions.len() < MAX_EXCLUSIONS,
-                    VaultError::ExclusionListFull
-                );
-                require!(
-                    !vault.reward_exclusions.contains(&wallet),
-                    VaultError::AlreadyExcluded
-                );
-                vault.reward_exclusions.push(wallet);
-                msg!("Added {} to reward exclusions", wallet);
-            }
-            ExclusionAction::Remove => {
-                vault.reward_exclusions.retain(|&x| x != wallet);
-                msg!("Removed {} from reward exclusions", wallet);
-            }
+        require!(
+            record.status == DistributionStatus::Pending,
+            VaultError::DistributionNotPending
+        );
+
+        // Clamped on purpose: a reward account that dropped below its snapshot received
+        // nothing, which settles as a shortfall for the admin rather than blocking
+        let reward_amount_received = ctx
+            .accounts
+            .reward_token_account
+            .amount
+            .saturating_sub(record.reward_balance_snapshot);
+        let shortfall = reward_amount_received < record.expected_minimum_reward_amount;
+
+        record.reward_amount_received = reward_amount_received;
+        record.settled_at = clock.unix_timestamp;
+        record.status = if shortfall {
+            DistributionStatus::Shortfall
+        } else {
+            DistributionStatus::Settled
+        };
+
+        let vault = &mut ctx.accounts.vault;
+        if vault.unsettled_distribution_id == record.distribution_id {
+            vault.unsettled_distribution_id = 0;
         }
+        if shortfall {
+            vault.unacknowledged_shortfall_id = record.distribution_id;
+        }
+
+        pay_owner_shares(
+            &record.revenue_split,
+            &vault.key(),
+            reward_amount_received,
+            ctx.remaining_accounts,
+            RewardSource {
+                token_program: ctx.accounts.reward_token_program.to_account_info(),
+                token_account: ctx.accounts.reward_token_account.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                decimals: ctx.accounts.reward_mint.decimals,
+                authority: ctx.accounts.keeper_authority.to_account_info(),
+            },
+            &[],
+        )?;
+
+        emit!(DistributionSettled {
+            timestamp: clock.unix_timestamp,
+            distribution_id: record.distribution_id,
+            expected_minimum_reward_amount: record.expected_minimum_reward_amount,
+            reward_amount_received,
+            shortfall,
+        });
+
+        msg!(
+            "Distribution {} settled: received {}, expected at least {}",
+            record.distribution_id,
+            reward_amount_received,
+            record.expected_minimum_reward_amount
+        );
 
         Ok(())
     }
 
-    /// Update vault configuration (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
-        new_owner_wallet: Option<Pubkey>,
-        new_harvest_threshold: Option<u64>,
-        new_authority: Option<Pubkey>,
-        new_keeper_authority: Option<Pubkey>,
-    ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Acknowledge a settlement shortfall and unblock the keeper (admin only)
+    pub fn acknowledge_shortfall(ctx: Context<AcknowledgeShortfall>) -> Result<()> {
+        let record = &mut ctx.accounts.distribution_record;
 
-        if let Some(owner) = new_owner_wallet {
-            vault.owner_wallet = owner;
-        }
-        if let Some(threshold) = new_harvest_threshold {
-            vault.harvest_threshold = threshold;
-        }
-        if let Some(authority) = new_authority {
-            vault.authority = authority;
-        }
-        if let Some(keeper) = new_keeper_authority {
-            vault.keeper_authority = keeper;
+        require!(
+            record.status == DistributionStatus::Shortfall,
+            VaultError::NoShortfallToAcknowledge
+        );
+
+        record.status = DistributionStatus::ShortfallAcknowledged;
+
+        let vault = &mut ctx.accounts.vault;
+        if vault.unacknowledged_shortfall_id == record.distribution_id {
+            vault.unacknowledged_shortfall_id = 0;
         }
 
-        msg!("Vault configuration updated");
+        emit!(ShortfallAcknowledged {
+            timestamp: Clock::get()?.unix_timestamp,
+            distribution_id: record.distribution_id,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        msg!(
+            "Shortfall acknowledged for distribution {}",
+            record.distribution_id
+        );
+
         Ok(())
     }
 
-    /// Emergency withdraw from vault (admin only)
-    pub fn emergency_withdraw_vault(ctx: Context<EmergencyWithdraw>, amount: u64) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Create the reward-per-share index for a reward mint (keeper or distributor)
+    /// Each reward token selected by the dial gets its own index
+    pub fn initialize_reward_index(ctx: Context<InitializeRewardIndex>) -> Result<()> {
+        let index = &mut ctx.accounts.reward_index;
+
+        index.vault = ctx.accounts.vault.key();
+        index.reward_mint = ctx.accounts.reward_mint.key();
+        index.reward_token_account = ctx.accounts.vault_reward_token_account.key();
+        index.acc_reward_per_share = 0;
+        index.total_staked = 0;
+        index.total_funded = 0;
+        index.total_claimed = 0;
+        index.last_funded_at = 0;
+        index.total_forfeited = 0;
+        index.open_positions = 0;
+
+        msg!("Reward index initialized for {}", index.reward_mint);
+        Ok(())
+    }
 
-        let seeds = &[VAULT_SEED, vault.token_mint.as_ref(), &[ctx.bumps.vault]];
-        let signer_seeds = &[&seeds[..]];
+    /// Credit vault-held reward tokens to checkpointed holders (keeper or distributor)
+    pub fn fund_reward_index(ctx: Context<FundRewardIndex>, amount: u64) -> Result<()> {
+        let index = &mut ctx.accounts.reward_index;
 
-        // Get mint decimals
-        let mint_data = ctx.accounts.token_mint.to_account_info();
-        let mint_data_borrowed = mint_data.try_borrow_data()?;
-        let mint_info = StateWithExtensions::<MintState>::unpack(&mint_data_borrowed)?;
-        let decimals = mint_info.base.decimals;
-        drop(mint_data_borrowed);
+        require!(amount > 0, VaultError::InvalidAmount);
+        require!(index.total_staked > 0, VaultError::NoStakedBalance);
 
-        token_2022::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token_2022::TransferChecked {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    mint: ctx.accounts.token_mint.to_account_info(),
-                    to: ctx.accounts.destination_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            amount,
-            decimals,
-        )?;
+        // Never promise more than the vault actually holds for this mint
+        let required = index
+            .outstanding()?
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            ctx.accounts.vault_reward_token_account.amount >= required,
+            VaultError::InsufficientRewardBalance
+        );
 
-        msg!("Emergency withdrawal: {} tokens", amount);
+        let increment = (amount as u128)
+            .checked_mul(REWARD_PER_SHARE_PRECISION)
+            .ok_or(VaultError::MathOverflow)?
+            / index.total_staked as u128;
+        index.acc_reward_per_share = index
+            .acc_reward_per_share
+            .checked_add(increment)
+            .ok_or(VaultError::MathOverflow)?;
+        index.total_funded = index
+            .total_funded
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        index.last_funded_at = Clock::get()?.unix_timestamp;
 
+        msg!(
+            "Funded {} rewards of {} over {} staked",
+            amount,
+            index.reward_mint,
+            index.total_staked
+        );
         Ok(())
     }
 
-    /// Emergency withdraw withheld from mint (admin only)
-    pub fn emergency_withdraw_withheld<'info>(
-        ctx: Context<'_, '_, '_, 'info, EmergencyWithdrawWithheld<'info>>,
-        accounts: Vec<Pubkey>,
-    ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Register a holder position for pull-based rewards (excluded wallets denied)
+    pub fn register_position(ctx: Context<RegisterPosition>) -> Result<()> {
+        let holder = ctx.accounts.holder.key();
 
         require!(
-            accounts.len() > 0 && accounts.len() <= 20,
-            VaultError::InvalidBatchSize
+            !ctx.accounts.vault.is_excluded(&holder),
+            VaultError::HolderExcluded
         );
 
-        let seeds = &[VAULT_SEED, vault.token_mint.as_ref(), &[ctx.bumps.vault]];
-        let signer_seeds = &[&seeds[..]];
+        let position = &mut ctx.accounts.holder_position;
+        position.vault = ctx.accounts.vault.key();
+        position.holder = holder;
+        position.registered_at = Clock::get()?.unix_timestamp;
 
-        let account_refs: Vec<&Pubkey> = accounts.iter().collect();
+        msg!("Registered holder position for {}", holder);
+        Ok(())
+    }
 
-        let ix = withdraw_withheld_tokens_from_accounts(
-            &ctx.accounts.token_program.key(),
-            &ctx.accounts.token_mint.key(),
-            &ctx.accounts.destination_token_account.key(),
-            &vault.key(),
-            &account_refs,
-            &[],
+    /// Checkpoint a holder's MIKO balance into a reward index (permissionless)
+    /// Anyone may poke a position so stale balances cannot keep earning
+    /// The stake is the balance tracked by the transfer hook, so MIKO moved to
+    /// another holder stops earning for the sender even before it is poked
+    pub fn checkpoint_position(ctx: Context<CheckpointPosition>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let holder = ctx.accounts.holder_position.holder;
+        let checkpoint = &ctx.accounts.holder_checkpoint;
+        let excluded = ctx.accounts.vault.is_excluded(&holder);
+        let new_balance = if excluded {
+            0
+        } else {
+            checkpoint
+                .balance
+                .min(ctx.accounts.holder_token_account.amount)
+        };
+
+        let index = &mut ctx.accounts.reward_index;
+        let position = &mut ctx.accounts.reward_position;
+
+        if position.holder == Pubkey::default() {
+            position.vault = index.vault;
+            position.holder = holder;
+            position.reward_mint = index.reward_mint;
+            position.token_account = checkpoint.token_account;
+        }
+        let was_open = position.is_open();
+        index.settle_position(position, checkpoint, now)?;
+        index.restake(position, checkpoint, new_balance)?;
+        index.track_open(was_open, position)?;
+
+        msg!(
+            "Checkpointed {} MIKO for {} on {}",
+            new_balance,
+            holder,
+            index.reward_mint
+        );
+        Ok(())
+    }
+
+    /// Claim accrued rewards of one reward mint (holder only)
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let index = &mut ctx.accounts.reward_index;
+        let position = &mut ctx.accounts.reward_position;
+
+        let was_open = position.is_open();
+        index.settle_position(
+            position,
+            &ctx.accounts.holder_checkpoint,
+            Clock::get()?.unix_timestamp,
         )?;
+        let amount = position.accrued;
+        require!(amount > 0, VaultError::NothingToClaim);
+
+        position.accrued = 0;
+        index.track_open(was_open, position)?;
+        position.total_claimed = position
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        index.total_claimed = index
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
 
-        let mut account_infos = vec![
-            ctx.accounts.token_mint.to_account_info(),
-            ctx.accounts.destination_token_account.to_account_info(),
-            ctx.accounts.vault.to_account_info(),
+        let token_mint_key = ctx.accounts.vault.token_mint;
+        let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.vault_reward_token_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.holder_reward_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        let distribution_id = ctx.accounts.vault.distribution_id;
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_reward_token_account.key(),
+            LedgerEntryKind::HolderClaim,
+            amount,
+            distribution_id,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        msg!("Claimed {} of {}", amount, ctx.accounts.reward_mint.key());
+        Ok(())
+    }
+
+    /// Apply a holder's transfer-hook weight to its reward position (permissionless)
+    /// Rewards since the last checkpoint are credited only if the hook shows the stake
+    /// was held throughout, and the stake drops to the hook-tracked balance if lower.
+    pub fn report_holder_weight(ctx: Context<ReportHolderWeight>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let checkpoint = &ctx.accounts.holder_checkpoint;
+        let index = &mut ctx.accounts.reward_index;
+        let position = &mut ctx.accounts.reward_position;
+
+        let excluded = ctx.accounts.vault.is_excluded(&checkpoint.owner);
+        // Raising the stake needs the token account itself, see `checkpoint_position`
+        let staked = if excluded {
+            0
+        } else {
+            position.balance.min(checkpoint.balance)
+        };
+        let accrued_before = position.accrued;
+        let was_open = position.is_open();
+        index.settle_position(position, checkpoint, now)?;
+        index.restake(position, checkpoint, staked)?;
+        index.track_open(was_open, position)?;
+
+        let balance_seconds = checkpoint.balance_seconds_at(now);
+        emit!(HolderWeightReport {
+            timestamp: now,
+            holder: checkpoint.owner,
+            token_account: checkpoint.token_account,
+            reward_mint: index.reward_mint,
+            balance: checkpoint.balance,
+            balance_seconds,
+            tracked_since: checkpoint.initialized_at,
+            staked,
+            credited: position
+                .accrued
+                .checked_sub(accrued_before)
+                .ok_or(VaultError::MathOverflow)?,
+            excluded,
+        });
+
+        msg!(
+            "Holder {} weight: {} balance-seconds since {}, staked {}",
+            checkpoint.owner,
+            balance_seconds,
+            checkpoint.initialized_at,
+            staked
+        );
+        Ok(())
+    }
+
+    /// Create the account holding `owner`'s revenue share of one reward mint
+    /// (permissionless). Swaps and settlements need one for every payee of the split.
+    pub fn initialize_owner_share(ctx: Context<InitializeOwnerShare>) -> Result<()> {
+        msg!(
+            "Owner share account for {} of {} initialized",
+            ctx.accounts.owner.key(),
+            ctx.accounts.reward_mint.key()
+        );
+        Ok(())
+    }
+
+    /// Claim the revenue share accrued to the signer for one reward mint
+    /// Shares stay with the destination that was configured when they accrued
+    pub fn claim_owner_share(ctx: Context<ClaimOwnerShare>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let reward_mint = ctx.accounts.reward_mint.key();
+        let amount = ctx.accounts.owner_share.amount;
+        require!(amount > 0, VaultError::NothingToClaim);
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            OWNER_SHARE_SEED,
+            vault_key.as_ref(),
+            &[ctx.bumps.owner_share_authority],
         ];
-        account_infos.extend(ctx.remaining_accounts.iter().cloned());
+        let signer_seeds = &[&seeds[..]];
 
-        invoke_signed(&ix, &account_infos, signer_seeds)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.owner_share.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.owner_reward_token_account.to_account_info(),
+                    authority: ctx.accounts.owner_share_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        emit!(OwnerShareClaimed {
+            timestamp: Clock::get()?.unix_timestamp,
+            owner,
+            reward_mint,
+            amount,
+        });
+
+        msg!("Owner share claimed: {} of {}", amount, reward_mint);
+        Ok(())
+    }
+
+    /// Initialize the revenue split with the default owner/holders shares (admin only)
+    pub fn initialize_revenue_split(ctx: Context<InitializeRevenueSplit>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let split = &mut ctx.accounts.revenue_split;
+
+        split.vault = vault.key();
+        split.payees = vec![
+            RevenuePayee {
+                label: "owner".to_string(),
+                destination: vault.owner_wallet,
+                share_bps: (OWNER_TAX_SHARE * 100) as u16,
+            },
+            RevenuePayee {
+                label: "holders".to_string(),
+                destination: vault.key(),
+                share_bps: (HOLDERS_TAX_SHARE * 100) as u16,
+            },
+        ];
+        split.pending_payees = Vec::new();
+        split.pending_eta = 0;
+        split.last_updated = Clock::get()?.unix_timestamp;
+
+        msg!("Revenue split initialized");
+        Ok(())
+    }
+
+    /// Queue a new revenue split, applicable after the config timelock (admin only)
+    /// Payees whose destination is the vault keep their share for holder rewards
+    pub fn propose_revenue_split(
+        ctx: Context<ProposeRevenueSplit>,
+        payees: Vec<RevenuePayee>,
+    ) -> Result<()> {
+        validate_revenue_split(&payees)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let split = &mut ctx.accounts.revenue_split;
+        split.pending_payees = payees;
+        split.pending_eta = now
+            .checked_add(CONFIG_TIMELOCK)
+            .ok_or(VaultError::MathOverflow)?;
+
+        emit!(RevenueSplitProposed {
+            timestamp: now,
+            payees: split.pending_payees.clone(),
+            eta: split.pending_eta,
+        });
 
         msg!(
-            "Emergency withdrawal of withheld fees from {} accounts",
-            accounts.len()
+            "Revenue split proposed, applicable at {}",
+            split.pending_eta
         );
+        Ok(())
+    }
 
+    /// Apply a queued revenue split once its timelock has elapsed (permissionless)
+    pub fn apply_revenue_split(ctx: Context<ApplyRevenueSplit>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let split = &mut ctx.accounts.revenue_split;
+
+        require!(split.pending_eta != 0, VaultError::NoPendingConfigChange);
+        require!(now >= split.pending_eta, VaultError::TimelockNotElapsed);
+
+        split.payees = std::mem::take(&mut split.pending_payees);
+        split.pending_eta = 0;
+        split.last_updated = now;
+
+        emit!(RevenueSplitApplied {
+            timestamp: now,
+            payees: split.payees.clone(),
+        });
+
+        msg!("Revenue split applied with {} payees", split.payees.len());
         Ok(())
     }
-}
 
-#[event]
-pub struct DistributionPlanReport {
-    pub timestamp: i64,
-    pub distribution_id: u64,
-    pub withdrawn_miko_amount: u64,
-    pub expected_minimum_reward_amount: u64,
-    pub distribution_hash: [u8; 32],
-    pub keeper: Pubkey,
-}
+    /// Cancel a queued revenue split (admin only)
+    pub fn cancel_revenue_split(ctx: Context<ProposeRevenueSplit>) -> Result<()> {
+        let split = &mut ctx.accounts.revenue_split;
+
+        require!(split.pending_eta != 0, VaultError::NoPendingConfigChange);
+
+        split.pending_payees = Vec::new();
+        split.pending_eta = 0;
+
+        msg!("Pending revenue split cancelled");
+        Ok(())
+    }
+
+    /// Log keeper work on-chain (keeper or logger)
+    /// Records swap and distribution activities for transparency
+    pub fn log_keeper_work(
+        ctx: Context<LogKeeperWork>,
+        work_type: KeeperWorkType,
+        amount: u64,
+        details: String,
+    ) -> Result<()> {
+        load_work_log(&ctx.accounts.keeper_work_log)?.record(
+            ctx.accounts.vault.key(),
+            &KeeperWorkEntry {
+                timestamp: Clock::get()?.unix_timestamp,
+                work_type,
+                amount,
+                details, // Cut to MAX_WORK_DETAILS_LEN bytes when stored
+            },
+        )?;
+
+        msg!("Logged keeper work: {:?}", work_type);
+
+        Ok(())
+    }
+
+    /// Grant a keeper role to a key (admin only)
+    pub fn grant_keeper_role(ctx: Context<GrantKeeperRole>, role: KeeperRoleKind) -> Result<()> {
+        let keeper_role = &mut ctx.accounts.keeper_role;
+        keeper_role.vault = ctx.accounts.vault.key();
+        keeper_role.key = ctx.accounts.grantee.key();
+        keeper_role.role = role;
+        keeper_role.granted_at = Clock::get()?.unix_timestamp;
+
+        msg!("Granted {:?} to {}", role, keeper_role.key);
+        Ok(())
+    }
+
+    /// Revoke a keeper role, returning its rent to the authority (admin only)
+    pub fn revoke_keeper_role(ctx: Context<RevokeKeeperRole>) -> Result<()> {
+        let keeper_role = &ctx.accounts.keeper_role;
+
+        msg!("Revoked {:?} from {}", keeper_role.role, keeper_role.key);
+        Ok(())
+    }
+
+    /// Signal keeper liveness without doing other work (keeper or harvester)
+    pub fn keeper_heartbeat(ctx: Context<KeeperHeartbeat>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.last_keeper_heartbeat = Clock::get()?.unix_timestamp;
+
+        msg!("Keeper heartbeat at {}", vault.last_keeper_heartbeat);
+        Ok(())
+    }
+
+    /// Configure when harvesting falls back to permissionless cranks and the bounty
+    /// paid to crankers from the keeper reserve (admin only). Zero period disables it.
+    pub fn update_liveness_config(
+        ctx: Context<UpdateConfig>,
+        keeper_inactivity_period: i64,
+        crank_bounty_lamports: u64,
+    ) -> Result<()> {
+        require!(
+            keeper_inactivity_period >= 0,
+            VaultError::InvalidLivenessConfig
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.keeper_inactivity_period = keeper_inactivity_period;
+        vault.crank_bounty_lamports = crank_bounty_lamports;
+
+        msg!("Liveness config updated");
+        Ok(())
+    }
+
+    /// Initialize the SOL reserve that refills the keeper's gas wallet (admin only)
+    pub fn initialize_keeper_reserve(
+        ctx: Context<InitializeKeeperReserve>,
+        min_keeper_balance: u64,
+        max_top_up_per_period: u64,
+        top_up_period: i64,
+    ) -> Result<()> {
+        require!(top_up_period > 0, VaultError::InvalidReserveConfig);
+
+        let reserve = &mut ctx.accounts.keeper_reserve;
+        reserve.vault = ctx.accounts.vault.key();
+        reserve.min_keeper_balance = min_keeper_balance;
+        reserve.max_top_up_per_period = max_top_up_per_period;
+        reserve.top_up_period = top_up_period;
+        reserve.period_start = 0;
+        reserve.period_topped_up = 0;
+        reserve.total_deposited = 0;
+        reserve.total_topped_up = 0;
+        reserve.total_bounties_paid = 0;
+
+        msg!("Keeper reserve initialized");
+        Ok(())
+    }
+
+    /// Update keeper reserve limits (admin only)
+    pub fn update_keeper_reserve(
+        ctx: Context<UpdateKeeperReserve>,
+        min_keeper_balance: u64,
+        max_top_up_per_period: u64,
+        top_up_period: i64,
+    ) -> Result<()> {
+        require!(top_up_period > 0, VaultError::InvalidReserveConfig);
+
+        let reserve = &mut ctx.accounts.keeper_reserve;
+        reserve.min_keeper_balance = min_keeper_balance;
+        reserve.max_top_up_per_period = max_top_up_per_period;
+        reserve.top_up_period = top_up_period;
+
+        msg!("Keeper reserve updated");
+        Ok(())
+    }
+
+    /// Deposit SOL into the keeper reserve (permissionless)
+    pub fn deposit_to_reserve(ctx: Context<DepositToReserve>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.keeper_reserve.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let reserve = &mut ctx.accounts.keeper_reserve;
+        reserve.total_deposited = reserve
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        msg!("Deposited {} lamports to keeper reserve", amount);
+        Ok(())
+    }
+
+    /// Refill the keeper's gas wallet from the reserve once it drops below the
+    /// minimum balance, within the per-period cap (keeper only)
+    pub fn top_up_keeper(ctx: Context<TopUpKeeper>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let keeper_info = ctx.accounts.keeper_authority.to_account_info();
+        let reserve_info = ctx.accounts.keeper_reserve.to_account_info();
+        let reserve = &mut ctx.accounts.keeper_reserve;
+
+        require!(
+            keeper_info.lamports() < reserve.min_keeper_balance,
+            VaultError::KeeperBalanceSufficient
+        );
+
+        let period_end = reserve
+            .period_start
+            .checked_add(reserve.top_up_period)
+            .ok_or(VaultError::MathOverflow)?;
+        if now >= period_end {
+            reserve.period_start = now;
+            reserve.period_topped_up = 0;
+        }
+        let period_topped_up = reserve
+            .period_topped_up
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            period_topped_up <= reserve.max_top_up_per_period,
+            VaultError::TopUpCapExceeded
+        );
+
+        // The reserve account itself must stay rent exempt
+        let rent_floor = Rent::get()?.minimum_balance(reserve_info.data_len());
+        let available = reserve_info.lamports().saturating_sub(rent_floor);
+        require!(amount <= available, VaultError::InsufficientReserve);
+
+        reserve.period_topped_up = period_topped_up;
+        reserve.total_topped_up = reserve
+            .total_topped_up
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        reserve_info.sub_lamports(amount)?;
+        keeper_info.add_lamports(amount)?;
+
+        load_work_log(&ctx.accounts.keeper_work_log)?.record(
+            ctx.accounts.vault.key(),
+            &KeeperWorkEntry {
+                timestamp: now,
+                work_type: KeeperWorkType::KeeperTopUp,
+                amount,
+                details: String::new(),
+            },
+        )?;
+
+        msg!("Topped up keeper with {} lamports", amount);
+        Ok(())
+    }
+
+    /// Manage reward exclusions (admin only)
+    pub fn manage_exclusions(
+        ctx: Context<ManageExclusions>,
+        action: ExclusionAction,
+        wallet: Pubkey,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        match action {
+            ExclusionAction::Add => {
+                require!(
+                    vault.reward_exclusions.len() < MAX_EXCLUSIONS,
+                    VaultError::ExclusionListFull
+                );
+                require!(
+                    !vault.reward_exclusions.contains(&wallet),
+                    VaultError::AlreadyExcluded
+                );
+                vault.reward_exclusions.push(wallet);
+                msg!("Added {} to reward exclusions", wallet);
+            }
+            ExclusionAction::Remove => {
+                // The vault's own wallets must never become reward eligible
+                let protected = [
+                    vault.authority,
+                    vault.keeper_authority,
+                    vault.owner_wallet,
+                    vault.key(),
+                    crate::ID,
+                ];
+                require!(!protected.contains(&wallet), VaultError::ProtectedExclusion);
+                vault.reward_exclusions.retain(|&x| x != wallet);
+                msg!("Removed {} from reward exclusions", wallet);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update vault configuration (admin only)
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_owner_wallet: Option<Pubkey>,
+        new_harvest_threshold: Option<u64>,
+        new_authority: Option<Pubkey>,
+        new_keeper_authority: Option<Pubkey>,
+        new_burn_share_bps: Option<u16>,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        // Owner shares accrued so far stay claimable by the previous owner
+        if let Some(owner) = new_owner_wallet {
+            vault.owner_wallet = owner;
+        }
+        if let Some(threshold) = new_harvest_threshold {
+            vault.harvest_threshold = threshold;
+        }
+        if let Some(authority) = new_authority {
+            vault.authority = authority;
+        }
+        if let Some(keeper) = new_keeper_authority {
+            vault.keeper_authority = keeper;
+        }
+        // Applies to fees withdrawn from now on; already earmarked amounts are unchanged
+        if let Some(burn_share_bps) = new_burn_share_bps {
+            require!(
+                burn_share_bps as u64 <= BPS_DENOMINATOR,
+                VaultError::InvalidBurnShare
+            );
+            vault.burn_share_bps = burn_share_bps;
+        }
+
+        msg!("Vault configuration updated");
+        Ok(())
+    }
+
+    /// Update the guards limiting keeper withdrawals (admin only). Zero disables a guard.
+    pub fn update_withdrawal_guards(
+        ctx: Context<UpdateConfig>,
+        min_distribution_interval: i64,
+        withdrawal_window: i64,
+        max_withdrawal_per_window: u64,
+        max_threshold_multiple: u64,
+    ) -> Result<()> {
+        require!(
+            min_distribution_interval >= 0 && withdrawal_window >= 0,
+            VaultError::InvalidWithdrawalGuards
+        );
+        require!(
+            max_withdrawal_per_window == 0 || withdrawal_window > 0,
+            VaultError::InvalidWithdrawalGuards
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.min_distribution_interval = min_distribution_interval;
+        vault.withdrawal_window = withdrawal_window;
+        vault.max_withdrawal_per_window = max_withdrawal_per_window;
+        vault.max_threshold_multiple = max_threshold_multiple;
+        // Restart the rolling window under the new limits
+        vault.window_start = 0;
+        vault.window_withdrawn = 0;
+
+        msg!("Withdrawal guards updated");
+        Ok(())
+    }
+
+    /// Update the emergency withdrawal policy (admin only)
+    /// Once a guardian is set, it must co-sign every policy change, including its own
+    /// replacement, so a compromised authority cannot allowlist its own destination
+    pub fn update_emergency_policy(
+        ctx: Context<UpdateEmergencyPolicy>,
+        guardian: Pubkey,
+        destinations: Vec<Pubkey>,
+        max_bps: u16,
+        cooldown: i64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        if vault.guardian != Pubkey::default() {
+            let cosigned = ctx
+                .accounts
+                .guardian
+                .as_ref()
+                .is_some_and(|signer| signer.key() == vault.guardian);
+            require!(cosigned, VaultError::GuardianSignatureRequired);
+        }
+        require!(
+            destinations.len() <= MAX_EMERGENCY_DESTINATIONS,
+            VaultError::InvalidEmergencyPolicy
+        );
+        require!(
+            max_bps as u64 <= BPS_DENOMINATOR && cooldown >= 0,
+            VaultError::InvalidEmergencyPolicy
+        );
+
+        vault.guardian = guardian;
+        vault.emergency_destinations = destinations;
+        vault.emergency_max_bps = max_bps;
+        vault.emergency_cooldown = cooldown;
+
+        msg!("Emergency policy updated");
+        Ok(())
+    }
+
+    /// Open a ledger for one vault-owned token account, starting from its current
+    /// balance (keeper or distributor)
+    pub fn initialize_asset_ledger(ctx: Context<InitializeAssetLedger>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mut ledger = ctx.accounts.asset_ledger.load_init()?;
+
+        ledger.version = ASSET_LEDGER_VERSION;
+        ledger.vault = ctx.accounts.vault.key();
+        ledger.mint = ctx.accounts.mint.key();
+        ledger.token_account = ctx.accounts.vault_token_account.key();
+        ledger.opening_balance = ctx.accounts.vault_token_account.amount;
+        ledger.opened_at = now;
+
+        msg!(
+            "Asset ledger opened for {} with balance {}",
+            ledger.mint,
+            ledger.opening_balance
+        );
+        Ok(())
+    }
+
+    /// Compare a ledger's recorded balance with its token account (permissionless)
+    /// Direct transfers into the vault show up as surplus, unrecorded outflows as deficit
+    pub fn reconcile_asset_ledger(ctx: Context<ReconcileAssetLedger>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let actual_balance = ctx.accounts.vault_token_account.amount;
+        let mut ledger = ctx.accounts.asset_ledger.load_mut()?;
+        let ledger_balance = ledger.balance()?;
+        ledger.last_reconciled_at = now;
+
+        emit!(AssetLedgerReconciled {
+            timestamp: now,
+            mint: ledger.mint,
+            token_account: ledger.token_account,
+            ledger_balance,
+            actual_balance,
+            surplus: actual_balance.saturating_sub(ledger_balance),
+            deficit: ledger_balance.saturating_sub(actual_balance),
+        });
+
+        msg!(
+            "Ledger balance {}, actual balance {}",
+            ledger_balance,
+            actual_balance
+        );
+        Ok(())
+    }
+
+    /// Check the vault's MIKO accounting against its token account (permissionless)
+    /// Every harvested token must be distributed, burned, withdrawn in an emergency or
+    /// still held; a deficit or a pending burn the vault cannot cover fails the check
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let actual_balance = ctx.accounts.vault_token_account.amount;
+        let vault = &mut ctx.accounts.vault;
+
+        let accounted_outflows = (vault.total_rewards_distributed as u128)
+            + vault.total_burned as u128
+            + vault.total_emergency_withdrawn as u128;
+        let harvested = vault.total_fees_harvested as u128;
+        let overdrawn = accounted_outflows.saturating_sub(harvested);
+        let expected_balance = harvested.saturating_sub(accounted_outflows);
+
+        let surplus = (actual_balance as u128).saturating_sub(expected_balance) as u64;
+        let deficit = expected_balance.saturating_sub(actual_balance as u128) as u64;
+        let passed = overdrawn == 0 && deficit == 0 && vault.pending_burn_amount <= actual_balance;
+
+        let report = ReconciliationReport {
+            timestamp: Clock::get()?.unix_timestamp,
+            expected_balance: expected_balance as u64,
+            actual_balance,
+            surplus,
+            deficit,
+            overdrawn: u64::try_from(overdrawn).map_err(|_| VaultError::MathOverflow)?,
+            passed,
+        };
+        vault.last_reconciliation = report.clone();
+
+        emit!(VaultReconciled {
+            timestamp: report.timestamp,
+            total_fees_harvested: vault.total_fees_harvested,
+            total_rewards_distributed: vault.total_rewards_distributed,
+            total_burned: vault.total_burned,
+            total_emergency_withdrawn: vault.total_emergency_withdrawn,
+            expected_balance: report.expected_balance,
+            actual_balance,
+            surplus: report.surplus,
+            deficit: report.deficit,
+            overdrawn: report.overdrawn,
+            passed: report.passed,
+        });
+
+        msg!(
+            "Reconciliation {}: expected {}, actual {}",
+            if report.passed { "passed" } else { "failed" },
+            report.expected_balance,
+            actual_balance
+        );
+        Ok(())
+    }
+
+    /// Whether `wallet` is excluded from rewards, returned as a bool (view)
+    pub fn is_excluded(ctx: Context<ViewVault>, wallet: Pubkey) -> Result<bool> {
+        Ok(ctx.accounts.vault.is_excluded(&wallet))
+    }
+
+    /// Vault balances, pending fees and accounting totals, returned as a
+    /// `VaultSummary` (view)
+    pub fn get_vault_summary(ctx: Context<GetVaultSummary>) -> Result<VaultSummary> {
+        let mint_data = ctx.accounts.token_mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+        let mint_withheld = u64::from(
+            mint_state
+                .get_extension::<TransferFeeConfig>()?
+                .withheld_amount,
+        );
+
+        Ok(ctx
+            .accounts
+            .vault
+            .summary(ctx.accounts.vault_token_account.amount, mint_withheld))
+    }
+
+    /// Rewrite a vault created under an unversioned layout into the current one,
+    /// reallocating in place (admin only)
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let (from_version, legacy) = read_legacy_vault(&vault_info.try_borrow_data()?)?;
+
+        require_keys_eq!(
+            legacy.authority,
+            ctx.accounts.authority.key(),
+            VaultError::Unauthorized
+        );
+        let (expected_vault, _) =
+            Pubkey::find_program_address(&[VAULT_SEED, legacy.token_mint.as_ref()], &crate::ID);
+        require_keys_eq!(
+            expected_vault,
+            vault_info.key(),
+            VaultError::UnsupportedAccountLayout
+        );
+
+        write_migrated(
+            &vault_info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &legacy.into_current(now),
+        )?;
+
+        emit!(AccountMigrated {
+            timestamp: now,
+            account: vault_info.key(),
+            from_version,
+            to_version: VAULT_STATE_VERSION,
+        });
+
+        msg!(
+            "Vault migrated from layout {} to {}",
+            from_version,
+            VAULT_STATE_VERSION
+        );
+        Ok(())
+    }
+
+    /// Rewrite an unversioned pool registry into the current layout (admin only)
+    /// The vault must be migrated first
+    pub fn migrate_pool_registry(ctx: Context<MigratePoolRegistry>) -> Result<()> {
+        let registry_info = ctx.accounts.pool_registry.to_account_info();
+        let legacy: PoolRegistryLayoutV0 = read_legacy(
+            &registry_info.try_borrow_data()?,
+            PoolRegistry::DISCRIMINATOR,
+            POOL_REGISTRY_LAYOUT_V0_LEN,
+        )?;
+
+        let registry = PoolRegistry {
+            version: POOL_REGISTRY_VERSION,
+            vault: legacy.vault,
+            pools: legacy.pools,
+            reserved: [0; 64],
+        };
+        write_migrated(
+            &registry_info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &registry,
+        )?;
+
+        emit!(AccountMigrated {
+            timestamp: Clock::get()?.unix_timestamp,
+            account: registry_info.key(),
+            from_version: 0,
+            to_version: POOL_REGISTRY_VERSION,
+        });
+
+        msg!("Pool registry migrated to layout {}", POOL_REGISTRY_VERSION);
+        Ok(())
+    }
+
+    /// Rewrite a keeper work log in a legacy Borsh layout into the current zero-copy
+    /// layout (admin only)
+    /// The vault must be migrated first
+    pub fn migrate_keeper_work_log(ctx: Context<MigrateKeeperWorkLog>) -> Result<()> {
+        let log_info = ctx.accounts.keeper_work_log.to_account_info();
+        let (from_version, legacy) = read_legacy_work_log(&log_info.try_borrow_data()?)?;
+
+        resize_for_migration(
+            &log_info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            KEEPER_WORK_LOG_LEN,
+        )?;
+        {
+            let mut data = log_info.try_borrow_mut_data()?;
+            data.fill(0);
+            data[..8].copy_from_slice(KeeperWorkLog::DISCRIMINATOR);
+            let log: &mut KeeperWorkLog = bytemuck::from_bytes_mut(&mut data[8..]);
+            log.version = KEEPER_WORK_LOG_VERSION;
+            log.vault = legacy.vault;
+            for entry in &legacy.entries {
+                log.record(legacy.vault, entry)?;
+            }
+        }
+
+        emit!(AccountMigrated {
+            timestamp: Clock::get()?.unix_timestamp,
+            account: log_info.key(),
+            from_version,
+            to_version: KEEPER_WORK_LOG_VERSION,
+        });
+
+        msg!(
+            "Keeper work log migrated to layout {}",
+            KEEPER_WORK_LOG_VERSION
+        );
+        Ok(())
+    }
+
+    /// Rewrite an asset ledger in the Borsh layout into the current zero-copy layout,
+    /// keeping its totals and entries (admin only)
+    pub fn migrate_asset_ledger(ctx: Context<MigrateAssetLedger>) -> Result<()> {
+        let ledger_info = ctx.accounts.asset_ledger.to_account_info();
+        let legacy: AssetLedgerLayoutV0 = read_legacy(
+            &ledger_info.try_borrow_data()?,
+            AssetLedger::DISCRIMINATOR,
+            ASSET_LEDGER_LAYOUT_V0_LEN,
+        )?;
+
+        resize_for_migration(
+            &ledger_info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            ASSET_LEDGER_LEN,
+        )?;
+        {
+            let mut data = ledger_info.try_borrow_mut_data()?;
+            data.fill(0);
+            data[..8].copy_from_slice(AssetLedger::DISCRIMINATOR);
+            let ledger: &mut AssetLedger = bytemuck::from_bytes_mut(&mut data[8..]);
+            ledger.version = ASSET_LEDGER_VERSION;
+            ledger.vault = legacy.vault;
+            ledger.mint = legacy.mint;
+            ledger.token_account = legacy.token_account;
+            ledger.opening_balance = legacy.opening_balance;
+            ledger.opened_at = legacy.opened_at;
+            ledger.last_reconciled_at = legacy.last_reconciled_at;
+            for entry in &legacy.entries {
+                ledger.push(entry)?;
+            }
+            // The totals cover entries the Borsh layout had already dropped
+            ledger.total_inflow = legacy.total_inflow;
+            ledger.total_outflow = legacy.total_outflow;
+        }
+
+        emit!(AccountMigrated {
+            timestamp: Clock::get()?.unix_timestamp,
+            account: ledger_info.key(),
+            from_version: 0,
+            to_version: ASSET_LEDGER_VERSION,
+        });
+
+        msg!("Asset ledger migrated to layout {}", ASSET_LEDGER_VERSION);
+        Ok(())
+    }
+
+    /// Close the keeper work log, sending its rent to `rent_receiver` (admin only)
+    pub fn close_keeper_work_log(ctx: Context<CloseKeeperWorkLog>) -> Result<()> {
+        msg!(
+            "Closed keeper work log, rent to {}",
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close the pool registry, sending its rent to `rent_receiver` (admin only)
+    pub fn close_pool_registry(ctx: Context<ClosePoolRegistry>) -> Result<()> {
+        msg!(
+            "Closed pool registry, rent to {}",
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close the revenue split, sending its rent to `rent_receiver` (admin only)
+    /// Swaps need a revenue split, so only close it when retiring the vault
+    pub fn close_revenue_split(ctx: Context<CloseRevenueSplit>) -> Result<()> {
+        msg!(
+            "Closed revenue split, rent to {}",
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close a settled or acknowledged distribution record, sending its rent to
+    /// `rent_receiver` (admin only)
+    pub fn close_distribution_record(ctx: Context<CloseDistributionRecord>) -> Result<()> {
+        let record = &ctx.accounts.distribution_record;
+        require!(
+            matches!(
+                record.status,
+                DistributionStatus::Settled | DistributionStatus::ShortfallAcknowledged
+            ),
+            VaultError::DistributionStillOpen
+        );
+
+        msg!(
+            "Closed distribution record {}, rent to {}",
+            record.distribution_id,
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close a reward index once every position has unstaked and claimed, sending its
+    /// rent to `rent_receiver` (admin only)
+    /// Rewards left over are rounding dust no position can claim; they are swept to
+    /// `dust_receiver`.
+    pub fn close_reward_index(ctx: Context<CloseRewardIndex>) -> Result<()> {
+        let index = &ctx.accounts.reward_index;
+        require!(index.open_positions == 0, VaultError::RewardsOutstanding);
+        let dust = index.outstanding()?;
+
+        if dust > 0 {
+            let token_mint_key = ctx.accounts.vault.token_mint;
+            let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
+            let signer_seeds = &[&seeds[..]];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: ctx.accounts.vault_reward_token_account.to_account_info(),
+                        mint: ctx.accounts.reward_mint.to_account_info(),
+                        to: ctx.accounts.dust_receiver.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                dust,
+                ctx.accounts.reward_mint.decimals,
+            )?;
+
+            record_ledger_entry(
+                &ctx.accounts.asset_ledger,
+                &ctx.accounts.vault_reward_token_account.key(),
+                LedgerEntryKind::DustSweep,
+                dust,
+                ctx.accounts.vault.distribution_id,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+
+        msg!(
+            "Closed reward index for {}, swept {} dust, rent to {}",
+            index.reward_mint,
+            dust,
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close an asset ledger, sending its rent to `rent_receiver` (admin only)
+    pub fn close_asset_ledger(ctx: Context<CloseAssetLedger>) -> Result<()> {
+        msg!(
+            "Closed asset ledger for {}, rent to {}",
+            ctx.accounts.asset_ledger.load()?.mint,
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close the keeper reserve, sending its rent and remaining SOL to `rent_receiver`
+    /// (admin only)
+    pub fn close_keeper_reserve(ctx: Context<CloseKeeperReserve>) -> Result<()> {
+        msg!(
+            "Closed keeper reserve, {} lamports to {}",
+            ctx.accounts.keeper_reserve.to_account_info().lamports(),
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Close an emptied vault, sending its rent to `rent_receiver` (admin only)
+    /// Requires a zero MIKO balance and no open distribution. Owner shares are held
+    /// outside the vault and stay claimable after it closes. A tombstone paid by the
+    /// authority keeps the vault PDA from being re-initialized under a new authority,
+    /// which would inherit every account still owned by it.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            VaultError::VaultNotEmpty
+        );
+        require!(
+            vault.unsettled_distribution_id == 0 && vault.unacknowledged_shortfall_id == 0,
+            VaultError::DistributionStillOpen
+        );
+
+        let tombstone = &mut ctx.accounts.vault_tombstone;
+        tombstone.token_mint = vault.token_mint;
+        tombstone.authority = vault.authority;
+        tombstone.closed_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Closed vault for {}, rent to {}",
+            vault.token_mint,
+            ctx.accounts.rent_receiver.key()
+        );
+        Ok(())
+    }
+
+    /// Recover tokens of any mint other than MIKO, or lamports above rent, sent to the
+    /// vault PDA by mistake (admin only). Pass the token accounts for a token recovery
+    /// and omit them for lamports. Reward mints are refused: the dial's current one, any
+    /// with a reward index, and that of the latest distribution, whose swap proceeds may
+    /// not be funded yet.
+    pub fn recover_foreign_asset(ctx: Context<RecoverForeignAsset>, amount: u64) -> Result<()> {
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        let accounts = ctx.accounts;
+        let vault_key = accounts.vault.key();
+        let destination = accounts.destination.key();
+
+        let recovered_mint = match (
+            &accounts.mint,
+            &accounts.vault_foreign_token_account,
+            &accounts.token_program,
+        ) {
+            (Some(mint), Some(source), Some(token_program)) => {
+                require!(
+                    mint.key() != accounts.vault.token_mint,
+                    VaultError::CannotRecoverVaultMint
+                );
+                require!(
+                    source.owner == vault_key && source.mint == mint.key(),
+                    VaultError::InvalidVaultTokenAccount
+                );
+
+                check_not_reward_mint(
+                    &accounts.vault,
+                    &mint.key(),
+                    accounts.reward_index.as_ref(),
+                    accounts.dial_state.as_ref(),
+                    accounts.distribution_record.as_ref(),
+                )?;
+                require!(
+                    amount <= source.amount,
+                    VaultError::InsufficientRecoverableBalance
+                );
+
+                let token_mint_key = accounts.vault.token_mint;
+                let seeds = &[VAULT_SEED, token_mint_key.as_ref(), &[ctx.bumps.vault]];
+                let signer_seeds = &[&seeds[..]];
+
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        token_interface::TransferChecked {
+                            from: source.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: accounts.destination.to_account_info(),
+                            authority: accounts.vault.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+
+                mint.key()
+            }
+            (None, None, None) => {
+                let vault_info = accounts.vault.to_account_info();
+                let rent_floor = Rent::get()?.minimum_balance(vault_info.data_len());
+                let excess = vault_info.lamports().saturating_sub(rent_floor);
+                require!(amount <= excess, VaultError::InsufficientRecoverableBalance);
+
+                vault_info.sub_lamports(amount)?;
+                accounts.destination.add_lamports(amount)?;
+
+                Pubkey::default()
+            }
+            _ => return err!(VaultError::InvalidRecoveryAccounts),
+        };
+
+        emit!(ForeignAssetRecovered {
+            timestamp: Clock::get()?.unix_timestamp,
+            authority: accounts.authority.key(),
+            mint: recovered_mint,
+            destination,
+            amount,
+        });
+
+        msg!(
+            "Recovered {} of {} to {}",
+            amount,
+            recovered_mint,
+            destination
+        );
+        Ok(())
+    }
+
+    /// Emergency withdraw from vault (admin only)
+    /// Limited to allowlisted destinations, a share of the vault balance and a cooldown
+    /// unless the guardian co-signs
+    /// Remaining accounts: extra transfer-hook accounts when the mint has a hook
+    pub fn emergency_withdraw_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyWithdraw<'info>>,
+        amount: u64,
+        reason_code: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let destination = ctx.accounts.destination_token_account.key();
+        let guardian_bypass = check_emergency_policy(
+            &mut ctx.accounts.vault,
+            &destination,
+            &ctx.accounts.guardian,
+            now,
+        )?;
+
+        if !guardian_bypass {
+            check_emergency_cap(
+                &ctx.accounts.vault,
+                ctx.accounts.vault_token_account.amount,
+                amount,
+            )?;
+        }
+
+        let vault = &ctx.accounts.vault;
+
+        let seeds = &[VAULT_SEED, vault.token_mint.as_ref(), &[ctx.bumps.vault]];
+        let signer_seeds = &[&seeds[..]];
+
+        // Get mint decimals
+        let mint_data = ctx.accounts.token_mint.to_account_info();
+        let mint_data_borrowed = mint_data.try_borrow_data()?;
+        let mint_info = StateWithExtensions::<MintState>::unpack(&mint_data_borrowed)?;
+        let decimals = mint_info.base.decimals;
+        drop(mint_data_borrowed);
+
+        invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.vault_token_account.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.destination_token_account.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            decimals,
+            signer_seeds,
+        )?;
+
+        emit!(EmergencyWithdrawal {
+            timestamp: now,
+            authority: ctx.accounts.authority.key(),
+            destination,
+            mint: ctx.accounts.token_mint.key(),
+            amount,
+            reason_code,
+            guardian_bypass,
+        });
+
+        // Only MIKO outflows count against harvested fees in `reconcile`
+        let vault = &mut ctx.accounts.vault;
+        if ctx.accounts.token_mint.key() == vault.token_mint {
+            vault.total_emergency_withdrawn = vault
+                .total_emergency_withdrawn
+                .checked_add(amount)
+                .ok_or(VaultError::MathOverflow)?;
+        }
+
+        let distribution_id = ctx.accounts.vault.distribution_id;
+        record_ledger_entry(
+            &ctx.accounts.asset_ledger,
+            &ctx.accounts.vault_token_account.key(),
+            LedgerEntryKind::EmergencyOut,
+            amount,
+            distribution_id,
+            now,
+        )?;
+
+        msg!("Emergency withdrawal: {} tokens", amount);
+
+        Ok(())
+    }
+
+    /// Emergency withdraw withheld from mint (admin only)
+    /// Limited to allowlisted destinations, a share of the vault holdings and a cooldown
+    /// unless the guardian co-signs
+    pub fn emergency_withdraw_withheld<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyWithdrawWithheld<'info>>,
+        accounts: Vec<Pubkey>,
+        reason_code: u16,
+    ) -> Result<()> {
+        require!(
+            !accounts.is_empty() && accounts.len() <= 20,
+            VaultError::InvalidBatchSize
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let destination = ctx.accounts.destination_token_account.key();
+        let guardian_bypass = check_emergency_policy(
+            &mut ctx.accounts.vault,
+            &destination,
+            &ctx.accounts.guardian,
+            now,
+        )?;
+
+        let vault = &ctx.accounts.vault;
+
+        let seeds = &[VAULT_SEED, vault.token_mint.as_ref(), &[ctx.bumps.vault]];
+        let signer_seeds = &[&seeds[..]];
+
+        let account_refs: Vec<&Pubkey> = accounts.iter().collect();
+
+        let ix = withdraw_withheld_tokens_from_accounts(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_mint.key(),
+            &ctx.accounts.destination_token_account.key(),
+            &vault.key(),
+            &[],
+            &account_refs,
+        )?;
+
+        let mut account_infos = vec![
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.destination_token_account.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+        ];
+        account_infos.extend(ctx.remaining_accounts.iter().cloned());
+
+        let destination_balance_before = ctx.accounts.destination_token_account.amount;
+        invoke_signed(&ix, &account_infos, signer_seeds)?;
+        ctx.accounts.destination_token_account.reload()?;
+        let amount = ctx
+            .accounts
+            .destination_token_account
+            .amount
+            .checked_sub(destination_balance_before)
+            .ok_or(VaultError::MathOverflow)?;
+
+        // Withheld fees count as vault holdings: harvesting would have moved them in
+        if !guardian_bypass {
+            let holdings = ctx
+                .accounts
+                .vault_token_account
+                .amount
+                .checked_add(amount)
+                .ok_or(VaultError::MathOverflow)?;
+            check_emergency_cap(&ctx.accounts.vault, holdings, amount)?;
+        }
+
+        emit!(EmergencyWithdrawal {
+            timestamp: now,
+            authority: ctx.accounts.authority.key(),
+            destination,
+            mint: ctx.accounts.token_mint.key(),
+            amount,
+            reason_code,
+            guardian_bypass,
+        });
+
+        // The fees are booked as harvested and withdrawn at once, so `reconcile` and
+        // the ledger see the withdrawal without expecting it in the vault balance
+        let vault = &mut ctx.accounts.vault;
+        vault.total_fees_harvested = vault
+            .total_fees_harvested
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        vault.total_emergency_withdrawn = vault
+            .total_emergency_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        let distribution_id = ctx.accounts.vault.distribution_id;
+        for kind in [LedgerEntryKind::FeeHarvest, LedgerEntryKind::EmergencyOut] {
+            record_ledger_entry(
+                &ctx.accounts.asset_ledger,
+                &ctx.accounts.vault_token_account.key(),
+                kind,
+                amount,
+                distribution_id,
+                now,
+            )?;
+        }
+
+        msg!(
+            "Emergency withdrawal of withheld fees from {} accounts",
+            accounts.len()
+        );
+
+        Ok(())
+    }
+}
+
+// Swap adapters
+
+/// Accounts of a vault-executed swap that are supplied by the vault itself
+pub struct SwapLegs<'info> {
+    pub amm_program: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub input_token_account: AccountInfo<'info>,
+    pub output_token_account: AccountInfo<'info>,
+    pub input_token_program: AccountInfo<'info>,
+    pub input_token_mint: AccountInfo<'info>,
+}
+
+impl SwapAdapter {
+    /// Raydium CPMM `swap_base_input` discriminator
+    const RAYDIUM_CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            SwapAdapter::RaydiumCpmm => RAYDIUM_CPMM_PROGRAM_ID,
+        }
+    }
+
+    /// Number of AMM-specific accounts expected in `remaining_accounts`
+    ///
+    /// RaydiumCpmm: [authority, amm_config, pool_state, input_vault, output_vault,
+    /// output_token_program, output_token_mint, observation_state]
+    pub fn accounts_len(&self) -> usize {
+        match self {
+            SwapAdapter::RaydiumCpmm => 8,
+        }
+    }
+
+    /// Pool account that must be present in the pool registry
+    pub fn pool<'a, 'info>(&self, accounts: &'a [AccountInfo<'info>]) -> &'a AccountInfo<'info> {
+        match self {
+            SwapAdapter::RaydiumCpmm => &accounts[2],
+        }
+    }
+
+    /// Build the swap CPI and the account infos it needs
+    pub fn swap_instruction<'info>(
+        &self,
+        legs: SwapLegs<'info>,
+        accounts: &[AccountInfo<'info>],
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> (Instruction, Vec<AccountInfo<'info>>) {
+        match self {
+            SwapAdapter::RaydiumCpmm => {
+                let account_infos = vec![
+                    legs.payer,
+                    accounts[0].clone(),
+                    accounts[1].clone(),
+                    accounts[2].clone(),
+                    legs.input_token_account,
+                    legs.output_token_account,
+                    accounts[3].clone(),
+                    accounts[4].clone(),
+                    legs.input_token_program,
+                    accounts[5].clone(),
+                    legs.input_token_mint,
+                    accounts[6].clone(),
+                    accounts[7].clone(),
+                ];
+                let writable = [
+                    false, false, false, true, true, true, true, true, false, false, false, false,
+                    true,
+                ];
+                let metas = account_infos
+                    .iter()
+                    .zip(writable)
+                    .enumerate()
+                    .map(|(i, (info, is_writable))| AccountMeta {
+                        pubkey: info.key(),
+                        is_signer: i == 0,
+                        is_writable,
+                    })
+                    .collect();
+
+                let mut data = Self::RAYDIUM_CPMM_SWAP_BASE_INPUT.to_vec();
+                data.extend_from_slice(&amount_in.to_le_bytes());
+                data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+                let mut infos = account_infos;
+                infos.push(legs.amm_program);
+
+                (
+                    Instruction {
+                        program_id: self.program_id(),
+                        accounts: metas,
+                        data,
+                    },
+                    infos,
+                )
+            }
+        }
+    }
+}
+
+#[event]
+pub struct DistributionPlanReport {
+    pub timestamp: i64,
+    pub distribution_id: u64,
+    pub withdrawn_miko_amount: u64,
+    pub expected_minimum_reward_amount: u64,
+    pub distribution_hash: [u8; 32],
+    pub keeper: Pubkey,
+}
+
+#[event]
+pub struct DistributionSettled {
+    pub timestamp: i64,
+    pub distribution_id: u64,
+    pub expected_minimum_reward_amount: u64,
+    pub reward_amount_received: u64,
+    pub shortfall: bool,
+}
+
+#[event]
+pub struct OwnerShareClaimed {
+    pub timestamp: i64,
+    pub owner: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HolderWeightReport {
+    pub timestamp: i64,
+    pub holder: Pubkey,
+    pub token_account: Pubkey,
+    pub reward_mint: Pubkey,
+    pub balance: u64,
+    pub balance_seconds: u128,
+    pub tracked_since: i64,
+    pub staked: u64,
+    pub credited: u64,
+    pub excluded: bool,
+}
+
+#[event]
+pub struct ShortfallAcknowledged {
+    pub timestamp: i64,
+    pub distribution_id: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct FeesBurned {
+    pub timestamp: i64,
+    pub amount: u64,
+    pub total_burned: u64,
+}
+
+#[event]
+pub struct RevenueSplitProposed {
+    pub timestamp: i64,
+    pub payees: Vec<RevenuePayee>,
+    pub eta: i64,
+}
+
+#[event]
+pub struct RevenueSplitApplied {
+    pub timestamp: i64,
+    pub payees: Vec<RevenuePayee>,
+}
+
+#[event]
+pub struct WithdrawalGuardTripped {
+    pub timestamp: i64,
+    pub keeper: Pubkey,
+    pub guard: WithdrawalGuard,
+    pub requested: u64,
+    pub limit: u64,
+}
+
+#[event]
+pub struct CrankExecuted {
+    pub timestamp: i64,
+    pub caller: Pubkey,
+    pub last_keeper_heartbeat: i64,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct EmergencyWithdrawal {
+    pub timestamp: i64,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub reason_code: u16,
+    pub guardian_bypass: bool,
+}
+
+#[event]
+pub struct ForeignAssetRecovered {
+    pub timestamp: i64,
+    pub authority: Pubkey,
+    /// Default pubkey for lamport recoveries
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AssetLedgerReconciled {
+    pub timestamp: i64,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub ledger_balance: u64,
+    pub actual_balance: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+}
+
+#[event]
+pub struct VaultReconciled {
+    pub timestamp: i64,
+    pub total_fees_harvested: u64,
+    pub total_rewards_distributed: u64,
+    pub total_burned: u64,
+    pub total_emergency_withdrawn: u64,
+    pub expected_balance: u64,
+    pub actual_balance: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+    pub overdrawn: u64,
+    pub passed: bool,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub timestamp: i64,
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+// Helper functions
+
+/// Decode an unversioned vault, upgrading the launch layout on the way
+fn read_legacy_vault(data: &[u8]) -> Result<(u8, VaultStateLayoutV1)> {
+    require!(
+        data.starts_with(VaultState::DISCRIMINATOR),
+        VaultError::UnsupportedAccountLayout
+    );
+    let mut fields = &data[VaultState::DISCRIMINATOR.len()..];
+    match data.len() {
+        VAULT_LAYOUT_V0_LEN => Ok((0, VaultStateLayoutV0::deserialize(&mut fields)?.into())),
+        VAULT_LAYOUT_V1_LEN => Ok((1, VaultStateLayoutV1::deserialize(&mut fields)?)),
+        _ => err!(VaultError::UnsupportedAccountLayout),
+    }
+}
+
+/// Decode a keeper work log still in one of the Borsh layouts, as the last of them,
+/// with the version it was read from
+fn read_legacy_work_log(data: &[u8]) -> Result<(u8, KeeperWorkLogLayoutV1)> {
+    require!(
+        data.starts_with(KeeperWorkLog::DISCRIMINATOR),
+        VaultError::UnsupportedAccountLayout
+    );
+    let mut fields = &data[KeeperWorkLog::DISCRIMINATOR.len()..];
+    match data.len() {
+        KEEPER_WORK_LOG_LAYOUT_V0_LEN => {
+            Ok((0, KeeperWorkLogLayoutV0::deserialize(&mut fields)?.into()))
+        }
+        KEEPER_WORK_LOG_LAYOUT_V1_LEN => Ok((1, KeeperWorkLogLayoutV1::deserialize(&mut fields)?)),
+        _ => err!(VaultError::UnsupportedAccountLayout),
+    }
+}
+
+/// Reward token the smart dial currently selects
+fn dial_reward_token(dial_state: &AccountLoader<DialState>) -> Result<Pubkey> {
+    // A dial still in a Borsh layout is shorter than the zero-copy account
+    require!(
+        dial_state.as_ref().data_len() == DIAL_STATE_LEN,
+        VaultError::AccountNotMigrated
+    );
+    Ok(dial_state.load()?.current_reward_token)
+}
+
+/// Zero-copy view of the keeper work log, which `init_if_needed` may have just
+/// created
+fn load_work_log<'a>(
+    log: &'a AccountLoader<KeeperWorkLog>,
+) -> Result<std::cell::RefMut<'a, KeeperWorkLog>> {
+    let info = log.as_ref();
+    // Borsh layouts share the discriminator but not the size
+    require!(
+        info.data_len() == KEEPER_WORK_LOG_LEN,
+        VaultError::AccountNotMigrated
+    );
+    // A log created in this instruction gets its discriminator on exit
+    let created = info.try_borrow_data()?[..8] == [0; 8];
+    if created {
+        log.load_init()
+    } else {
+        log.load_mut()
+    }
+}
+
+/// Decode an account still in the single unversioned layout of `T`
+fn read_legacy<T: AnchorDeserialize>(
+    data: &[u8],
+    discriminator: &[u8],
+    legacy_len: usize,
+) -> Result<T> {
+    require!(
+        data.starts_with(discriminator) && data.len() == legacy_len,
+        VaultError::UnsupportedAccountLayout
+    );
+    Ok(T::deserialize(&mut &data[discriminator.len()..])?)
+}
+
+/// Resize a legacy account to the current layout of `T` and write `state` over it,
+/// topping up rent from `payer`
+fn write_migrated<'info, T: AccountSerialize + Space>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    state: &T,
+) -> Result<()> {
+    resize_for_migration(account, payer, system_program, 8 + T::INIT_SPACE)?;
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// Resize a legacy account to `new_len`, topping up rent from `payer`
+fn resize_for_migration<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// Record a movement on the asset ledger of `token_account`. The ledger PDA must be
+/// passed so it cannot be skipped once opened; it may be uninitialized when the asset
+/// never had one.
+fn record_ledger_entry(
+    ledger: &UncheckedAccount,
+    token_account: &Pubkey,
+    kind: LedgerEntryKind,
+    amount: u64,
+    distribution_id: u64,
+    timestamp: i64,
+) -> Result<()> {
+    if ledger.owner != &crate::ID || ledger.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut data = ledger.try_borrow_mut_data()?;
+    // The Borsh layout shares the discriminator but not the size
+    require!(
+        data.starts_with(AssetLedger::DISCRIMINATOR) && data.len() == ASSET_LEDGER_LEN,
+        VaultError::AccountNotMigrated
+    );
+    let asset_ledger: &mut AssetLedger = bytemuck::from_bytes_mut(&mut data[8..]);
+    require_keys_eq!(
+        asset_ledger.token_account,
+        *token_account,
+        VaultError::InvalidAssetLedger
+    );
+    asset_ledger.record(&LedgerEntry {
+        timestamp,
+        distribution_id,
+        kind,
+        amount,
+    })
+}
+
+/// Refuse recovery of reward tokens: the dial's current reward mint, a mint with a
+/// reward index, or the latest distribution's reward mint. The reward index PDA must
+/// be passed so it cannot be skipped; it may be uninitialized when the mint never had
+/// one.
+fn check_not_reward_mint(
+    vault: &Account<VaultState>,
+    mint: &Pubkey,
+    reward_index: Option<&UncheckedAccount>,
+    dial_state: Option<&AccountLoader<DialState>>,
+    distribution_record: Option<&UncheckedAccount>,
+) -> Result<()> {
+    let (Some(reward_index), Some(dial_state), Some(distribution_record)) =
+        (reward_index, dial_state, distribution_record)
+    else {
+        return err!(VaultError::InvalidRecoveryAccounts);
+    };
+    let (expected, _) = Pubkey::find_program_address(
+        &[REWARD_INDEX_SEED, vault.key().as_ref(), mint.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(
+        reward_index.key(),
+        expected,
+        VaultError::InvalidRecoveryAccounts
+    );
+
+    let index_opened = reward_index.owner == &crate::ID && !reward_index.data_is_empty();
+    require!(
+        !index_opened && dial_reward_token(dial_state)? != *mint,
+        VaultError::CannotRecoverRewardMint
+    );
+
+    if distribution_record.owner == &crate::ID && !distribution_record.data_is_empty() {
+        let data = distribution_record.try_borrow_data()?;
+        let record = DistributionRecord::try_deserialize(&mut &data[..])?;
+        require!(
+            record.reward_mint != *mint,
+            VaultError::CannotRecoverRewardMint
+        );
+    }
+    Ok(())
+}
+
+/// Reward tokens a distribution's payee shares are paid out of
+pub struct RewardSource<'info> {
+    pub token_program: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub decimals: u8,
+    pub authority: AccountInfo<'info>,
+}
+
+/// Move each payee's share of `amount` into its owner share account and return the
+/// total moved. `owner_shares` holds one account per payee not routed to the vault,
+/// in split order; shares routed to the vault stay in the source account.
+fn pay_owner_shares<'info>(
+    payees: &[RevenuePayee],
+    vault: &Pubkey,
+    amount: u64,
+    owner_shares: &[AccountInfo<'info>],
+    source: RewardSource<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let mut owner_shares = owner_shares.iter();
+    let mut paid = 0u64;
+
+    for payee in payees.iter().filter(|payee| &payee.destination != vault) {
+        let owner_share = owner_shares
+            .next()
+            .ok_or(VaultError::InvalidOwnerShareAccounts)?;
+        let (expected, _) = Pubkey::find_program_address(
+            &[
+                OWNER_SHARE_SEED,
+                vault.as_ref(),
+                payee.destination.as_ref(),
+                source.mint.key.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            owner_share.key(),
+            expected,
+            VaultError::InvalidOwnerShareAccounts
+        );
+
+        let share = payee.share_of(amount)?;
+        if share == 0 {
+            continue;
+        }
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                source.token_program.clone(),
+                token_interface::TransferChecked {
+                    from: source.token_account.clone(),
+                    mint: source.mint.clone(),
+                    to: owner_share.clone(),
+                    authority: source.authority.clone(),
+                },
+                signer_seeds,
+            ),
+            share,
+            source.decimals,
+        )?;
+        paid = paid.checked_add(share).ok_or(VaultError::MathOverflow)?;
+    }
+
+    require!(
+        owner_shares.next().is_none(),
+        VaultError::InvalidOwnerShareAccounts
+    );
+    Ok(paid)
+}
+
+/// Apply the emergency withdrawal allowlist and cooldown, and start a new cooldown.
+/// Returns whether the guardian co-signed, which lifts the restrictions.
+fn check_emergency_policy(
+    vault: &mut VaultState,
+    destination: &Pubkey,
+    guardian: &Option<Signer>,
+    now: i64,
+) -> Result<bool> {
+    let guardian_bypass = match guardian {
+        Some(signer) => {
+            require!(
+                vault.guardian != Pubkey::default() && signer.key() == vault.guardian,
+                VaultError::Unauthorized
+            );
+            true
+        }
+        None => false,
+    };
+
+    if !guardian_bypass {
+        require!(
+            vault.emergency_destinations.contains(destination),
+            VaultError::DestinationNotAllowlisted
+        );
+        if vault.last_emergency_withdrawal != 0 {
+            require!(
+                now >= vault
+                    .last_emergency_withdrawal
+                    .saturating_add(vault.emergency_cooldown),
+                VaultError::EmergencyCooldownActive
+            );
+        }
+    }
+
+    vault.last_emergency_withdrawal = now;
+    Ok(guardian_bypass)
+}
+
+/// Limit an emergency withdrawal to `emergency_max_bps` of the holdings it draws from
+fn check_emergency_cap(vault: &VaultState, holdings: u64, amount: u64) -> Result<()> {
+    let cap = (holdings as u128)
+        .checked_mul(vault.emergency_max_bps as u128)
+        .ok_or(VaultError::MathOverflow)?
+        / BPS_DENOMINATOR as u128;
+    require!(amount as u128 <= cap, VaultError::EmergencyAmountExceedsCap);
+    Ok(())
+}
+
+/// The keeper and harvesters may always call harvest-path instructions and refresh the
+/// heartbeat doing so. Anyone else may only crank once the keeper has gone inactive.
+/// Returns whether the caller is a fallback cranker.
+fn authorize_crank(
+    vault: &mut VaultState,
+    caller: Pubkey,
+    keeper_role: &Option<Account<KeeperRole>>,
+    now: i64,
+) -> Result<bool> {
+    if vault.is_keeper(&caller, keeper_role) {
+        vault.last_keeper_heartbeat = now;
+        return Ok(false);
+    }
+
+    require!(vault.keeper_inactive(now), VaultError::Unauthorized);
+    msg!(
+        "Keeper inactive since {}, crank by {}",
+        vault.last_keeper_heartbeat,
+        caller
+    );
+    Ok(true)
+}
+
+/// Fees withheld on a Token-2022 mint awaiting withdrawal
+fn mint_withheld_amount(token_mint: &AccountInfo) -> Result<u64> {
+    let mint_data = token_mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let transfer_fee_extension = mint_state.get_extension::<TransferFeeConfig>()?;
+    Ok(u64::from(transfer_fee_extension.withheld_amount))
+}
+
+/// Pay the crank bounty from the keeper reserve when one is passed and can afford it.
+/// An empty reserve never blocks the crank itself.
+fn pay_crank_bounty<'info>(
+    vault: &VaultState,
+    keeper_reserve: Option<&mut Account<'info, KeeperReserve>>,
+    caller: &Signer<'info>,
+    now: i64,
+) -> Result<()> {
+    let mut bounty = 0;
+
+    if let Some(reserve) = keeper_reserve {
+        let reserve_info = reserve.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(reserve_info.data_len());
+        // Clamped on purpose: a reserve below its rent floor has nothing to pay out
+        let available = reserve_info.lamports().saturating_sub(rent_floor);
+
+        if vault.crank_bounty_lamports > 0 && vault.crank_bounty_lamports <= available {
+            bounty = vault.crank_bounty_lamports;
+            reserve_info.sub_lamports(bounty)?;
+            caller.to_account_info().add_lamports(bounty)?;
+            reserve.total_bounties_paid = reserve
+                .total_bounties_paid
+                .checked_add(bounty)
+                .ok_or(VaultError::MathOverflow)?;
+        }
+    }
+
+    emit!(CrankExecuted {
+        timestamp: now,
+        caller: caller.key(),
+        last_keeper_heartbeat: vault.last_keeper_heartbeat,
+        bounty,
+    });
+    Ok(())
+}
+
+/// Check a keeper withdrawal against the vault's guards and count it toward the
+/// rolling window. A tripped guard emits an alert before failing the transaction.
+fn enforce_withdrawal_guards(
+    vault: &mut VaultState,
+    keeper: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let mut tripped = None;
+
+    if vault.min_distribution_interval > 0 && vault.last_distribution_time != 0 {
+        let next_allowed = vault
+            .last_distribution_time
+            .checked_add(vault.min_distribution_interval)
+            .ok_or(VaultError::MathOverflow)?;
+        if now < next_allowed {
+            tripped = Some((
+                WithdrawalGuard::MinInterval,
+                vault.min_distribution_interval as u64,
+                VaultError::DistributionTooSoon,
+            ));
+        }
+    }
+
+    if tripped.is_none() && vault.max_threshold_multiple > 0 {
+        // Clamped on purpose: a cap past u64::MAX cannot be exceeded by any amount
+        let cap = vault
+            .harvest_threshold
+            .saturating_mul(vault.max_threshold_multiple);
+        if amount > cap {
+            tripped = Some((
+                WithdrawalGuard::ThresholdMultiple,
+                cap,
+                VaultError::WithdrawalAboveThresholdCap,
+            ));
+        }
+    }
+
+    if tripped.is_none() && vault.max_withdrawal_per_window > 0 {
+        let window_end = vault
+            .window_start
+            .checked_add(vault.withdrawal_window)
+            .ok_or(VaultError::MathOverflow)?;
+        if now >= window_end {
+            vault.window_start = now;
+            vault.window_withdrawn = 0;
+        }
+        let withdrawn = vault
+            .window_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        if withdrawn > vault.max_withdrawal_per_window {
+            tripped = Some((
+                WithdrawalGuard::WindowCap,
+                vault.max_withdrawal_per_window,
+                VaultError::WindowCapExceeded,
+            ));
+        } else {
+            vault.window_withdrawn = withdrawn;
+        }
+    }
+
+    if let Some((guard, limit, error)) = tripped {
+        emit!(WithdrawalGuardTripped {
+            timestamp: now,
+            keeper,
+            guard,
+            requested: amount,
+            limit,
+        });
+        return Err(error.into());
+    }
+
+    Ok(())
+}
+
+/// Revenue splits must have 1..=MAX_REVENUE_PAYEES payees whose shares sum to 10000 bps
+fn validate_revenue_split(payees: &[RevenuePayee]) -> Result<()> {
+    require!(
+        !payees.is_empty() && payees.len() <= MAX_REVENUE_PAYEES,
+        VaultError::InvalidDistributionSplit
+    );
+
+    let mut total_bps: u64 = 0;
+    for payee in payees {
+        require!(payee.share_bps > 0, VaultError::InvalidDistributionSplit);
+        require!(
+            payee.label.len() <= MAX_PAYEE_LABEL_LEN,
+            VaultError::InvalidDistributionSplit
+        );
+        total_bps = total_bps
+            .checked_add(payee.share_bps as u64)
+            .ok_or(VaultError::MathOverflow)?;
+    }
+
+    require!(
+        total_bps == BPS_DENOMINATOR,
+        VaultError::InvalidDistributionSplit
+    );
+    Ok(())
+}
+
+// Account structures
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VaultState::INIT_SPACE,
+        seeds = [VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Token mint
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Left by `close_vault`; a vault closed for this mint is never re-created
+    #[account(
+        seeds = [VAULT_TOMBSTONE_SEED, token_mint.key().as_ref()],
+        bump,
+        constraint = vault_tombstone.data_is_empty() @ VaultError::VaultClosed
+    )]
+    pub vault_tombstone: UncheckedAccount<'info>,
+
+    /// CHECK: Vault program ID
+    pub vault_program: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePoolRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolRegistry::INIT_SPACE,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLaunchTime<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump,
+        constraint = pool_registry.version == POOL_REGISTRY_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub keeper_authority: Signer<'info>,
+
+    /// PoolReporter role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::PoolReporter as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestFees<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// Keeper or harvester, or any cranker once the keeper is inactive
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    /// CHECK: Token mint
+    #[account(mut)]
+    pub token_mint: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+
+    /// Pays the crank bounty when present
+    #[account(
+        mut,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Option<Account<'info, KeeperReserve>>,
+
+    /// Harvester role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Harvester as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFeesFromMint<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// Keeper or harvester, or any cranker once the keeper is inactive
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    /// CHECK: Token mint
+    #[account(
+        mut,
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token2022>,
+
+    /// Pays the crank bounty when present
+    #[account(
+        mut,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Option<Account<'info, KeeperReserve>>,
+
+    /// Harvester role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Harvester as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+
+    /// CHECK: MIKO ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), vault.token_mint.as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BurnHarvestedFees<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: MIKO ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), vault.token_mint.as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAndReport<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = keeper_authority,
+        space = 8 + DistributionRecord::INIT_SPACE,
+        // Wrapping only to keep seed derivation panic free; the handler rejects the overflow
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &vault.distribution_id.wrapping_add(1).to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Account<'info, DistributionRecord>,
+
+    #[account(
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = keeper_reward_token_account.owner == keeper_authority.key() @ VaultError::InvalidRewardTokenAccount,
+        constraint = keeper_reward_token_account.mint == dial_reward_token(&dial_state)? @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub keeper_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [DIAL_STATE_SEED],
+        bump,
+        seeds::program = smart_dial::ID
+    )]
+    pub dial_state: AccountLoader<'info, DialState>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+
+    /// CHECK: MIKO ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), vault.token_mint.as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SwapAndReport<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump,
+        constraint = pool_registry.version == POOL_REGISTRY_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        init,
+        payer = keeper_authority,
+        space = 8 + DistributionRecord::INIT_SPACE,
+        // Wrapping only to keep seed derivation panic free; the handler rejects the overflow
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &vault.distribution_id.wrapping_add(1).to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Account<'info, DistributionRecord>,
+
+    #[account(
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_reward_token_account.owner == vault.key() @ VaultError::InvalidRewardTokenAccount,
+        constraint = vault_reward_token_account.mint != vault.token_mint @ VaultError::InvalidRewardTokenAccount,
+        constraint = vault_reward_token_account.mint == dial_reward_token(&dial_state)? @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub vault_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = reward_mint.key() == vault_reward_token_account.mint @ VaultError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [DIAL_STATE_SEED],
+        bump,
+        seeds::program = smart_dial::ID
+    )]
+    pub dial_state: AccountLoader<'info, DialState>,
+
+    /// CHECK: Validated against the selected swap adapter
+    pub amm_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the reward mint, paying payee shares
+    pub reward_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+
+    /// CHECK: MIKO ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), vault.token_mint.as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+
+    /// CHECK: Reward token ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), vault_reward_token_account.mint.as_ref()],
+        bump
+    )]
+    pub reward_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardIndex<'info> {
+    #[account(
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = keeper_authority,
+        space = 8 + RewardIndex::INIT_SPACE,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_index: Account<'info, RewardIndex>,
+
+    #[account(
+        constraint = reward_mint.key() != vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = vault_reward_token_account.owner == vault.key() @ VaultError::InvalidRewardTokenAccount,
+        constraint = vault_reward_token_account.mint == reward_mint.key() @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub vault_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardIndex<'info> {
+    #[account(
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_index.reward_mint.as_ref()],
+        bump
+    )]
+    pub reward_index: Account<'info, RewardIndex>,
+
+    #[account(
+        constraint = vault_reward_token_account.key() == reward_index.reward_token_account @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub vault_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterPosition<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    pub holder: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HolderPosition::INIT_SPACE,
+        seeds = [HOLDER_POSITION_SEED, vault.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_position: Account<'info, HolderPosition>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckpointPosition<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [HOLDER_POSITION_SEED, vault.key().as_ref(), holder_position.holder.as_ref()],
+        bump
+    )]
+    pub holder_position: Account<'info, HolderPosition>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_index.reward_mint.as_ref()],
+        bump
+    )]
+    pub reward_index: Account<'info, RewardIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RewardPosition::INIT_SPACE,
+        seeds = [
+            REWARD_POSITION_SEED,
+            vault.key().as_ref(),
+            holder_position.holder.as_ref(),
+            reward_index.reward_mint.as_ref()
+        ],
+        bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+
+    /// An existing position stays on the token account it was staked from unless the
+    /// holder signs; anyone could otherwise move it to an empty account they opened
+    #[account(
+        constraint = holder_token_account.owner == holder_position.holder @ VaultError::InvalidHolderTokenAccount,
+        constraint = holder_token_account.mint == vault.token_mint @ VaultError::InvalidHolderTokenAccount,
+        constraint = reward_position.token_account == Pubkey::default()
+            || reward_position.token_account == holder_token_account.key()
+            || holder.as_ref().is_some_and(|h| h.key() == holder_position.holder)
+            @ VaultError::InvalidHolderTokenAccount
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CHECKPOINT_SEED, holder_token_account.key().as_ref()],
+        bump,
+        seeds::program = miko_transfer_hook::ID
+    )]
+    pub holder_checkpoint: Account<'info, HolderCheckpoint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub holder: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_index.reward_mint.as_ref()],
+        bump
+    )]
+    pub reward_index: Account<'info, RewardIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POSITION_SEED,
+            vault.key().as_ref(),
+            holder.key().as_ref(),
+            reward_index.reward_mint.as_ref()
+        ],
+        bump
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+
+    #[account(
+        seeds = [CHECKPOINT_SEED, reward_position.token_account.as_ref()],
+        bump,
+        seeds::program = miko_transfer_hook::ID
+    )]
+    pub holder_checkpoint: Account<'info, HolderCheckpoint>,
+
+    #[account(
+        constraint = reward_mint.key() == reward_index.reward_mint @ VaultError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = vault_reward_token_account.key() == reward_index.reward_token_account @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub vault_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub holder_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Reward token ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleDistribution<'info> {
+    #[account(
+        mut,
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &distribution_record.distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Account<'info, DistributionRecord>,
+
+    #[account(
+        mut,
+        constraint = reward_token_account.key() == distribution_record.reward_token_account @ VaultError::InvalidRewardTokenAccount,
+        constraint = reward_token_account.mint == distribution_record.reward_mint @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = reward_mint.key() == distribution_record.reward_mint @ VaultError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the reward mint, paying payee shares
+    pub reward_token_program: Interface<'info, TokenInterface>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct AcknowledgeShortfall<'info> {
+    #[account(
+        mut,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &distribution_record.distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Account<'info, DistributionRecord>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeOwnerShare<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    /// CHECK: Payee the share is held for; any address may be a split destination
+    pub owner: UncheckedAccount<'info>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA that owns every owner share account of the vault
+    #[account(
+        seeds = [OWNER_SHARE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub owner_share_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            OWNER_SHARE_SEED,
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+            reward_mint.key().as_ref()
+        ],
+        bump,
+        token::mint = reward_mint,
+        token::authority = owner_share_authority,
+        token::token_program = token_program
+    )]
+    pub owner_share: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimOwnerShare<'info> {
+    /// CHECK: Only seeds the owner share accounts, which stay claimable after the
+    /// vault is closed
+    pub vault: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: PDA that owns every owner share account of the vault
+    #[account(
+        seeds = [OWNER_SHARE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub owner_share_authority: UncheckedAccount<'info>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            OWNER_SHARE_SEED,
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+            reward_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub owner_share: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ReportHolderWeight<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        constraint = holder_checkpoint.mint == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub holder_checkpoint: Account<'info, HolderCheckpoint>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_index.reward_mint.as_ref()],
+        bump
+    )]
+    pub reward_index: Account<'info, RewardIndex>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_POSITION_SEED,
+            vault.key().as_ref(),
+            holder_checkpoint.owner.as_ref(),
+            reward_index.reward_mint.as_ref()
+        ],
+        bump,
+        constraint = reward_position.token_account == holder_checkpoint.token_account @ VaultError::InvalidHolderTokenAccount
+    )]
+    pub reward_position: Account<'info, RewardPosition>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRevenueSplit<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RevenueSplit::INIT_SPACE,
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRevenueSplit<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyRevenueSplit<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+}
+
+#[derive(Accounts)]
+pub struct LogKeeperWork<'info> {
+    #[account(
+        init_if_needed,
+        payer = keeper_authority,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref()],
+        bump,
+        space = KEEPER_WORK_LOG_LEN
+    )]
+    pub keeper_work_log: AccountLoader<'info, KeeperWorkLog>,
+
+    #[account(
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Logger role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Logger as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: KeeperRoleKind)]
+pub struct GrantKeeperRole<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Key receiving the role
+    pub grantee: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + KeeperRole::INIT_SPACE,
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), grantee.key().as_ref(), &[role as u8]],
+        bump
+    )]
+    pub keeper_role: Account<'info, KeeperRole>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeKeeperRole<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_role.key.as_ref(), &[keeper_role.role as u8]],
+        bump
+    )]
+    pub keeper_role: Account<'info, KeeperRole>,
+}
+
+#[derive(Accounts)]
+pub struct KeeperHeartbeat<'info> {
+    #[account(
+        mut,
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub keeper_authority: Signer<'info>,
+
+    /// Harvester role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Harvester as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperReserve<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + KeeperReserve::INIT_SPACE,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Account<'info, KeeperReserve>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateKeeperReserve<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Account<'info, KeeperReserve>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToReserve<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Account<'info, KeeperReserve>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpKeeper<'info> {
+    #[account(
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Account<'info, KeeperReserve>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper_authority,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref()],
+        bump,
+        space = KEEPER_WORK_LOG_LEN
+    )]
+    pub keeper_work_log: AccountLoader<'info, KeeperWorkLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageExclusions<'info> {
+    #[account(
+        mut,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Token mint
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Guardian co-signature lifting the emergency policy limits
+    pub guardian: Option<Signer<'info>>,
+
+    /// CHECK: Ledger of the withdrawn asset, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdrawWithheld<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Token mint
+    #[account(
+        mut,
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// Vault MIKO account, the basis of the emergency cap
+    #[account(
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token2022>,
+
+    /// Guardian co-signature lifting the emergency policy limits
+    pub guardian: Option<Signer<'info>>,
+
+    /// CHECK: MIKO ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAssetLedger<'info> {
+    #[account(
+        constraint = vault.is_keeper(&keeper_authority.key(), &keeper_role) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == mint.key() @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = keeper_authority,
+        space = ASSET_LEDGER_LEN,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: AccountLoader<'info, AssetLedger>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Distributor role grant, required unless the signer is `vault.keeper_authority`
+    #[account(
+        seeds = [KEEPER_ROLE_SEED, vault.key().as_ref(), keeper_authority.key().as_ref(), &[KeeperRoleKind::Distributor as u8]],
+        bump
+    )]
+    pub keeper_role: Option<Account<'info, KeeperRole>>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileAssetLedger<'info> {
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        constraint = asset_ledger.as_ref().data_len() == ASSET_LEDGER_LEN @ VaultError::AccountNotMigrated,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), asset_ledger.load()?.mint.as_ref()],
+        bump
+    )]
+    pub asset_ledger: AccountLoader<'info, AssetLedger>,
+
+    #[account(
+        constraint = vault_token_account.key() == asset_ledger.load()?.token_account @ VaultError::InvalidAssetLedger
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    #[account(
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ViewVault<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultSummary<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// CHECK: Token mint
+    #[account(
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidMint
+    )]
+    pub token_mint: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// CHECK: Vault in a legacy layout; discriminator, size, seeds and authority are
+    /// checked by the handler
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePoolRegistry<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// CHECK: Pool registry in a legacy layout; discriminator and size are checked by
+    /// the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAssetLedger<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// CHECK: Mint the ledger tracks, only used to derive the ledger address
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Asset ledger in the Borsh layout; discriminator and size are checked by
+    /// the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateKeeperWorkLog<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    /// CHECK: Keeper work log in a legacy layout; discriminator and size are checked
+    /// by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_work_log: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseKeeperWorkLog<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_work_log: AccountLoader<'info, KeeperWorkLog>,
+
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePoolRegistry<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRevenueSplit<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [REVENUE_SPLIT_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
 
-// Account structures
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
 
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct CloseDistributionRecord<'info> {
     #[account(
-        init,
-        payer = payer,
-        space = 8 + VaultState::INIT_SPACE,
-        seeds = [VAULT_SEED, token_mint.key().as_ref()],
-        bump
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
     )]
     pub vault: Account<'info, VaultState>,
 
     pub authority: Signer<'info>,
 
-    /// CHECK: Token mint
-    pub token_mint: UncheckedAccount<'info>,
-
-    /// CHECK: Vault program ID
-    pub vault_program: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &distribution_record.distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Account<'info, DistributionRecord>,
 
+    /// CHECK: Any account chosen to receive the reclaimed rent
     #[account(mut)]
-    pub payer: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
+    pub rent_receiver: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializePoolRegistry<'info> {
+pub struct CloseRewardIndex<'info> {
     #[account(
-        init,
-        payer = payer,
-        space = 8 + PoolRegistry::INIT_SPACE,
-        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [REWARD_INDEX_SEED, vault.key().as_ref(), reward_index.reward_mint.as_ref()],
         bump
     )]
-    pub pool_registry: Account<'info, PoolRegistry>,
+    pub reward_index: Account<'info, RewardIndex>,
 
-    pub vault: Account<'info, VaultState>,
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
 
+    #[account(
+        constraint = reward_mint.key() == reward_index.reward_mint @ VaultError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = vault_reward_token_account.key() == reward_index.reward_token_account @ VaultError::InvalidRewardTokenAccount
+    )]
+    pub vault_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Any account of the reward mint chosen to receive the rounding dust
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub dust_receiver: InterfaceAccount<'info, TokenAccount>,
 
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Reward token ledger, recorded once opened; may be uninitialized
+    #[account(
+        mut,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_ledger: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SetLaunchTime<'info> {
-    #[account(mut)]
+pub struct CloseAssetLedger<'info> {
+    #[account(
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
     pub vault: Account<'info, VaultState>,
-}
 
-#[derive(Accounts)]
-pub struct UpdatePoolRegistry<'info> {
+    pub authority: Signer<'info>,
+
     #[account(
         mut,
-        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        close = rent_receiver,
+        constraint = asset_ledger.as_ref().data_len() == ASSET_LEDGER_LEN @ VaultError::AccountNotMigrated,
+        seeds = [ASSET_LEDGER_SEED, vault.key().as_ref(), asset_ledger.load()?.mint.as_ref()],
         bump
     )]
-    pub pool_registry: Account<'info, PoolRegistry>,
+    pub asset_ledger: AccountLoader<'info, AssetLedger>,
+
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
 
+#[derive(Accounts)]
+pub struct CloseKeeperReserve<'info> {
     #[account(
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
     )]
     pub vault: Account<'info, VaultState>,
 
-    pub keeper_authority: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_receiver,
+        seeds = [KEEPER_RESERVE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub keeper_reserve: Account<'info, KeeperReserve>,
+
+    /// CHECK: Any account chosen to receive the reclaimed rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct HarvestFees<'info> {
+pub struct CloseVault<'info> {
     #[account(
         mut,
+        close = rent_receiver,
         seeds = [VAULT_SEED, vault.token_mint.as_ref()],
         bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
     )]
     pub vault: Account<'info, VaultState>,
 
-    pub keeper_authority: Signer<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
-    /// CHECK: Token mint
+    #[account(
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidVaultTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VaultTombstone::INIT_SPACE,
+        seeds = [VAULT_TOMBSTONE_SEED, vault.token_mint.as_ref()],
+        bump
+    )]
+    pub vault_tombstone: Account<'info, VaultTombstone>,
+
+    /// CHECK: Any account chosen to receive the reclaimed rent
     #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
+    pub rent_receiver: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFeesFromMint<'info> {
+pub struct RecoverForeignAsset<'info> {
     #[account(
         mut,
         seeds = [VAULT_SEED, vault.token_mint.as_ref()],
         bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
     )]
     pub vault: Account<'info, VaultState>,
 
-    pub keeper_authority: Signer<'info>,
+    pub authority: Signer<'info>,
 
-    /// CHECK: Token mint
+    /// CHECK: Lamport recipient, or destination token account validated by the token program
     #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
+    pub destination: UncheckedAccount<'info>,
 
+    /// Token recovery only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Token recovery only
     #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_foreign_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    pub token_program: Program<'info, Token2022>,
+    /// CHECK: Token recovery only; reward index PDA of the mint, checked in the handler
+    pub reward_index: Option<UncheckedAccount<'info>>,
+
+    /// SPL Token or Token-2022; token recovery only
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Token recovery only
+    #[account(
+        seeds = [DIAL_STATE_SEED],
+        bump,
+        seeds::program = smart_dial::ID
+    )]
+    pub dial_state: Option<AccountLoader<'info, DialState>>,
+
+    /// CHECK: Token recovery only; record of the latest distribution, which may not
+    /// exist yet
+    #[account(
+        seeds = [DISTRIBUTION_SEED, vault.key().as_ref(), &vault.distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution_record: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawAndReport<'info> {
+pub struct UpdateEmergencyPolicy<'info> {
     #[account(
         mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
     )]
     pub vault: Account<'info, VaultState>,
 
-    pub keeper_authority: Signer<'info>,
+    pub authority: Signer<'info>,
 
-    /// CHECK: Token mint
-    pub token_mint: InterfaceAccount<'info, Mint>,
+    /// Current guardian, required once one is set
+    pub guardian: Option<Signer<'info>>,
+}
+
+// State structures
+
+#[account]
+#[derive(InitSpace)]
+pub struct VaultState {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    #[max_len(100)]
+    pub reward_exclusions: Vec<Pubkey>,
+    pub harvest_threshold: u64,
+    pub total_fees_harvested: u64,
+    pub total_burned: u64,
+    pub total_rewards_distributed: u64,
+    pub distribution_id: u64,
+    pub pending_withheld: u64,
+    pub last_harvest_time: i64,
+    pub last_harvest_amount: u64,
+    pub last_distribution_time: i64,
+    pub launch_timestamp: i64,
+    pub unsettled_distribution_id: u64,
+    pub unacknowledged_shortfall_id: u64,
+    pub burn_share_bps: u16,
+    pub pending_burn_amount: u64,
+    pub min_distribution_interval: i64,
+    pub withdrawal_window: i64,
+    pub max_withdrawal_per_window: u64,
+    pub max_threshold_multiple: u64,
+    pub window_start: i64,
+    pub window_withdrawn: u64,
+    pub last_keeper_heartbeat: i64,
+    pub keeper_inactivity_period: i64,
+    pub crank_bounty_lamports: u64,
+    pub guardian: Pubkey,
+    #[max_len(5)]
+    pub emergency_destinations: Vec<Pubkey>,
+    pub emergency_max_bps: u16,
+    pub emergency_cooldown: i64,
+    pub last_emergency_withdrawal: i64,
+    pub total_emergency_withdrawn: u64,
+    pub last_reconciliation: ReconciliationReport,
+    /// Zeroed space for future fields, so they can be added without a realloc
+    pub reserved: [u8; 128],
+}
+
+impl VaultState {
+    /// Whether `wallet` is excluded from reward distributions
+    pub fn is_excluded(&self, wallet: &Pubkey) -> bool {
+        self.reward_exclusions.contains(wallet)
+    }
+
+    /// Snapshot returned by `get_vault_summary`
+    pub fn summary(&self, vault_balance: u64, mint_withheld: u64) -> VaultSummary {
+        VaultSummary {
+            version: self.version,
+            token_mint: self.token_mint,
+            vault_balance,
+            mint_withheld,
+            pending_withheld: self.pending_withheld,
+            pending_burn_amount: self.pending_burn_amount,
+            harvest_threshold: self.harvest_threshold,
+            total_fees_harvested: self.total_fees_harvested,
+            total_burned: self.total_burned,
+            total_rewards_distributed: self.total_rewards_distributed,
+            total_emergency_withdrawn: self.total_emergency_withdrawn,
+            distribution_id: self.distribution_id,
+            unsettled_distribution_id: self.unsettled_distribution_id,
+            last_harvest_time: self.last_harvest_time,
+            last_distribution_time: self.last_distribution_time,
+            exclusion_count: self.reward_exclusions.len() as u32,
+        }
+    }
+
+    /// Id the next distribution record is created under
+    pub fn next_distribution_id(&self) -> Result<u64> {
+        self.distribution_id
+            .checked_add(1)
+            .ok_or_else(|| error!(VaultError::MathOverflow))
+    }
+
+    /// Add a harvest to the totals and earmark its burn share, returning the burn amount
+    /// Nothing is written unless every counter fits
+    pub fn record_harvest(&mut self, amount: u64, now: i64) -> Result<u64> {
+        let total_fees_harvested = self
+            .total_fees_harvested
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        // Bounded by `amount` since burn_share_bps never exceeds BPS_DENOMINATOR
+        let burn_amount =
+            (amount as u128 * self.burn_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let pending_burn_amount = self
+            .pending_burn_amount
+            .checked_add(burn_amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        self.total_fees_harvested = total_fees_harvested;
+        self.pending_burn_amount = pending_burn_amount;
+        self.last_harvest_amount = amount;
+        self.last_harvest_time = now;
+        Ok(burn_amount)
+    }
+
+    /// Open the next distribution for `amount` of MIKO, returning its id
+    /// Nothing is written unless every counter fits
+    pub fn record_distribution(&mut self, amount: u64, now: i64) -> Result<u64> {
+        let distribution_id = self.next_distribution_id()?;
+        let total_rewards_distributed = self
+            .total_rewards_distributed
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        self.distribution_id = distribution_id;
+        self.total_rewards_distributed = total_rewards_distributed;
+        self.last_distribution_time = now;
+        Ok(distribution_id)
+    }
+
+    /// The keeper authority holds every role; other keys need a matching role grant,
+    /// whose PDA seeds tie it to the signer and the role an instruction requires
+    pub fn is_keeper(&self, key: &Pubkey, keeper_role: &Option<Account<KeeperRole>>) -> bool {
+        *key == self.keeper_authority || keeper_role.is_some()
+    }
+
+    /// True once the keeper has missed heartbeats for the configured inactivity period
+    pub fn keeper_inactive(&self, now: i64) -> bool {
+        self.keeper_inactivity_period > 0
+            && now
+                >= self
+                    .last_keeper_heartbeat
+                    .saturating_add(self.keeper_inactivity_period)
+    }
+}
+
+/// Return data of `get_vault_summary`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultSummary {
+    pub version: u8,
+    pub token_mint: Pubkey,
+    /// MIKO held by the vault token account
+    pub vault_balance: u64,
+    /// Fees withheld on the mint, ready for `withdraw_fees_from_mint`
+    pub mint_withheld: u64,
+    pub pending_withheld: u64,
+    pub pending_burn_amount: u64,
+    pub harvest_threshold: u64,
+    pub total_fees_harvested: u64,
+    pub total_burned: u64,
+    pub total_rewards_distributed: u64,
+    pub total_emergency_withdrawn: u64,
+    pub distribution_id: u64,
+    pub unsettled_distribution_id: u64,
+    pub last_harvest_time: i64,
+    pub last_distribution_time: i64,
+    pub exclusion_count: u32,
+}
+
+/// Outcome of the last `reconcile` run, kept for monitoring
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ReconciliationReport {
+    pub timestamp: i64,
+    pub expected_balance: u64,
+    pub actual_balance: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+    pub overdrawn: u64,
+    pub passed: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DistributionRecord {
+    pub vault: Pubkey,
+    pub distribution_id: u64,
+    pub keeper: Pubkey,
+    pub withdrawn_miko_amount: u64,
+    pub expected_minimum_reward_amount: u64,
+    pub reward_token_account: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_balance_snapshot: u64,
+    pub reward_amount_received: u64,
+    pub status: DistributionStatus,
+    pub created_at: i64,
+    pub settled_at: i64,
+    #[max_len(5)]
+    pub revenue_split: Vec<RevenuePayee>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RevenueSplit {
+    pub vault: Pubkey,
+    #[max_len(5)]
+    pub payees: Vec<RevenuePayee>,
+    #[max_len(5)]
+    pub pending_payees: Vec<RevenuePayee>,
+    pub pending_eta: i64,
+    pub last_updated: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct RevenuePayee {
+    #[max_len(16)]
+    pub label: String,
+    pub destination: Pubkey,
+    pub share_bps: u16,
+}
+
+impl RevenuePayee {
+    /// This payee's share of `amount`, rounded down
+    pub fn share_of(&self, amount: u64) -> Result<u64> {
+        Ok(amount
+            .checked_mul(self.share_bps as u64)
+            .ok_or(VaultError::MathOverflow)?
+            / BPS_DENOMINATOR)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardIndex {
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_account: Pubkey,
+    pub acc_reward_per_share: u128,
+    pub total_staked: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub last_funded_at: i64,
+    /// Rewards withheld from positions whose hook-tracked balance fell below their
+    /// stake; they are free again for the next funding
+    pub total_forfeited: u64,
+    /// Positions still holding a stake or unclaimed rewards
+    pub open_positions: u64,
+}
+
+impl RewardIndex {
+    /// Rewards accumulated by `balance` since the index started, in reward token units
+    pub fn accumulated_reward(&self, balance: u64) -> Result<u128> {
+        Ok((balance as u128)
+            .checked_mul(self.acc_reward_per_share)
+            .ok_or(VaultError::MathOverflow)?
+            / REWARD_PER_SHARE_PRECISION)
+    }
+
+    /// Rewards earned by a position since its last checkpoint or claim
+    pub fn pending_reward(&self, position: &RewardPosition) -> Result<u64> {
+        let pending = self
+            .accumulated_reward(position.balance)?
+            .checked_sub(position.reward_debt)
+            .ok_or(VaultError::MathOverflow)?;
+        u64::try_from(pending).map_err(|_| error!(VaultError::MathOverflow))
+    }
+
+    /// Funded rewards not yet claimed or forfeited
+    pub fn outstanding(&self) -> Result<u64> {
+        self.total_funded
+            .checked_sub(self.total_claimed)
+            .and_then(|owed| owed.checked_sub(self.total_forfeited))
+            .ok_or(error!(VaultError::MathOverflow))
+    }
+
+    /// Move a position's pending rewards into its accrued balance if the hook
+    /// checkpoint shows the stake was held throughout, or forfeit them otherwise
+    pub fn settle_position(
+        &mut self,
+        position: &mut RewardPosition,
+        checkpoint: &HolderCheckpoint,
+        now: i64,
+    ) -> Result<()> {
+        let pending = self.pending_reward(position)?;
+        if position.held_stake(checkpoint, now) {
+            position.accrued = position
+                .accrued
+                .checked_add(pending)
+                .ok_or(VaultError::MathOverflow)?;
+        } else {
+            self.total_forfeited = self
+                .total_forfeited
+                .checked_add(pending)
+                .ok_or(VaultError::MathOverflow)?;
+        }
+        position.reward_debt = self.accumulated_reward(position.balance)?;
+        position.balance_seconds = checkpoint.balance_seconds_at(now);
+        position.last_checkpoint = now;
+        Ok(())
+    }
+
+    /// Replace a settled position's stake with `balance`, tracked by `checkpoint`
+    pub fn restake(
+        &mut self,
+        position: &mut RewardPosition,
+        checkpoint: &HolderCheckpoint,
+        balance: u64,
+    ) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(position.balance)
+            .and_then(|staked| staked.checked_add(balance))
+            .ok_or(VaultError::MathOverflow)?;
+        position.balance = balance;
+        position.token_account = checkpoint.token_account;
+        position.reward_debt = self.accumulated_reward(balance)?;
+        Ok(())
+    }
+
+    /// Count `position` in or out of `open_positions` after a change from `was_open`
+    pub fn track_open(&mut self, was_open: bool, position: &RewardPosition) -> Result<()> {
+        self.open_positions = match (was_open, position.is_open()) {
+            (false, true) => self.open_positions.checked_add(1),
+            (true, false) => self.open_positions.checked_sub(1),
+            _ => Some(self.open_positions),
+        }
+        .ok_or(VaultError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderPosition {
+    pub vault: Pubkey,
+    pub holder: Pubkey,
+    pub registered_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardPosition {
+    pub vault: Pubkey,
+    pub holder: Pubkey,
+    pub reward_mint: Pubkey,
+    pub balance: u64,
+    pub reward_debt: u128,
+    pub accrued: u64,
+    pub total_claimed: u64,
+    pub last_checkpoint: i64,
+    /// MIKO token account whose transfer-hook checkpoint backs the stake
+    pub token_account: Pubkey,
+    /// Hook balance-seconds of `token_account` at `last_checkpoint`
+    pub balance_seconds: u128,
+}
+
+impl RewardPosition {
+    /// Whether the position can still earn or claim rewards
+    pub fn is_open(&self) -> bool {
+        self.balance > 0 || self.accrued > 0
+    }
+
+    /// Whether the hook checkpoint shows at least `balance` was held since the last
+    /// checkpoint. The hook tracks balance-seconds, so a transfer out and back within
+    /// one second is only caught by the current balance.
+    pub fn held_stake(&self, checkpoint: &HolderCheckpoint, now: i64) -> bool {
+        if checkpoint.token_account != self.token_account || checkpoint.balance < self.balance {
+            return false;
+        }
+        let elapsed = now.saturating_sub(self.last_checkpoint).max(0) as u128;
+        let required = (self.balance as u128).saturating_mul(elapsed);
+        checkpoint
+            .balance_seconds_at(now)
+            .checked_sub(self.balance_seconds)
+            .is_some_and(|held| held >= required)
+    }
+}
+
+#[account(zero_copy)]
+pub struct KeeperWorkLog {
+    pub vault: Pubkey,
+    /// Sequence number of the oldest kept entry; entry `n` lives in slot
+    /// `n % MAX_KEEPER_WORK_ENTRIES` of `slots`
+    pub head: u64,
+    /// Sequence number the next entry is written at
+    pub tail: u64,
+    pub slots: [KeeperWorkRecord; 50],
+    pub version: u8,
+    pub reserved: [u8; 63],
+}
+
+impl KeeperWorkLog {
+    /// Append an entry, overwriting the oldest once the last 50 are kept
+    pub fn record(&mut self, vault: Pubkey, entry: &KeeperWorkEntry) -> Result<()> {
+        // Initialize if new
+        if self.vault == Pubkey::default() {
+            self.version = KEEPER_WORK_LOG_VERSION;
+            self.vault = vault;
+        }
+        require!(
+            self.version == KEEPER_WORK_LOG_VERSION,
+            VaultError::AccountNotMigrated
+        );
+
+        self.slots[work_log_slot(self.tail)] = KeeperWorkRecord::new(entry);
+        self.tail = self.tail.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        if self.len()? > MAX_KEEPER_WORK_ENTRIES {
+            self.head = self.head.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> Result<usize> {
+        let len = self
+            .tail
+            .checked_sub(self.head)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(len as usize)
+    }
 
-    #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
 
-    #[account(mut)]
-    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Kept records, oldest first
+    pub fn records(&self) -> impl Iterator<Item = &KeeperWorkRecord> {
+        (self.head..self.tail).map(|sequence| &self.slots[work_log_slot(sequence)])
+    }
 
-    pub token_program: Interface<'info, TokenInterface>,
+    /// Kept entries decoded, oldest first
+    pub fn entries(&self) -> Vec<KeeperWorkEntry> {
+        self.records().filter_map(KeeperWorkRecord::entry).collect()
+    }
 }
 
-#[derive(Accounts)]
-pub struct LogKeeperWork<'info> {
-    #[account(
-        init_if_needed,
-        payer = keeper_authority,
-        seeds = [b"keeper_log", vault.key().as_ref()],
-        bump,
-        space = 8 + KeeperWorkLog::INIT_SPACE
-    )]
-    pub keeper_work_log: Account<'info, KeeperWorkLog>,
+fn work_log_slot(sequence: u64) -> usize {
+    (sequence % MAX_KEEPER_WORK_ENTRIES as u64) as usize
+}
 
-    #[account(
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
+/// Fixed-size form of a `KeeperWorkEntry` as the work log stores it
+#[zero_copy]
+pub struct KeeperWorkRecord {
+    pub timestamp: i64,
+    pub amount: u64,
+    /// UTF-8, `details_len` bytes used
+    pub details: [u8; 100],
+    /// `KeeperWorkType` discriminant
+    pub work_type: u8,
+    pub details_len: u8,
+    pub padding: [u8; 2],
+}
 
-    #[account(mut)]
-    pub keeper_authority: Signer<'info>,
+impl KeeperWorkRecord {
+    /// Details longer than MAX_WORK_DETAILS_LEN bytes are cut at a char boundary
+    pub fn new(entry: &KeeperWorkEntry) -> Self {
+        let mut len = entry.details.len().min(MAX_WORK_DETAILS_LEN);
+        while !entry.details.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut details = [0; MAX_WORK_DETAILS_LEN];
+        details[..len].copy_from_slice(&entry.details.as_bytes()[..len]);
+        Self {
+            timestamp: entry.timestamp,
+            amount: entry.amount,
+            details,
+            work_type: entry.work_type as u8,
+            details_len: len as u8,
+            padding: [0; 2],
+        }
+    }
 
-    pub system_program: Program<'info, System>,
+    /// `None` only for bytes this program never writes
+    pub fn entry(&self) -> Option<KeeperWorkEntry> {
+        let details = self.details.get(..self.details_len as usize)?;
+        Some(KeeperWorkEntry {
+            timestamp: self.timestamp,
+            work_type: KeeperWorkType::try_from_slice(&[self.work_type]).ok()?,
+            amount: self.amount,
+            details: std::str::from_utf8(details).ok()?.to_string(),
+        })
+    }
 }
 
-#[derive(Accounts)]
-pub struct ManageExclusions<'info> {
-    #[account(
-        mut,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
+/// Left at a closed vault's mint so the vault PDA cannot be initialized again
+#[account]
+#[derive(InitSpace)]
+pub struct VaultTombstone {
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub closed_at: i64,
+}
 
-    pub authority: Signer<'info>,
+/// Per-asset treasury ledger of one vault-owned token account
+#[account(zero_copy)]
+pub struct AssetLedger {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub opening_balance: u64,
+    pub total_inflow: u64,
+    pub total_outflow: u64,
+    pub opened_at: i64,
+    pub last_reconciled_at: i64,
+    /// Sequence number of the oldest kept entry; entry `n` lives in slot
+    /// `n % MAX_LEDGER_ENTRIES` of `slots`
+    pub head: u64,
+    /// Sequence number the next entry is written at
+    pub tail: u64,
+    pub slots: [LedgerRecord; 32],
+    pub version: u8,
+    pub reserved: [u8; 63],
 }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(
-        mut,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
+impl AssetLedger {
+    /// Balance implied by the opening balance and every recorded movement
+    pub fn balance(&self) -> Result<u64> {
+        self.opening_balance
+            .checked_add(self.total_inflow)
+            .and_then(|total| total.checked_sub(self.total_outflow))
+            .ok_or_else(|| error!(VaultError::MathOverflow))
+    }
 
-    pub authority: Signer<'info>,
-}
+    /// Add an entry to the totals, keeping the last MAX_LEDGER_ENTRIES
+    pub fn record(&mut self, entry: &LedgerEntry) -> Result<()> {
+        require!(
+            self.version == ASSET_LEDGER_VERSION,
+            VaultError::AccountNotMigrated
+        );
+        if entry.kind.is_inflow() {
+            self.total_inflow = self
+                .total_inflow
+                .checked_add(entry.amount)
+                .ok_or(VaultError::MathOverflow)?;
+        } else {
+            self.total_outflow = self
+                .total_outflow
+                .checked_add(entry.amount)
+                .ok_or(VaultError::MathOverflow)?;
+        }
+        self.push(entry)
+    }
 
-#[derive(Accounts)]
-pub struct EmergencyWithdraw<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
+    /// Keep an entry without touching the totals, overwriting the oldest once full
+    fn push(&mut self, entry: &LedgerEntry) -> Result<()> {
+        self.slots[ledger_slot(self.tail)] = LedgerRecord::new(entry);
+        self.tail = self.tail.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        if self.len()? > MAX_LEDGER_ENTRIES {
+            self.head = self.head.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        }
+        Ok(())
+    }
 
-    pub authority: Signer<'info>,
+    pub fn len(&self) -> Result<usize> {
+        let len = self
+            .tail
+            .checked_sub(self.head)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(len as usize)
+    }
 
-    #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
 
-    #[account(mut)]
-    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Kept entries decoded, oldest first
+    pub fn entries(&self) -> Vec<LedgerEntry> {
+        (self.head..self.tail)
+            .filter_map(|sequence| self.slots[ledger_slot(sequence)].entry())
+            .collect()
+    }
+}
 
-    /// CHECK: Token mint
-    pub token_mint: InterfaceAccount<'info, Mint>,
+fn ledger_slot(sequence: u64) -> usize {
+    (sequence % MAX_LEDGER_ENTRIES as u64) as usize
+}
 
-    pub token_program: Interface<'info, TokenInterface>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct LedgerEntry {
+    pub timestamp: i64,
+    pub distribution_id: u64,
+    pub kind: LedgerEntryKind,
+    pub amount: u64,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyWithdrawWithheld<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
+/// Fixed-size form of a `LedgerEntry` as the asset ledger stores it
+#[zero_copy]
+pub struct LedgerRecord {
+    pub timestamp: i64,
+    pub distribution_id: u64,
+    pub amount: u64,
+    /// `LedgerEntryKind` discriminant
+    pub kind: u8,
+    pub padding: [u8; 7],
+}
 
-    pub authority: Signer<'info>,
+impl LedgerRecord {
+    pub fn new(entry: &LedgerEntry) -> Self {
+        Self {
+            timestamp: entry.timestamp,
+            distribution_id: entry.distribution_id,
+            amount: entry.amount,
+            kind: entry.kind as u8,
+            padding: [0; 7],
+        }
+    }
 
-    /// CHECK: Token mint
-    #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
+    /// `None` only for bytes this program never writes
+    pub fn entry(&self) -> Option<LedgerEntry> {
+        Some(LedgerEntry {
+            timestamp: self.timestamp,
+            distribution_id: self.distribution_id,
+            kind: LedgerEntryKind::try_from_slice(&[self.kind]).ok()?,
+            amount: self.amount,
+        })
+    }
+}
 
-    #[account(mut)]
-    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+#[account]
+#[derive(InitSpace)]
+pub struct KeeperRole {
+    pub vault: Pubkey,
+    pub key: Pubkey,
+    pub role: KeeperRoleKind,
+    pub granted_at: i64,
+}
 
-    pub token_program: Program<'info, Token2022>,
+#[account]
+#[derive(InitSpace)]
+pub struct KeeperReserve {
+    pub vault: Pubkey,
+    pub min_keeper_balance: u64,
+    pub max_top_up_per_period: u64,
+    pub top_up_period: i64,
+    pub period_start: i64,
+    pub period_topped_up: u64,
+    pub total_deposited: u64,
+    pub total_topped_up: u64,
+    pub total_bounties_paid: u64,
 }
 
-// State structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct KeeperWorkEntry {
+    pub timestamp: i64,
+    pub work_type: KeeperWorkType,
+    pub amount: u64,
+    #[max_len(100)]
+    pub details: String,
+}
 
 #[account]
 #[derive(InitSpace)]
-pub struct VaultState {
+pub struct PoolRegistry {
+    pub version: u8,
+    pub vault: Pubkey,
+    #[max_len(50)]
+    pub pools: Vec<Pubkey>,
+    pub reserved: [u8; 64],
+}
+
+// Legacy layouts
+
+/// `VaultState` as first deployed, before `last_harvest_amount` was inserted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VaultStateLayoutV0 {
+    pub authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    #[max_len(100)]
+    pub reward_exclusions: Vec<Pubkey>,
+    pub harvest_threshold: u64,
+    pub total_fees_harvested: u64,
+    pub total_rewards_distributed: u64,
+    pub distribution_id: u64,
+    pub pending_withheld: u64,
+    pub last_harvest_time: i64,
+    pub last_distribution_time: i64,
+    pub launch_timestamp: i64,
+}
+
+/// Last unversioned `VaultState` layout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VaultStateLayoutV1 {
     pub authority: Pubkey,
     pub keeper_authority: Pubkey,
     pub owner_wallet: Pubkey,
@@ -703,29 +5170,123 @@ pub struct VaultState {
     pub launch_timestamp: i64,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct KeeperWorkLog {
+impl From<VaultStateLayoutV0> for VaultStateLayoutV1 {
+    fn from(v0: VaultStateLayoutV0) -> Self {
+        Self {
+            authority: v0.authority,
+            keeper_authority: v0.keeper_authority,
+            owner_wallet: v0.owner_wallet,
+            token_mint: v0.token_mint,
+            reward_exclusions: v0.reward_exclusions,
+            harvest_threshold: v0.harvest_threshold,
+            total_fees_harvested: v0.total_fees_harvested,
+            total_rewards_distributed: v0.total_rewards_distributed,
+            distribution_id: v0.distribution_id,
+            pending_withheld: v0.pending_withheld,
+            last_harvest_time: v0.last_harvest_time,
+            last_harvest_amount: 0,
+            last_distribution_time: v0.last_distribution_time,
+            launch_timestamp: v0.launch_timestamp,
+        }
+    }
+}
+
+impl VaultStateLayoutV1 {
+    /// Current layout carrying the legacy fields over, with every field added since
+    /// at the default `initialize` gives it
+    pub fn into_current(self, now: i64) -> VaultState {
+        VaultState {
+            version: VAULT_STATE_VERSION,
+            authority: self.authority,
+            keeper_authority: self.keeper_authority,
+            owner_wallet: self.owner_wallet,
+            token_mint: self.token_mint,
+            reward_exclusions: self.reward_exclusions,
+            harvest_threshold: self.harvest_threshold,
+            total_fees_harvested: self.total_fees_harvested,
+            total_burned: 0,
+            total_rewards_distributed: self.total_rewards_distributed,
+            distribution_id: self.distribution_id,
+            pending_withheld: self.pending_withheld,
+            last_harvest_time: self.last_harvest_time,
+            last_harvest_amount: self.last_harvest_amount,
+            last_distribution_time: self.last_distribution_time,
+            launch_timestamp: self.launch_timestamp,
+            unsettled_distribution_id: 0,
+            unacknowledged_shortfall_id: 0,
+            burn_share_bps: 0,
+            pending_burn_amount: 0,
+            min_distribution_interval: 0,
+            withdrawal_window: 0,
+            max_withdrawal_per_window: 0,
+            max_threshold_multiple: 0,
+            window_start: 0,
+            window_withdrawn: 0,
+            last_keeper_heartbeat: now,
+            keeper_inactivity_period: 0,
+            crank_bounty_lamports: 0,
+            guardian: Pubkey::default(),
+            emergency_destinations: Vec::new(),
+            emergency_max_bps: BPS_DENOMINATOR as u16,
+            emergency_cooldown: EMERGENCY_COOLDOWN,
+            last_emergency_withdrawal: 0,
+            total_emergency_withdrawn: 0,
+            last_reconciliation: ReconciliationReport::default(),
+            reserved: [0; 128],
+        }
+    }
+}
+
+/// Unversioned `PoolRegistry` layout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PoolRegistryLayoutV0 {
     pub vault: Pubkey,
     #[max_len(50)]
-    pub entries: Vec<KeeperWorkEntry>,
+    pub pools: Vec<Pubkey>,
 }
 
+/// Unversioned `KeeperWorkLog` layout
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
-pub struct KeeperWorkEntry {
-    pub timestamp: i64,
-    pub work_type: KeeperWorkType,
-    pub amount: u64,
-    #[max_len(100)]
-    pub details: String,
+pub struct KeeperWorkLogLayoutV0 {
+    pub vault: Pubkey,
+    #[max_len(50)]
+    pub entries: Vec<KeeperWorkEntry>,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct PoolRegistry {
+/// `KeeperWorkLog` while it was Borsh encoded and shifted its entries on every append
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct KeeperWorkLogLayoutV1 {
+    pub version: u8,
     pub vault: Pubkey,
     #[max_len(50)]
-    pub pools: Vec<Pubkey>,
+    pub entries: Vec<KeeperWorkEntry>,
+    pub reserved: [u8; 64],
+}
+
+impl From<KeeperWorkLogLayoutV0> for KeeperWorkLogLayoutV1 {
+    fn from(v0: KeeperWorkLogLayoutV0) -> Self {
+        Self {
+            version: 1,
+            vault: v0.vault,
+            entries: v0.entries,
+            reserved: [0; 64],
+        }
+    }
+}
+
+/// `AssetLedger` while it was Borsh encoded and shifted its entries on every append
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AssetLedgerLayoutV0 {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub opening_balance: u64,
+    pub total_inflow: u64,
+    pub total_outflow: u64,
+    pub opened_at: i64,
+    pub last_reconciled_at: i64,
+    #[max_len(32)]
+    pub entries: Vec<LedgerEntry>,
 }
 
 // Enums
@@ -745,6 +5306,55 @@ pub enum KeeperWorkType {
     KeeperTopUp,
 }
 
+/// Least-privilege keeper duties; `vault.keeper_authority` holds all of them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum KeeperRoleKind {
+    Harvester,
+    Distributor,
+    PoolReporter,
+    Logger,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum LedgerEntryKind {
+    FeeHarvest,
+    SwapIn,
+    SwapOut,
+    DistributionOut,
+    Burn,
+    HolderClaim,
+    PayeeShare,
+    EmergencyOut,
+    DustSweep,
+}
+
+impl LedgerEntryKind {
+    pub fn is_inflow(&self) -> bool {
+        matches!(self, LedgerEntryKind::FeeHarvest | LedgerEntryKind::SwapIn)
+    }
+}
+
+/// AMMs the vault can swap through, each with a fixed CPI account layout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapAdapter {
+    RaydiumCpmm,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WithdrawalGuard {
+    MinInterval,
+    ThresholdMultiple,
+    WindowCap,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum DistributionStatus {
+    Pending,
+    Settled,
+    Shortfall,
+    ShortfallAcknowledged,
+}
+
 // Errors
 
 #[error_code]
@@ -775,4 +5385,157 @@ pub enum VaultError {
 
     #[msg("Launch time already set")]
     LaunchTimeAlreadySet,
+
+    #[msg("Previous distribution has not been settled")]
+    PreviousDistributionNotSettled,
+
+    #[msg("Unacknowledged distribution shortfall")]
+    UnacknowledgedShortfall,
+
+    #[msg("Distribution is not pending settlement")]
+    DistributionNotPending,
+
+    #[msg("No shortfall to acknowledge")]
+    NoShortfallToAcknowledge,
+
+    #[msg("Invalid reward token account")]
+    InvalidRewardTokenAccount,
+
+    #[msg("Invalid mint")]
+    InvalidMint,
+
+    #[msg("Invalid vault token account")]
+    InvalidVaultTokenAccount,
+
+    #[msg("Swap program does not match adapter")]
+    InvalidSwapProgram,
+
+    #[msg("Invalid swap accounts for adapter")]
+    InvalidSwapAccounts,
+
+    #[msg("Pool not registered")]
+    PoolNotRegistered,
+
+    #[msg("Swap output below expected minimum reward amount")]
+    SlippageExceeded,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("No checkpointed balance to fund")]
+    NoStakedBalance,
+
+    #[msg("Insufficient reward balance in vault")]
+    InsufficientRewardBalance,
+
+    #[msg("Holder is excluded from rewards")]
+    HolderExcluded,
+
+    #[msg("Invalid holder token account")]
+    InvalidHolderTokenAccount,
+
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+
+    #[msg("Owner share accounts do not match the revenue split")]
+    InvalidOwnerShareAccounts,
+
+    #[msg("No pending config change")]
+    NoPendingConfigChange,
+
+    #[msg("Config timelock has not elapsed")]
+    TimelockNotElapsed,
+
+    #[msg("Burn share exceeds 10000 bps")]
+    InvalidBurnShare,
+
+    #[msg("Harvested fees must be burned first")]
+    BurnPending,
+
+    #[msg("Nothing to burn")]
+    NothingToBurn,
+
+    #[msg("Invalid withdrawal guard configuration")]
+    InvalidWithdrawalGuards,
+
+    #[msg("Minimum interval between distributions not elapsed")]
+    DistributionTooSoon,
+
+    #[msg("Withdrawal exceeds harvest threshold cap")]
+    WithdrawalAboveThresholdCap,
+
+    #[msg("Withdrawal exceeds rolling window cap")]
+    WindowCapExceeded,
+
+    #[msg("Invalid keeper reserve configuration")]
+    InvalidReserveConfig,
+
+    #[msg("Keeper balance is above the top-up minimum")]
+    KeeperBalanceSufficient,
+
+    #[msg("Top-up exceeds per-period cap")]
+    TopUpCapExceeded,
+
+    #[msg("Insufficient keeper reserve")]
+    InsufficientReserve,
+
+    #[msg("Invalid liveness configuration")]
+    InvalidLivenessConfig,
+
+    #[msg("Invalid emergency policy")]
+    InvalidEmergencyPolicy,
+
+    #[msg("Guardian signature required")]
+    GuardianSignatureRequired,
+
+    #[msg("Destination not on emergency allowlist")]
+    DestinationNotAllowlisted,
+
+    #[msg("Emergency withdrawal exceeds cap")]
+    EmergencyAmountExceedsCap,
+
+    #[msg("Emergency withdrawal cooldown active")]
+    EmergencyCooldownActive,
+
+    #[msg("Vault mint cannot be recovered")]
+    CannotRecoverVaultMint,
+
+    #[msg("Invalid recovery accounts")]
+    InvalidRecoveryAccounts,
+
+    #[msg("Insufficient recoverable balance")]
+    InsufficientRecoverableBalance,
+
+    #[msg("Asset ledger does not track this token account")]
+    InvalidAssetLedger,
+
+    #[msg("Account is not in a migratable legacy layout")]
+    UnsupportedAccountLayout,
+
+    #[msg("Account must be migrated to the current layout first")]
+    AccountNotMigrated,
+
+    #[msg("Vault token account still holds MIKO")]
+    VaultNotEmpty,
+
+    #[msg("Distribution is still open")]
+    DistributionStillOpen,
+
+    #[msg("Reward positions are still staked or unclaimed")]
+    RewardsOutstanding,
+
+    #[msg("Protocol wallets cannot be removed from reward exclusions")]
+    ProtectedExclusion,
+
+    #[msg("No withheld fees were harvested from the given accounts")]
+    NothingToHarvest,
+
+    #[msg("No withheld fees to withdraw from the mint")]
+    NothingToWithdraw,
+
+    #[msg("Vault was closed for this mint and cannot be initialized again")]
+    VaultClosed,
+
+    #[msg("Reward tokens cannot be recovered as foreign assets")]
+    CannotRecoverRewardMint,
 }
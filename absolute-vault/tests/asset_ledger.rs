@@ -0,0 +1,211 @@
+mod common;
+
+use absolute_vault::{AssetLedger, LedgerEntryKind, VaultError};
+use common::*;
+use miko_client::pda;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const OPENING_BALANCE: u64 = 500;
+const WITHHELD: u64 = 1_000_000;
+
+struct LedgerFixture {
+    distribution: DistributionFixture,
+    asset_ledger: Pubkey,
+}
+
+impl LedgerFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let (ctx, distribution) = DistributionFixture::start(OPENING_BALANCE, WITHHELD).await;
+        let vault = &distribution.vault;
+        let fixture = Self {
+            asset_ledger: pda::asset_ledger_pda(&vault.vault, &vault.token_mint),
+            distribution,
+        };
+        (ctx, fixture)
+    }
+
+    fn vault(&self) -> &VaultFixture {
+        &self.distribution.vault
+    }
+
+    fn initialize_ledger_ix(&self, vault_token_account: Pubkey) -> Instruction {
+        let vault = self.vault();
+        self.vault().ix.initialize_asset_ledger(
+            &vault.primary_keeper(),
+            &vault.token_mint,
+            &vault_token_account,
+        )
+    }
+
+    fn withdraw_fees_ix(&self) -> Instruction {
+        self.vault()
+            .ix
+            .withdraw_fees_from_mint(&self.vault().primary_keeper())
+    }
+
+    fn withdraw_ix(&self, amount: u64) -> Instruction {
+        self.vault().ix.withdraw_and_report_distribution_plan(
+            &self.vault().primary_keeper(),
+            &distribution_plan(1, amount, 0),
+            &self.distribution.keeper_token_account,
+            &self.distribution.keeper_reward_token_account,
+            &[],
+        )
+    }
+
+    fn reconcile_ix(&self) -> Instruction {
+        let vault = self.vault();
+        self.vault()
+            .ix
+            .reconcile_asset_ledger(&vault.token_mint, &vault.vault_token_account)
+    }
+}
+
+#[tokio::test]
+async fn ledger_tracks_movements_attributed_to_distributions() {
+    let (mut ctx, fixture) = LedgerFixture::start().await;
+    // Distributions always withdraw the full vault balance
+    let distributed = OPENING_BALANCE + WITHHELD;
+
+    send(
+        &mut ctx,
+        &[
+            fixture.initialize_ledger_ix(fixture.vault().vault_token_account),
+            fixture.withdraw_fees_ix(),
+            fixture.withdraw_ix(distributed),
+            fixture.reconcile_ix(),
+        ],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap();
+
+    let ledger: AssetLedger = fetch_zero_copy(&mut ctx, &fixture.asset_ledger).await;
+    assert_eq!(ledger.opening_balance, OPENING_BALANCE);
+    assert_eq!(ledger.total_inflow, WITHHELD);
+    assert_eq!(ledger.total_outflow, distributed);
+    assert!(ledger.last_reconciled_at > 0);
+    assert_eq!(
+        ledger.balance().unwrap(),
+        token_balance(&mut ctx, &fixture.vault().vault_token_account).await
+    );
+
+    let entries: Vec<_> = ledger
+        .entries()
+        .iter()
+        .map(|entry| (entry.kind, entry.amount, entry.distribution_id))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (LedgerEntryKind::FeeHarvest, WITHHELD, 1),
+            (LedgerEntryKind::DistributionOut, distributed, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn ledger_requires_a_vault_owned_token_account() {
+    let (mut ctx, fixture) = LedgerFixture::start().await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.initialize_ledger_ix(fixture.distribution.keeper_token_account)],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidVaultTokenAccount.into())
+    );
+}
+
+#[tokio::test]
+async fn reconcile_leaves_unrecorded_movements_visible() {
+    let (mut ctx, fixture) = LedgerFixture::start().await;
+
+    send(
+        &mut ctx,
+        &[
+            fixture.initialize_ledger_ix(fixture.vault().vault_token_account),
+            fixture.withdraw_fees_ix(),
+        ],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap();
+    set_token_balance(
+        &mut ctx,
+        &fixture.vault().vault_token_account,
+        OPENING_BALANCE + WITHHELD + 7,
+    )
+    .await;
+
+    send(&mut ctx, &[fixture.reconcile_ix()], &[])
+        .await
+        .unwrap();
+    let ledger: AssetLedger = fetch_zero_copy(&mut ctx, &fixture.asset_ledger).await;
+    assert_eq!(ledger.balance().unwrap(), OPENING_BALANCE + WITHHELD);
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault().vault_token_account).await,
+        OPENING_BALANCE + WITHHELD + 7
+    );
+}
+
+#[tokio::test]
+async fn ledger_only_reconciles_against_its_own_token_account() {
+    let (mut ctx, fixture) = LedgerFixture::start().await;
+    send(
+        &mut ctx,
+        &[fixture.initialize_ledger_ix(fixture.vault().vault_token_account)],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap();
+
+    let reconcile_other = fixture.vault().ix.reconcile_asset_ledger(
+        &fixture.vault().token_mint,
+        &fixture.distribution.keeper_token_account,
+    );
+    let err = send(&mut ctx, &[reconcile_other], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::InvalidAssetLedger.into()));
+}
+
+#[tokio::test]
+async fn opened_ledger_cannot_be_skipped() {
+    let (mut ctx, fixture) = LedgerFixture::start().await;
+    send(
+        &mut ctx,
+        &[fixture.initialize_ledger_ix(fixture.vault().vault_token_account)],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap();
+
+    // An omitted optional account is passed as the program id
+    let mut without_ledger = fixture.withdraw_fees_ix();
+    for account in &mut without_ledger.accounts {
+        if account.pubkey == fixture.asset_ledger {
+            account.pubkey = absolute_vault::ID;
+        }
+    }
+    let err = send(&mut ctx, &[without_ledger], &[&fixture.vault().keeper])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(anchor_lang::error::ErrorCode::ConstraintSeeds.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix()],
+        &[&fixture.vault().keeper],
+    )
+    .await
+    .unwrap();
+    let ledger: AssetLedger = fetch_zero_copy(&mut ctx, &fixture.asset_ledger).await;
+    assert_eq!(ledger.total_inflow, WITHHELD);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use absolute_vault::VaultError;
+use common::*;
+use miko_client::vault::ConfigUpdate;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_token_2022::{extension::StateWithExtensions, state::Mint as MintState};
+
+const WITHHELD: u64 = 1_000_000;
+
+async fn start(burn_share_bps: u16) -> (ProgramTestContext, DistributionFixture) {
+    let (mut ctx, fixture) = DistributionFixture::start(0, WITHHELD).await;
+    fixture.harvest(&mut ctx, burn_share_bps).await;
+    (ctx, fixture)
+}
+
+fn burn_ix(fixture: &DistributionFixture) -> Instruction {
+    fixture.vault.ix.burn_harvested_fees()
+}
+
+fn withdraw_ix(fixture: &DistributionFixture, amount: u64) -> Instruction {
+    fixture.withdraw_ix(&distribution_plan(1, amount, 0))
+}
+
+async fn mint_supply(ctx: &mut ProgramTestContext, mint: &Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(*mint).await.unwrap().unwrap();
+    StateWithExtensions::<MintState>::unpack(&account.data)
+        .unwrap()
+        .base
+        .supply
+}
+
+#[tokio::test]
+async fn burn_share_of_harvest_is_burned_and_tracked() {
+    let (mut ctx, fixture) = start(2_000).await;
+    let burn_amount = WITHHELD / 5;
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.total_fees_harvested, WITHHELD);
+    assert_eq!(vault.pending_burn_amount, burn_amount);
+
+    let supply_before = mint_supply(&mut ctx, &fixture.vault.token_mint).await;
+    send(&mut ctx, &[burn_ix(&fixture)], &[]).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        WITHHELD - burn_amount
+    );
+    assert_eq!(
+        mint_supply(&mut ctx, &fixture.vault.token_mint).await,
+        supply_before - burn_amount
+    );
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.pending_burn_amount, 0);
+    assert_eq!(vault.total_burned, burn_amount);
+
+    // New blockhash so the identical retry is not deduplicated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(&mut ctx, &[burn_ix(&fixture)], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NothingToBurn.into()));
+}
+
+#[tokio::test]
+async fn distribution_waits_for_pending_burn() {
+    let (mut ctx, fixture) = start(2_000).await;
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, WITHHELD)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::BurnPending.into()));
+
+    let remaining = WITHHELD - WITHHELD / 5;
+    send(
+        &mut ctx,
+        &[burn_ix(&fixture), withdraw_ix(&fixture, remaining)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.keeper_token_account).await,
+        remaining
+    );
+}
+
+#[tokio::test]
+async fn burn_share_cannot_exceed_the_whole_harvest() {
+    let (mut ctx, fixture) = start(0).await;
+
+    let update_config = fixture.vault.update_config_ix(ConfigUpdate {
+        new_burn_share_bps: Some(10_001),
+        ..ConfigUpdate::default()
+    });
+    let err = send(&mut ctx, &[update_config], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::InvalidBurnShare.into()));
+}
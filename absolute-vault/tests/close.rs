@@ -0,0 +1,155 @@
+mod common;
+
+use absolute_vault::VaultError;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+struct CloseFixture {
+    vault: VaultFixture,
+    rent_receiver: Pubkey,
+}
+
+impl CloseFixture {
+    async fn start(vault_balance: u64) -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let fixture = Self {
+            vault: VaultFixture::new(Pubkey::new_unique()),
+            rent_receiver: Pubkey::new_unique(),
+        };
+        let vault = &fixture.vault;
+        pt.add_account(
+            vault.token_mint,
+            fee_mint_account(MIKO_DECIMALS, &Pubkey::new_unique(), &vault.vault, 0),
+        );
+        vault.add_vault_token_account(&mut pt, vault_balance);
+        vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+        (ctx, fixture)
+    }
+
+    fn close_pool_registry_ix(&self, authority: Pubkey) -> Instruction {
+        self.vault
+            .ix
+            .close_pool_registry(&authority, &self.rent_receiver)
+    }
+
+    fn close_vault_ix(&self) -> Instruction {
+        self.vault
+            .ix
+            .close_vault(&self.vault.authority.pubkey(), &self.rent_receiver)
+    }
+}
+
+async fn lamports(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    ctx.banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .map_or(0, |account| account.lamports)
+}
+
+#[tokio::test]
+async fn closing_auxiliary_accounts_returns_rent_to_the_receiver() {
+    let (mut ctx, fixture) = CloseFixture::start(0).await;
+    let registry_rent = lamports(&mut ctx, &fixture.vault.pool_registry).await;
+    let split_rent = lamports(&mut ctx, &fixture.vault.revenue_split).await;
+
+    let close_split = fixture
+        .vault
+        .ix
+        .close_revenue_split(&fixture.vault.authority.pubkey(), &fixture.rent_receiver);
+    send(
+        &mut ctx,
+        &[
+            fixture.close_pool_registry_ix(fixture.vault.authority.pubkey()),
+            close_split,
+        ],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(lamports(&mut ctx, &fixture.vault.pool_registry).await, 0);
+    assert_eq!(lamports(&mut ctx, &fixture.vault.revenue_split).await, 0);
+    assert_eq!(
+        lamports(&mut ctx, &fixture.rent_receiver).await,
+        registry_rent + split_rent
+    );
+}
+
+#[tokio::test]
+async fn closing_requires_the_vault_authority() {
+    let (mut ctx, fixture) = CloseFixture::start(0).await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.close_pool_registry_ix(fixture.vault.keeper.pubkey())],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+    assert!(lamports(&mut ctx, &fixture.vault.pool_registry).await > 0);
+}
+
+#[tokio::test]
+async fn vault_closes_only_once_emptied() {
+    let (mut ctx, fixture) = CloseFixture::start(10).await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.close_vault_ix()],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::VaultNotEmpty.into()));
+
+    set_token_balance(&mut ctx, &fixture.vault.vault_token_account, 0).await;
+
+    let vault_rent = lamports(&mut ctx, &fixture.vault.vault).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    send(
+        &mut ctx,
+        &[fixture.close_vault_ix()],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+    assert_eq!(lamports(&mut ctx, &fixture.vault.vault).await, 0);
+    assert_eq!(lamports(&mut ctx, &fixture.rent_receiver).await, vault_rent);
+}
+
+#[tokio::test]
+async fn closed_vault_cannot_be_initialized_again() {
+    let (mut ctx, fixture) = CloseFixture::start(0).await;
+    send(
+        &mut ctx,
+        &[fixture.close_vault_ix()],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+
+    // A new authority would otherwise take over every account the vault PDA still owns
+    let usurper = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let reinitialize = fixture.vault.ix.initialize(
+        &usurper.pubkey(),
+        &payer,
+        usurper.pubkey(),
+        usurper.pubkey(),
+    );
+    let err = send(&mut ctx, &[reinitialize], &[&usurper])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::VaultClosed.into()));
+    assert_eq!(lamports(&mut ctx, &fixture.vault.vault).await, 0);
+}
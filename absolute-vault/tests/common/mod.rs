@@ -0,0 +1,560 @@
+#![allow(dead_code)]
+
+use absolute_vault::VaultState;
+use anchor_lang::{system_program, AccountDeserialize, AccountSerialize, Discriminator, ZeroCopy};
+use bytemuck::Zeroable;
+use miko_client::{
+    pda,
+    vault::{ConfigUpdate, DistributionPlan},
+    Keeper, VaultInstructions,
+};
+use miko_transfer_hook::HolderCheckpoint;
+use smart_dial::{DialState, DIAL_STATE_LEN, DIAL_STATE_VERSION};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccountState, AccountState, Mint as MintState},
+};
+
+pub const MIKO_DECIMALS: u8 = 9;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint wants matching lifetimes on the slice and the infos
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    absolute_vault::entry(program_id, accounts, data)
+}
+
+pub fn program_test() -> ProgramTest {
+    let mut pt = ProgramTest::new(
+        "absolute_vault",
+        absolute_vault::id(),
+        processor!(process_instruction),
+    );
+    pt.prefer_bpf(false);
+    pt
+}
+
+pub fn mint_account(decimals: u8, mint_authority: &Pubkey) -> Account {
+    let mut data = vec![0; MintState::LEN];
+    MintState {
+        mint_authority: COption::Some(*mint_authority),
+        supply: u64::MAX / 2,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Mint with a transfer fee config holding `withheld` tokens for `withdraw_authority`
+pub fn fee_mint_account(
+    decimals: u8,
+    mint_authority: &Pubkey,
+    withdraw_authority: &Pubkey,
+    withheld: u64,
+) -> Account {
+    let len =
+        ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0; len];
+    let mut state = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.withdraw_withheld_authority = Some(*withdraw_authority).try_into().unwrap();
+    config.withheld_amount = withheld.into();
+    state.base = MintState {
+        mint_authority: COption::Some(*mint_authority),
+        supply: u64::MAX / 2,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; TokenAccountState::LEN];
+    TokenAccountState {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccountState::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Smart dial state selecting `reward_token`, at its current zero-copy length
+pub fn dial_account(reward_token: &Pubkey) -> Account {
+    let mut dial = DialState::zeroed();
+    dial.current_reward_token = *reward_token;
+    dial.version = DIAL_STATE_VERSION;
+    let mut data = DialState::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&dial));
+    assert_eq!(data.len(), DIAL_STATE_LEN);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: smart_dial::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Place the dial at its PDA, selecting `reward_token`
+pub fn add_dial(pt: &mut ProgramTest, reward_token: &Pubkey) {
+    pt.add_account(pda::dial_state_pda(), dial_account(reward_token));
+}
+
+/// Transfer-hook checkpoint of `token_account`, holding `balance` since the epoch
+pub fn holder_checkpoint_account(
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+    balance: u64,
+) -> Account {
+    let checkpoint = HolderCheckpoint {
+        mint: *mint,
+        token_account: *token_account,
+        owner: *owner,
+        balance,
+        last_change: 0,
+        accumulated_balance_seconds: 0,
+        initialized_at: 0,
+    };
+    let mut data = Vec::new();
+    checkpoint.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: miko_transfer_hook::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Place a hooked MIKO account holding `balance`, with its checkpoint at its PDA
+pub fn add_hooked_token_account(
+    pt: &mut ProgramTest,
+    address: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    balance: u64,
+) {
+    pt.add_account(address, token_account(mint, owner, balance));
+    pt.add_account(
+        pda::holder_checkpoint_pda(&address),
+        holder_checkpoint_account(mint, &address, owner, balance),
+    );
+}
+
+/// Open a hooked MIKO account holding `balance` on a running bank
+pub fn set_hooked_token_account(
+    ctx: &mut ProgramTestContext,
+    address: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    balance: u64,
+) {
+    ctx.set_account(&address, &token_account(mint, owner, balance).into());
+    ctx.set_account(
+        &pda::holder_checkpoint_pda(&address),
+        &holder_checkpoint_account(mint, &address, owner, balance).into(),
+    );
+}
+
+pub fn funded_account() -> Account {
+    Account {
+        lamports: 10_000_000_000,
+        owner: system_program::ID,
+        ..Account::default()
+    }
+}
+
+pub async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    // Lets `get_new_latest_blockhash` guarantee a retry gets a different signature
+    ctx.last_blockhash = blockhash;
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/// Advance the bank clock by `seconds`
+pub async fn warp_by(ctx: &mut ProgramTestContext, seconds: i64) {
+    // Move to a fresh bank first: a clock set on the current bank would be
+    // replaced by wall-clock time as soon as the next bank starts
+    let before: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    ctx.warp_to_slot(before.slot + 2).unwrap();
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = before.unix_timestamp + seconds;
+    ctx.set_sysvar(&clock);
+}
+
+/// Custom program error code carried by a failed transaction
+pub fn error_code(err: BanksClientError) -> Option<u32> {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+pub async fn token_balance(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = ctx
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    TokenAccountState::unpack_from_slice(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// Overwrite the balance of a token account, as a transfer the vault never saw would
+pub async fn set_token_balance(ctx: &mut ProgramTestContext, address: &Pubkey, amount: u64) {
+    let mut account = ctx
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = TokenAccountState::unpack_from_slice(&account.data).unwrap();
+    state.amount = amount;
+    state.pack_into_slice(&mut account.data);
+    ctx.set_account(address, &account.into());
+}
+
+/// Move MIKO between hooked token accounts, recording both checkpoints as the
+/// transfer hook would
+pub async fn hooked_transfer(
+    ctx: &mut ProgramTestContext,
+    from: &Pubkey,
+    to: &Pubkey,
+    amount: u64,
+) {
+    let now = ctx
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    for (address, balance) in [
+        (from, token_balance(ctx, from).await - amount),
+        (to, token_balance(ctx, to).await + amount),
+    ] {
+        set_token_balance(ctx, address, balance).await;
+
+        let checkpoint_address = pda::holder_checkpoint_pda(address);
+        let mut checkpoint: HolderCheckpoint = fetch(ctx, &checkpoint_address).await;
+        checkpoint.record(balance, now);
+        let mut account = ctx
+            .banks_client
+            .get_account(checkpoint_address)
+            .await
+            .unwrap()
+            .unwrap();
+        account.data.clear();
+        checkpoint.try_serialize(&mut account.data).unwrap();
+        ctx.set_account(&checkpoint_address, &account.into());
+    }
+}
+
+/// Point the dial at `reward_token`, as a dial update would
+pub fn set_dial(ctx: &mut ProgramTestContext, reward_token: &Pubkey) {
+    ctx.set_account(&pda::dial_state_pda(), &dial_account(reward_token).into());
+}
+
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = ctx
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Zero-copy account of type `T`, checking its discriminator
+pub async fn fetch_zero_copy<T: ZeroCopy>(ctx: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = ctx
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    let fields = account.data.strip_prefix(T::DISCRIMINATOR).unwrap();
+    bytemuck::pod_read_unaligned(&fields[..std::mem::size_of::<T>()])
+}
+
+pub async fn fetch_vault(ctx: &mut ProgramTestContext, vault: &Pubkey) -> VaultState {
+    fetch(ctx, vault).await
+}
+
+/// Keys of a freshly initialized vault with its pool registry and revenue split
+pub struct VaultFixture {
+    pub authority: Keypair,
+    pub keeper: Keypair,
+    pub owner: Keypair,
+    pub token_mint: Pubkey,
+    pub vault: Pubkey,
+    pub pool_registry: Pubkey,
+    pub revenue_split: Pubkey,
+    /// Vault-owned MIKO account; `add_vault_token_account` creates it
+    pub vault_token_account: Pubkey,
+    pub ix: VaultInstructions,
+}
+
+impl VaultFixture {
+    pub fn new(token_mint: Pubkey) -> Self {
+        Self::with_vault_token_account(token_mint, Pubkey::new_unique())
+    }
+
+    /// Vault whose MIKO account is created at `vault_token_account` by the test
+    pub fn with_vault_token_account(token_mint: Pubkey, vault_token_account: Pubkey) -> Self {
+        let ix = VaultInstructions::new(token_mint, vault_token_account);
+        Self {
+            authority: Keypair::new(),
+            keeper: Keypair::new(),
+            owner: Keypair::new(),
+            token_mint,
+            vault: ix.vault,
+            pool_registry: pda::pool_registry_pda(&ix.vault),
+            revenue_split: pda::revenue_split_pda(&ix.vault),
+            vault_token_account,
+            ix,
+        }
+    }
+
+    /// `vault.keeper_authority`, signing without a role grant
+    pub fn primary_keeper(&self) -> Keeper {
+        Keeper::primary(self.keeper.pubkey())
+    }
+
+    /// Fund the vault's signers so they can pay for accounts they create
+    pub fn add_signers(&self, pt: &mut ProgramTest) {
+        pt.add_account(self.authority.pubkey(), funded_account());
+        pt.add_account(self.keeper.pubkey(), funded_account());
+        pt.add_account(self.owner.pubkey(), funded_account());
+    }
+
+    pub fn add_vault_token_account(&self, pt: &mut ProgramTest, amount: u64) {
+        pt.add_account(
+            self.vault_token_account,
+            token_account(&self.token_mint, &self.vault, amount),
+        );
+    }
+
+    /// Plain MIKO mint, signers and vault, for tests that move no MIKO
+    pub async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let fixture = Self::new(Pubkey::new_unique());
+        pt.add_account(
+            fixture.token_mint,
+            mint_account(MIKO_DECIMALS, &Pubkey::new_unique()),
+        );
+        fixture.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        fixture.initialize(&mut ctx).await;
+        (ctx, fixture)
+    }
+
+    pub async fn initialize(&self, ctx: &mut ProgramTestContext) {
+        let payer = ctx.payer.pubkey();
+        let authority = self.authority.pubkey();
+        let instructions = [
+            self.ix.initialize(
+                &authority,
+                &payer,
+                self.owner.pubkey(),
+                self.keeper.pubkey(),
+            ),
+            self.ix.initialize_pool_registry(&payer),
+            self.ix.initialize_revenue_split(&authority, &payer),
+        ];
+        send(ctx, &instructions, &[&self.authority]).await.unwrap();
+    }
+
+    /// Create the owner share account the default revenue split pays the owner into
+    pub async fn initialize_owner_share(&self, ctx: &mut ProgramTestContext, reward_mint: &Pubkey) {
+        let payer = ctx.payer.pubkey();
+        let ix = self.ix.initialize_owner_share(
+            &self.owner.pubkey(),
+            reward_mint,
+            &spl_token_2022::id(),
+            &payer,
+        );
+        send(ctx, &[ix], &[]).await.unwrap();
+    }
+
+    /// `update_config` signed by the vault authority
+    pub fn update_config_ix(&self, update: ConfigUpdate) -> Instruction {
+        self.ix.update_config(&self.authority.pubkey(), &update)
+    }
+
+    pub fn update_pool_registry_ix(&self, pools_to_add: Vec<Pubkey>) -> Instruction {
+        self.ix
+            .update_pool_registry(&self.primary_keeper(), pools_to_add)
+    }
+
+    pub fn withdraw_fees_ix(&self) -> Instruction {
+        self.ix.withdraw_fees_from_mint(&self.primary_keeper())
+    }
+}
+
+/// Plan recorded under `distribution_id`, withdrawing `amount`
+pub fn distribution_plan(
+    distribution_id: u64,
+    amount: u64,
+    expected_minimum_reward_amount: u64,
+) -> DistributionPlan {
+    DistributionPlan {
+        distribution_id,
+        amount,
+        expected_minimum_reward_amount,
+        distribution_hash: [0; 32],
+    }
+}
+
+/// Vault over a fee-bearing MIKO mint, with the keeper accounts a raw-MIKO
+/// distribution pays into
+pub struct DistributionFixture {
+    pub vault: VaultFixture,
+    pub reward_mint: Pubkey,
+    pub keeper_token_account: Pubkey,
+    pub keeper_reward_token_account: Pubkey,
+}
+
+impl DistributionFixture {
+    /// `vault_balance` MIKO in the vault and `withheld` fees waiting in the mint
+    pub async fn start(vault_balance: u64, withheld: u64) -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let fixture = Self {
+            vault: VaultFixture::new(Pubkey::new_unique()),
+            reward_mint: Pubkey::new_unique(),
+            keeper_token_account: Pubkey::new_unique(),
+            keeper_reward_token_account: Pubkey::new_unique(),
+        };
+        let vault = &fixture.vault;
+
+        let mint_authority = Pubkey::new_unique();
+        pt.add_account(
+            vault.token_mint,
+            fee_mint_account(MIKO_DECIMALS, &mint_authority, &vault.vault, withheld),
+        );
+        pt.add_account(fixture.reward_mint, mint_account(6, &mint_authority));
+        vault.add_vault_token_account(&mut pt, vault_balance);
+        let keeper = vault.keeper.pubkey();
+        pt.add_account(
+            fixture.keeper_token_account,
+            token_account(&vault.token_mint, &keeper, 0),
+        );
+        pt.add_account(
+            fixture.keeper_reward_token_account,
+            token_account(&fixture.reward_mint, &keeper, 0),
+        );
+        add_dial(&mut pt, &fixture.reward_mint);
+        vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+        vault
+            .initialize_owner_share(&mut ctx, &fixture.reward_mint)
+            .await;
+        (ctx, fixture)
+    }
+
+    /// Withdraw the mint's withheld fees into the vault, earmarking `burn_share_bps`
+    /// of them for burning
+    pub async fn harvest(&self, ctx: &mut ProgramTestContext, burn_share_bps: u16) {
+        let update_config = self.vault.update_config_ix(ConfigUpdate {
+            new_burn_share_bps: Some(burn_share_bps),
+            ..ConfigUpdate::default()
+        });
+        send(ctx, &[update_config], &[&self.vault.authority])
+            .await
+            .unwrap();
+        send(ctx, &[self.vault.withdraw_fees_ix()], &[&self.vault.keeper])
+            .await
+            .unwrap();
+    }
+
+    pub fn withdraw_ix(&self, plan: &DistributionPlan) -> Instruction {
+        self.vault.ix.withdraw_and_report_distribution_plan(
+            &self.vault.primary_keeper(),
+            plan,
+            &self.keeper_token_account,
+            &self.keeper_reward_token_account,
+            &[],
+        )
+    }
+
+    pub fn settle_ix(&self, distribution_id: u64) -> Instruction {
+        self.vault.ix.settle_distribution(
+            &self.vault.primary_keeper(),
+            distribution_id,
+            &self.keeper_reward_token_account,
+            &self.reward_mint,
+            &spl_token_2022::id(),
+            &[self.vault.owner.pubkey()],
+        )
+    }
+
+    pub fn acknowledge_ix(&self, distribution_id: u64) -> Instruction {
+        self.vault
+            .ix
+            .acknowledge_shortfall(&self.vault.authority.pubkey(), distribution_id)
+    }
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use absolute_vault::{ExclusionAction, VaultError, MAX_EXCLUSIONS};
+use common::*;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+/// Exclusion updates that fit in one transaction
+const EXCLUSIONS_PER_TX: usize = 19;
+
+fn exclusion_ix(fixture: &VaultFixture, action: ExclusionAction, wallet: Pubkey) -> Instruction {
+    fixture
+        .ix
+        .manage_exclusions(&fixture.authority.pubkey(), action, wallet)
+}
+
+#[tokio::test]
+async fn exclusions_reject_duplicates_and_stop_at_capacity() {
+    let (mut ctx, fixture) = VaultFixture::start().await;
+
+    let err = send(
+        &mut ctx,
+        &[exclusion_ix(
+            &fixture,
+            ExclusionAction::Add,
+            fixture.owner.pubkey(),
+        )],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::AlreadyExcluded.into()));
+
+    let initial = fetch_vault(&mut ctx, &fixture.vault)
+        .await
+        .reward_exclusions
+        .len();
+    let wallets: Vec<Pubkey> = (initial..MAX_EXCLUSIONS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for chunk in wallets.chunks(EXCLUSIONS_PER_TX) {
+        let instructions: Vec<Instruction> = chunk
+            .iter()
+            .map(|wallet| exclusion_ix(&fixture, ExclusionAction::Add, *wallet))
+            .collect();
+        send(&mut ctx, &instructions, &[&fixture.authority])
+            .await
+            .unwrap();
+    }
+    let vault = fetch_vault(&mut ctx, &fixture.vault).await;
+    assert_eq!(vault.reward_exclusions.len(), MAX_EXCLUSIONS);
+
+    let extra = Pubkey::new_unique();
+    let err = send(
+        &mut ctx,
+        &[exclusion_ix(&fixture, ExclusionAction::Add, extra)],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::ExclusionListFull.into()));
+
+    // Removing one makes room again
+    send(
+        &mut ctx,
+        &[
+            exclusion_ix(&fixture, ExclusionAction::Remove, wallets[0]),
+            exclusion_ix(&fixture, ExclusionAction::Add, extra),
+        ],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+    let vault = fetch_vault(&mut ctx, &fixture.vault).await;
+    assert!(vault.is_excluded(&extra));
+    assert!(!vault.is_excluded(&wallets[0]));
+}
+
+#[tokio::test]
+async fn launch_time_is_set_once() {
+    let (mut ctx, fixture) = VaultFixture::start().await;
+    let set_launch_time = fixture.ix.set_launch_time();
+
+    send(&mut ctx, std::slice::from_ref(&set_launch_time), &[])
+        .await
+        .unwrap();
+    let launch_timestamp = fetch_vault(&mut ctx, &fixture.vault).await.launch_timestamp;
+    assert!(launch_timestamp > 0);
+
+    warp_by(&mut ctx, 60).await;
+    let err = send(&mut ctx, &[set_launch_time], &[]).await.unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::LaunchTimeAlreadySet.into())
+    );
+    assert_eq!(
+        fetch_vault(&mut ctx, &fixture.vault).await.launch_timestamp,
+        launch_timestamp
+    );
+}
+
+#[tokio::test]
+async fn protocol_wallets_stay_excluded() {
+    let (mut ctx, fixture) = VaultFixture::start().await;
+
+    for wallet in [
+        fixture.authority.pubkey(),
+        fixture.keeper.pubkey(),
+        fixture.owner.pubkey(),
+        fixture.vault,
+        absolute_vault::id(),
+    ] {
+        let err = send(
+            &mut ctx,
+            &[exclusion_ix(&fixture, ExclusionAction::Remove, wallet)],
+            &[&fixture.authority],
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error_code(err), Some(VaultError::ProtectedExclusion.into()));
+    }
+    let vault = fetch_vault(&mut ctx, &fixture.vault).await;
+    assert_eq!(vault.reward_exclusions.len(), 5);
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use absolute_vault::VaultError;
+use common::*;
+use miko_client::vault::EmergencyTransfer;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const VAULT_BALANCE: u64 = 1_000_000;
+const COOLDOWN: i64 = 3_600;
+
+struct EmergencyFixture {
+    vault: VaultFixture,
+    guardian: Keypair,
+    treasury: Pubkey,
+    other_destination: Pubkey,
+}
+
+impl EmergencyFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let fixture = Self {
+            vault: VaultFixture::new(Pubkey::new_unique()),
+            guardian: Keypair::new(),
+            treasury: Pubkey::new_unique(),
+            other_destination: Pubkey::new_unique(),
+        };
+        let vault = &fixture.vault;
+
+        pt.add_account(
+            vault.token_mint,
+            mint_account(MIKO_DECIMALS, &Pubkey::new_unique()),
+        );
+        vault.add_vault_token_account(&mut pt, VAULT_BALANCE);
+        let outsider = Pubkey::new_unique();
+        for destination in [fixture.treasury, fixture.other_destination] {
+            pt.add_account(destination, token_account(&vault.token_mint, &outsider, 0));
+        }
+        vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+        (ctx, fixture)
+    }
+
+    fn policy_ix(&self, guardian: Option<Pubkey>, max_bps: u16) -> Instruction {
+        self.vault.ix.update_emergency_policy(
+            &self.vault.authority.pubkey(),
+            guardian,
+            self.guardian.pubkey(),
+            vec![self.treasury],
+            max_bps,
+            COOLDOWN,
+        )
+    }
+
+    fn withdraw_ix(
+        &self,
+        destination: Pubkey,
+        amount: u64,
+        guardian: Option<Pubkey>,
+    ) -> Instruction {
+        let transfer = EmergencyTransfer {
+            mint: self.vault.token_mint,
+            vault_token_account: self.vault.vault_token_account,
+            destination_token_account: destination,
+            token_program: spl_token_2022::id(),
+            amount,
+            reason_code: 1,
+        };
+        self.vault.ix.emergency_withdraw_vault(
+            &self.vault.authority.pubkey(),
+            guardian,
+            &transfer,
+            &[],
+        )
+    }
+}
+
+#[tokio::test]
+async fn emergency_withdrawals_follow_allowlist_cap_and_cooldown() {
+    let (mut ctx, fixture) = EmergencyFixture::start().await;
+    let authority = &fixture.vault.authority;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.treasury, 1, None)],
+        &[authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::DestinationNotAllowlisted.into())
+    );
+
+    send(&mut ctx, &[fixture.policy_ix(None, 5_000)], &[authority])
+        .await
+        .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.treasury, VAULT_BALANCE / 2 + 1, None)],
+        &[authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::EmergencyAmountExceedsCap.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.treasury, VAULT_BALANCE / 2, None)],
+        &[authority],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.treasury).await,
+        VAULT_BALANCE / 2
+    );
+
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.treasury, 1, None)],
+        &[authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::EmergencyCooldownActive.into())
+    );
+
+    warp_by(&mut ctx, COOLDOWN).await;
+    send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.treasury, 1, None)],
+        &[authority],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn guardian_cosignature_lifts_limits_and_guards_policy() {
+    let (mut ctx, fixture) = EmergencyFixture::start().await;
+    let authority = &fixture.vault.authority;
+    let guardian = fixture.guardian.pubkey();
+
+    send(&mut ctx, &[fixture.policy_ix(None, 1_000)], &[authority])
+        .await
+        .unwrap();
+
+    let err = send(&mut ctx, &[fixture.policy_ix(None, 10_000)], &[authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::GuardianSignatureRequired.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.withdraw_ix(fixture.other_destination, VAULT_BALANCE, Some(guardian))],
+        &[authority, &fixture.guardian],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.other_destination).await,
+        VAULT_BALANCE
+    );
+}
+
+#[tokio::test]
+async fn policy_rejects_oversized_caps_and_destination_lists() {
+    let (mut ctx, fixture) = EmergencyFixture::start().await;
+
+    let policy_ix = |destinations: Vec<Pubkey>, max_bps: u16, cooldown: i64| {
+        fixture.vault.ix.update_emergency_policy(
+            &fixture.vault.authority.pubkey(),
+            None,
+            fixture.guardian.pubkey(),
+            destinations,
+            max_bps,
+            cooldown,
+        )
+    };
+    let too_many = (0..=absolute_vault::MAX_EMERGENCY_DESTINATIONS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+
+    for ix in [
+        policy_ix(vec![fixture.treasury], 10_001, COOLDOWN),
+        policy_ix(vec![fixture.treasury], 1_000, -1),
+        policy_ix(too_many, 1_000, COOLDOWN),
+    ] {
+        let err = send(&mut ctx, &[ix], &[&fixture.vault.authority])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error_code(err),
+            Some(VaultError::InvalidEmergencyPolicy.into())
+        );
+    }
+}
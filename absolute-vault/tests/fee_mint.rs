@@ -0,0 +1,487 @@
+mod common;
+
+use absolute_vault::{
+    AssetLedger, DistributionRecord, DistributionStatus, KeeperWorkLog, KeeperWorkType,
+    LedgerEntryKind, VaultError,
+};
+use common::*;
+use miko_client::{
+    pda,
+    vault::{ConfigUpdate, DistributionPlan},
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction as transfer_fee_instruction, TransferFeeConfig},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    instruction as token_instruction,
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+
+/// The MIKO launch fee: 6% of every transfer is withheld
+const FEE_BPS: u16 = 600;
+const MINTED: u64 = 10_000_000_000;
+const TRANSFER: u64 = 1_000_000_000;
+const TRANSFERS: u64 = 3;
+
+/// Token-2022 rounds the fee up
+fn fee_of(amount: u64) -> u64 {
+    (amount * FEE_BPS as u64).div_ceil(10_000)
+}
+
+/// A vault over a real Token-2022 mint whose withheld fees only the vault PDA can withdraw
+struct FeeMintFixture {
+    vault: VaultFixture,
+    mint_authority: Keypair,
+    sender: Keypair,
+    sender_account: Pubkey,
+    receiver_account: Pubkey,
+    keeper_token_account: Pubkey,
+    keeper_reward_token_account: Pubkey,
+}
+
+impl FeeMintFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let mint = Keypair::new();
+        let vault_token_account = Keypair::new();
+        let vault =
+            VaultFixture::with_vault_token_account(mint.pubkey(), vault_token_account.pubkey());
+        let reward_mint = Pubkey::new_unique();
+        let keeper_reward_token_account = Pubkey::new_unique();
+        pt.add_account(reward_mint, mint_account(6, &Pubkey::new_unique()));
+        add_dial(&mut pt, &reward_mint);
+        pt.add_account(
+            keeper_reward_token_account,
+            token_account(&reward_mint, &vault.keeper.pubkey(), 0),
+        );
+        vault.add_signers(&mut pt);
+        let mut ctx = pt.start_with_context().await;
+
+        let mint_authority = Keypair::new();
+        let payer = ctx.payer.pubkey();
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let create_mint = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            transfer_fee_instruction::initialize_transfer_fee_config(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                Some(&vault.authority.pubkey()),
+                Some(&vault.vault),
+                FEE_BPS,
+                u64::MAX,
+            )
+            .unwrap(),
+            token_instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                &mint_authority.pubkey(),
+                None,
+                MIKO_DECIMALS,
+            )
+            .unwrap(),
+        ];
+        send(&mut ctx, &create_mint, &[&mint]).await.unwrap();
+        vault.initialize(&mut ctx).await;
+
+        create_token_account_at(&mut ctx, vault_token_account, &mint.pubkey(), &vault.vault).await;
+        let sender = Keypair::new();
+        let fixture = Self {
+            sender_account: create_token_account(&mut ctx, &mint.pubkey(), &sender.pubkey()).await,
+            receiver_account: create_token_account(&mut ctx, &mint.pubkey(), &Pubkey::new_unique())
+                .await,
+            keeper_token_account: create_token_account(
+                &mut ctx,
+                &mint.pubkey(),
+                &vault.keeper.pubkey(),
+            )
+            .await,
+            keeper_reward_token_account,
+            mint_authority,
+            sender,
+            vault,
+        };
+
+        let mint_to = token_instruction::mint_to(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &fixture.sender_account,
+            &fixture.mint_authority.pubkey(),
+            &[],
+            MINTED,
+        )
+        .unwrap();
+        send(&mut ctx, &[mint_to], &[&fixture.mint_authority])
+            .await
+            .unwrap();
+
+        (ctx, fixture)
+    }
+
+    fn transfer_ix(&self, amount: u64) -> Instruction {
+        transfer_fee_instruction::transfer_checked_with_fee(
+            &spl_token_2022::id(),
+            &self.sender_account,
+            &self.vault.token_mint,
+            &self.receiver_account,
+            &self.sender.pubkey(),
+            &[],
+            amount,
+            MIKO_DECIMALS,
+            fee_of(amount),
+        )
+        .unwrap()
+    }
+
+    fn harvest_ix(&self, accounts: Vec<Pubkey>) -> Instruction {
+        self.vault
+            .ix
+            .harvest_fees(&self.vault.primary_keeper(), &accounts)
+    }
+
+    fn set_threshold_ix(&self, threshold: u64) -> Instruction {
+        self.vault.update_config_ix(ConfigUpdate {
+            new_harvest_threshold: Some(threshold),
+            ..ConfigUpdate::default()
+        })
+    }
+
+    fn withdraw_and_report_ix(&self, amount: u64) -> Instruction {
+        let plan = DistributionPlan {
+            distribution_hash: [9; 32],
+            ..distribution_plan(1, amount, 0)
+        };
+        self.vault.ix.withdraw_and_report_distribution_plan(
+            &self.vault.primary_keeper(),
+            &plan,
+            &self.keeper_token_account,
+            &self.keeper_reward_token_account,
+            &[],
+        )
+    }
+
+    fn log_work_ix(&self, work_type: KeeperWorkType, amount: u64) -> Instruction {
+        self.vault.ix.log_keeper_work(
+            &self.vault.primary_keeper(),
+            work_type,
+            amount,
+            "harvest and distribute".to_string(),
+        )
+    }
+}
+
+/// Token-2022 account sized for the transfer fee extension the mint requires
+async fn create_token_account(
+    ctx: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    create_token_account_at(ctx, Keypair::new(), mint, owner).await
+}
+
+async fn create_token_account_at(
+    ctx: &mut ProgramTestContext,
+    account: Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let space = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &ctx.payer.pubkey(),
+            &account.pubkey(),
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        token_instruction::initialize_account3(
+            &spl_token_2022::id(),
+            &account.pubkey(),
+            mint,
+            owner,
+        )
+        .unwrap(),
+    ];
+    send(ctx, &instructions, &[&account]).await.unwrap();
+    account.pubkey()
+}
+
+async fn mint_withheld(ctx: &mut ProgramTestContext, mint: &Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(*mint).await.unwrap().unwrap();
+    let state = StateWithExtensions::<MintState>::unpack(&account.data).unwrap();
+    state
+        .get_extension::<TransferFeeConfig>()
+        .unwrap()
+        .withheld_amount
+        .into()
+}
+
+async fn token_amount(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = ctx
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    StateWithExtensions::<TokenAccountState>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[tokio::test]
+async fn transfer_fees_flow_from_holders_to_the_keeper() {
+    let (mut ctx, fixture) = FeeMintFixture::start().await;
+    let transfers: Vec<Instruction> = (0..TRANSFERS)
+        .map(|i| fixture.transfer_ix(TRANSFER + i))
+        .collect();
+    send(&mut ctx, &transfers, &[&fixture.sender])
+        .await
+        .unwrap();
+    let fees: u64 = (0..TRANSFERS).map(|i| fee_of(TRANSFER + i)).sum();
+    let sent: u64 = (0..TRANSFERS).map(|i| TRANSFER + i).sum();
+    assert_eq!(
+        token_amount(&mut ctx, &fixture.receiver_account).await,
+        sent - fees
+    );
+
+    // The threshold is measured against fees already harvested to the mint
+    send(
+        &mut ctx,
+        &[
+            fixture.set_threshold_ix(0),
+            fixture.harvest_ix(vec![fixture.receiver_account, fixture.sender_account]),
+        ],
+        &[&fixture.vault.authority, &fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        mint_withheld(&mut ctx, &fixture.vault.token_mint).await,
+        fees
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.vault.withdraw_fees_ix()],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    assert_eq!(mint_withheld(&mut ctx, &fixture.vault.token_mint).await, 0);
+    assert_eq!(
+        token_amount(&mut ctx, &fixture.vault.vault_token_account).await,
+        fees
+    );
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.total_fees_harvested, fees);
+    assert!(vault.last_harvest_time > 0);
+
+    send(
+        &mut ctx,
+        &[
+            fixture.withdraw_and_report_ix(fees),
+            fixture.log_work_ix(KeeperWorkType::HarvestFees, fees),
+        ],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    // Moving fees to the keeper is itself a taxed transfer
+    assert_eq!(
+        token_amount(&mut ctx, &fixture.vault.vault_token_account).await,
+        0
+    );
+    assert_eq!(
+        token_amount(&mut ctx, &fixture.keeper_token_account).await,
+        fees - fee_of(fees)
+    );
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.distribution_id, 1);
+    assert_eq!(vault.total_rewards_distributed, fees);
+    assert_eq!(vault.unsettled_distribution_id, 1);
+
+    let record: DistributionRecord =
+        fetch(&mut ctx, &pda::distribution_pda(&fixture.vault.vault, 1)).await;
+    assert_eq!(record.status, DistributionStatus::Pending);
+    assert_eq!(record.withdrawn_miko_amount, fees);
+    assert_eq!(record.keeper, fixture.vault.keeper.pubkey());
+
+    let log: KeeperWorkLog =
+        fetch_zero_copy(&mut ctx, &pda::keeper_log_pda(&fixture.vault.vault)).await;
+    let entries = log.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].work_type, KeeperWorkType::HarvestFees);
+    assert_eq!(entries[0].amount, fees);
+}
+
+#[tokio::test]
+async fn harvest_waits_for_the_threshold_and_bounds_its_batch() {
+    let (mut ctx, fixture) = FeeMintFixture::start().await;
+    send(
+        &mut ctx,
+        &[fixture.transfer_ix(TRANSFER)],
+        &[&fixture.sender],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.harvest_ix(vec![fixture.receiver_account])],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::HarvestThresholdNotMet.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.set_threshold_ix(0)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+    let oversized = vec![fixture.receiver_account; 21];
+    for accounts in [vec![], oversized] {
+        let err = send(
+            &mut ctx,
+            &[fixture.harvest_ix(accounts)],
+            &[&fixture.vault.keeper],
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error_code(err), Some(VaultError::InvalidBatchSize.into()));
+    }
+
+    // Nothing left the holder's account while the harvest was refused
+    send(
+        &mut ctx,
+        &[fixture.harvest_ix(vec![fixture.receiver_account])],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        mint_withheld(&mut ctx, &fixture.vault.token_mint).await,
+        fee_of(TRANSFER)
+    );
+}
+
+#[tokio::test]
+async fn emergency_withheld_withdrawal_is_capped_and_recorded() {
+    let (mut ctx, fixture) = FeeMintFixture::start().await;
+    let vault = &fixture.vault;
+    let treasury = create_token_account(&mut ctx, &vault.token_mint, &Pubkey::new_unique()).await;
+    send(
+        &mut ctx,
+        &[fixture.transfer_ix(TRANSFER)],
+        &[&fixture.sender],
+    )
+    .await
+    .unwrap();
+
+    let policy_ix = vault.ix.update_emergency_policy(
+        &vault.authority.pubkey(),
+        None,
+        Pubkey::new_unique(),
+        vec![treasury],
+        5_000,
+        0,
+    );
+    let withdraw_ix = vault.ix.emergency_withdraw_withheld(
+        &vault.authority.pubkey(),
+        None,
+        &treasury,
+        &[fixture.receiver_account],
+        1,
+    );
+    send(
+        &mut ctx,
+        &[
+            vault.ix.initialize_asset_ledger(
+                &vault.primary_keeper(),
+                &vault.token_mint,
+                &vault.vault_token_account,
+            ),
+            policy_ix,
+        ],
+        &[&vault.keeper, &vault.authority],
+    )
+    .await
+    .unwrap();
+
+    // With an empty vault the withheld fees are all of its holdings
+    let err = send(
+        &mut ctx,
+        std::slice::from_ref(&withdraw_ix),
+        &[&vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::EmergencyAmountExceedsCap.into())
+    );
+
+    let fee = fee_of(TRANSFER);
+    let fill_vault = token_instruction::mint_to(
+        &spl_token_2022::id(),
+        &vault.token_mint,
+        &vault.vault_token_account,
+        &fixture.mint_authority.pubkey(),
+        &[],
+        fee,
+    )
+    .unwrap();
+    send(
+        &mut ctx,
+        &[fill_vault, withdraw_ix, vault.ix.reconcile()],
+        &[&fixture.mint_authority, &vault.authority],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_amount(&mut ctx, &treasury).await, fee);
+
+    let state = fetch_vault(&mut ctx, &vault.vault).await;
+    assert_eq!(state.total_fees_harvested, fee);
+    assert_eq!(state.total_emergency_withdrawn, fee);
+    assert!(state.last_reconciliation.passed);
+
+    let ledger: AssetLedger = fetch_zero_copy(
+        &mut ctx,
+        &pda::asset_ledger_pda(&vault.vault, &vault.token_mint),
+    )
+    .await;
+    let kinds: Vec<LedgerEntryKind> = ledger.entries().iter().map(|entry| entry.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![LedgerEntryKind::FeeHarvest, LedgerEntryKind::EmergencyOut]
+    );
+    assert_eq!(ledger.balance().unwrap(), 0);
+}
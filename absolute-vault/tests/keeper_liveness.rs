@@ -0,0 +1,185 @@
+mod common;
+
+use absolute_vault::{KeeperReserve, VaultError};
+use common::*;
+use miko_client::{pda, vault::KeeperReserveConfig, Keeper};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const WITHHELD: u64 = 1_000_000;
+const INACTIVITY_PERIOD: i64 = 3_600;
+const BOUNTY: u64 = 5_000_000;
+
+struct LivenessFixture {
+    vault: VaultFixture,
+    keeper_reserve: Pubkey,
+    cranker: Keypair,
+}
+
+impl LivenessFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let vault = VaultFixture::new(Pubkey::new_unique());
+        let fixture = Self {
+            keeper_reserve: pda::keeper_reserve_pda(&vault.vault),
+            cranker: Keypair::new(),
+            vault,
+        };
+        let vault = &fixture.vault;
+
+        pt.add_account(
+            vault.token_mint,
+            fee_mint_account(MIKO_DECIMALS, &Pubkey::new_unique(), &vault.vault, WITHHELD),
+        );
+        vault.add_vault_token_account(&mut pt, 0);
+        pt.add_account(fixture.cranker.pubkey(), funded_account());
+        vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+
+        let authority = vault.authority.pubkey();
+        let reserve_config = KeeperReserveConfig {
+            min_keeper_balance: 0,
+            max_top_up_per_period: 0,
+            top_up_period: 86_400,
+        };
+        send(
+            &mut ctx,
+            &[
+                vault
+                    .ix
+                    .update_liveness_config(&authority, INACTIVITY_PERIOD, BOUNTY),
+                vault
+                    .ix
+                    .initialize_keeper_reserve(&authority, &reserve_config),
+                vault.ix.deposit_to_reserve(&authority, BOUNTY),
+            ],
+            &[&vault.authority],
+        )
+        .await
+        .unwrap();
+
+        (ctx, fixture)
+    }
+
+    fn withdraw_fees_ix(&self, caller: &Pubkey) -> Instruction {
+        self.vault
+            .ix
+            .with_keeper_reserve()
+            .withdraw_fees_from_mint(&Keeper::primary(*caller))
+    }
+
+    fn heartbeat_ix(&self) -> Instruction {
+        self.vault.ix.keeper_heartbeat(&self.vault.primary_keeper())
+    }
+}
+
+#[tokio::test]
+async fn anyone_can_crank_after_keeper_goes_quiet() {
+    let (mut ctx, fixture) = LivenessFixture::start().await;
+    let cranker = fixture.cranker.pubkey();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix(&cranker)],
+        &[&fixture.cranker],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+
+    warp_by(&mut ctx, INACTIVITY_PERIOD).await;
+    let cranker_before = ctx.banks_client.get_balance(cranker).await.unwrap();
+    send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix(&cranker)],
+        &[&fixture.cranker],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        WITHHELD
+    );
+    assert_eq!(
+        ctx.banks_client.get_balance(cranker).await.unwrap(),
+        cranker_before + BOUNTY
+    );
+    let reserve: KeeperReserve = fetch(&mut ctx, &fixture.keeper_reserve).await;
+    assert_eq!(reserve.total_bounties_paid, BOUNTY);
+}
+
+#[tokio::test]
+async fn crank_that_moves_no_fees_is_not_paid() {
+    let (mut ctx, fixture) = LivenessFixture::start().await;
+    let cranker = fixture.cranker.pubkey();
+
+    warp_by(&mut ctx, INACTIVITY_PERIOD).await;
+    send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix(&cranker)],
+        &[&fixture.cranker],
+    )
+    .await
+    .unwrap();
+
+    // The mint is now empty; repeating the crank would only drain the reserve
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix(&cranker)],
+        &[&fixture.cranker],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NothingToWithdraw.into()));
+    let reserve: KeeperReserve = fetch(&mut ctx, &fixture.keeper_reserve).await;
+    assert_eq!(reserve.total_bounties_paid, BOUNTY);
+}
+
+#[tokio::test]
+async fn keeper_heartbeat_closes_the_crank_window() {
+    let (mut ctx, fixture) = LivenessFixture::start().await;
+    let cranker = fixture.cranker.pubkey();
+
+    warp_by(&mut ctx, INACTIVITY_PERIOD).await;
+    send(
+        &mut ctx,
+        &[fixture.heartbeat_ix()],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.withdraw_fees_ix(&cranker)],
+        &[&fixture.cranker],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+}
+
+#[tokio::test]
+async fn inactivity_period_cannot_be_negative() {
+    let (mut ctx, fixture) = LivenessFixture::start().await;
+
+    let liveness =
+        fixture
+            .vault
+            .ix
+            .update_liveness_config(&fixture.vault.authority.pubkey(), -1, BOUNTY);
+    let err = send(&mut ctx, &[liveness], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidLivenessConfig.into())
+    );
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use absolute_vault::{KeeperReserve, KeeperWorkLog, KeeperWorkType, VaultError};
+use common::*;
+use miko_client::{pda, vault::KeeperReserveConfig};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+const SOL: u64 = 1_000_000_000;
+const PERIOD: i64 = 86_400;
+
+struct ReserveFixture {
+    vault: VaultFixture,
+    keeper_reserve: Pubkey,
+    keeper_work_log: Pubkey,
+}
+
+impl ReserveFixture {
+    async fn start(
+        min_keeper_balance: u64,
+        max_top_up_per_period: u64,
+    ) -> (ProgramTestContext, Self) {
+        let (mut ctx, vault) = VaultFixture::start().await;
+        let fixture = Self {
+            keeper_reserve: pda::keeper_reserve_pda(&vault.vault),
+            keeper_work_log: pda::keeper_log_pda(&vault.vault),
+            vault,
+        };
+
+        let config = KeeperReserveConfig {
+            min_keeper_balance,
+            max_top_up_per_period,
+            top_up_period: PERIOD,
+        };
+        let payer = ctx.payer.pubkey();
+        let instructions = [
+            fixture
+                .vault
+                .ix
+                .initialize_keeper_reserve(&fixture.vault.authority.pubkey(), &config),
+            fixture.vault.ix.deposit_to_reserve(&payer, 5 * SOL),
+        ];
+        send(&mut ctx, &instructions, &[&fixture.vault.authority])
+            .await
+            .unwrap();
+
+        (ctx, fixture)
+    }
+
+    fn top_up_ix(&self, amount: u64) -> Instruction {
+        self.vault
+            .ix
+            .top_up_keeper(&self.vault.keeper.pubkey(), amount)
+    }
+}
+
+#[tokio::test]
+async fn top_up_moves_reserve_lamports_and_logs_work() {
+    // Fixture keepers hold 10 SOL, below the 20 SOL minimum
+    let (mut ctx, fixture) = ReserveFixture::start(20 * SOL, 3 * SOL).await;
+    let keeper = fixture.vault.keeper.pubkey();
+    let keeper_before = ctx.banks_client.get_balance(keeper).await.unwrap();
+
+    send(
+        &mut ctx,
+        &[fixture.top_up_ix(2 * SOL)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let reserve: KeeperReserve = fetch(&mut ctx, &fixture.keeper_reserve).await;
+    assert_eq!(reserve.total_deposited, 5 * SOL);
+    assert_eq!(reserve.total_topped_up, 2 * SOL);
+    assert_eq!(reserve.period_topped_up, 2 * SOL);
+
+    // The keeper also pays for the work log on first use
+    let keeper_after = ctx.banks_client.get_balance(keeper).await.unwrap();
+    assert!(keeper_after > keeper_before + SOL);
+
+    let log: KeeperWorkLog = fetch_zero_copy(&mut ctx, &fixture.keeper_work_log).await;
+    let entries = log.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].work_type, KeeperWorkType::KeeperTopUp);
+    assert_eq!(entries[0].amount, 2 * SOL);
+
+    // New blockhash so the identical retry is not deduplicated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(
+        &mut ctx,
+        &[fixture.top_up_ix(2 * SOL)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::TopUpCapExceeded.into()));
+}
+
+#[tokio::test]
+async fn top_up_requires_keeper_below_minimum() {
+    let (mut ctx, fixture) = ReserveFixture::start(SOL, 3 * SOL).await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.top_up_ix(SOL)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::KeeperBalanceSufficient.into())
+    );
+}
+
+#[tokio::test]
+async fn top_up_cannot_drain_reserve_below_rent() {
+    let (mut ctx, fixture) = ReserveFixture::start(20 * SOL, 10 * SOL).await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.top_up_ix(5 * SOL + 1)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InsufficientReserve.into())
+    );
+}
+
+#[tokio::test]
+async fn reserve_period_must_be_positive() {
+    let (mut ctx, fixture) = ReserveFixture::start(SOL, SOL).await;
+
+    let update = fixture.vault.ix.update_keeper_reserve(
+        &fixture.vault.authority.pubkey(),
+        &KeeperReserveConfig {
+            min_keeper_balance: SOL,
+            max_top_up_per_period: SOL,
+            top_up_period: 0,
+        },
+    );
+    let err = send(&mut ctx, &[update], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidReserveConfig.into())
+    );
+
+    let reserve: KeeperReserve = fetch(&mut ctx, &fixture.keeper_reserve).await;
+    assert_eq!(reserve.top_up_period, PERIOD);
+}
+
+#[tokio::test]
+async fn top_up_fails_when_the_period_end_overflows() {
+    let (mut ctx, fixture) = ReserveFixture::start(20 * SOL, SOL).await;
+    // The first top-up starts a period
+    send(
+        &mut ctx,
+        &[fixture.top_up_ix(SOL / 2)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let update = fixture.vault.ix.update_keeper_reserve(
+        &fixture.vault.authority.pubkey(),
+        &KeeperReserveConfig {
+            min_keeper_balance: 20 * SOL,
+            max_top_up_per_period: SOL,
+            top_up_period: i64::MAX,
+        },
+    );
+    send(&mut ctx, &[update], &[&fixture.vault.authority])
+        .await
+        .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.top_up_ix(SOL)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::MathOverflow.into()));
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use absolute_vault::{KeeperRole, KeeperRoleKind, KeeperWorkType, PoolRegistry, VaultError};
+use common::*;
+use miko_client::{pda, Keeper};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+async fn start() -> (ProgramTestContext, VaultFixture, Keypair) {
+    let mut pt = program_test();
+    let vault = VaultFixture::new(Pubkey::new_unique());
+    let reporter = Keypair::new();
+    pt.add_account(
+        vault.token_mint,
+        mint_account(MIKO_DECIMALS, &Pubkey::new_unique()),
+    );
+    pt.add_account(reporter.pubkey(), funded_account());
+    vault.add_signers(&mut pt);
+
+    let mut ctx = pt.start_with_context().await;
+    vault.initialize(&mut ctx).await;
+    (ctx, vault, reporter)
+}
+
+fn grant_ix(vault: &VaultFixture, grantee: &Pubkey, role: KeeperRoleKind) -> Instruction {
+    vault
+        .ix
+        .grant_keeper_role(&vault.authority.pubkey(), grantee, role)
+}
+
+fn revoke_ix(vault: &VaultFixture, grantee: &Pubkey, role: KeeperRoleKind) -> Instruction {
+    vault
+        .ix
+        .revoke_keeper_role(&vault.authority.pubkey(), grantee, role)
+}
+
+fn report_pools_ix(vault: &VaultFixture, reporter: &Keeper) -> Instruction {
+    vault
+        .ix
+        .update_pool_registry(reporter, vec![Pubkey::new_unique()])
+}
+
+fn log_work_ix(vault: &VaultFixture, logger: &Keeper) -> Instruction {
+    vault
+        .ix
+        .log_keeper_work(logger, KeeperWorkType::HarvestFees, 0, String::new())
+}
+
+#[tokio::test]
+async fn granted_role_unlocks_only_its_instructions() {
+    let (mut ctx, vault, reporter) = start().await;
+    let key = reporter.pubkey();
+    let role = pda::keeper_role_pda(&vault.vault, &key, KeeperRoleKind::PoolReporter);
+
+    send(
+        &mut ctx,
+        &[grant_ix(&vault, &key, KeeperRoleKind::PoolReporter)],
+        &[&vault.authority],
+    )
+    .await
+    .unwrap();
+    let grant: KeeperRole = fetch(&mut ctx, &role).await;
+    assert_eq!(grant.key, key);
+    assert_eq!(grant.role, KeeperRoleKind::PoolReporter);
+
+    send(
+        &mut ctx,
+        &[report_pools_ix(&vault, &Keeper::granted(key))],
+        &[&reporter],
+    )
+    .await
+    .unwrap();
+    let registry: PoolRegistry = fetch(&mut ctx, &vault.pool_registry).await;
+    assert_eq!(registry.pools.len(), 1);
+
+    let err = send(
+        &mut ctx,
+        &[log_work_ix(&vault, &Keeper::primary(key))],
+        &[&reporter],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+
+    // A grant for another role cannot stand in for the one required
+    let logger_role = pda::keeper_role_pda(&vault.vault, &key, KeeperRoleKind::Logger);
+    let mut log_with_reporter_grant = log_work_ix(&vault, &Keeper::granted(key));
+    log_with_reporter_grant
+        .accounts
+        .iter_mut()
+        .filter(|meta| meta.pubkey == logger_role)
+        .for_each(|meta| meta.pubkey = role);
+    let err = send(&mut ctx, &[log_with_reporter_grant], &[&reporter])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(anchor_lang::error::ErrorCode::ConstraintSeeds.into())
+    );
+}
+
+#[tokio::test]
+async fn revoked_role_no_longer_authorizes() {
+    let (mut ctx, vault, reporter) = start().await;
+    let key = reporter.pubkey();
+
+    send(
+        &mut ctx,
+        &[
+            grant_ix(&vault, &key, KeeperRoleKind::PoolReporter),
+            revoke_ix(&vault, &key, KeeperRoleKind::PoolReporter),
+        ],
+        &[&vault.authority],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[report_pools_ix(&vault, &Keeper::primary(key))],
+        &[&reporter],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+}
+
+#[tokio::test]
+async fn keeper_authority_keeps_every_role() {
+    let (mut ctx, vault, _) = start().await;
+    let keeper = vault.primary_keeper();
+
+    send(
+        &mut ctx,
+        &[
+            report_pools_ix(&vault, &keeper),
+            log_work_ix(&vault, &keeper),
+        ],
+        &[&vault.keeper],
+    )
+    .await
+    .unwrap();
+}
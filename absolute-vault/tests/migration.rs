@@ -0,0 +1,399 @@
+mod common;
+
+use absolute_vault::{
+    AssetLedger, AssetLedgerLayoutV0, KeeperWorkEntry, KeeperWorkLog, KeeperWorkLogLayoutV0,
+    KeeperWorkType, LedgerEntry, LedgerEntryKind, PoolRegistry, PoolRegistryLayoutV0, VaultError,
+    VaultState, VaultStateLayoutV0, VaultStateLayoutV1, ASSET_LEDGER_LAYOUT_V0_LEN,
+    ASSET_LEDGER_VERSION, EMERGENCY_COOLDOWN, KEEPER_WORK_LOG_LAYOUT_V0_LEN,
+    KEEPER_WORK_LOG_VERSION, POOL_REGISTRY_LAYOUT_V0_LEN, POOL_REGISTRY_VERSION,
+    VAULT_LAYOUT_V0_LEN, VAULT_LAYOUT_V1_LEN, VAULT_STATE_VERSION,
+};
+use anchor_lang::{AccountSerialize, AnchorSerialize, Discriminator, Space};
+use common::*;
+use miko_client::pda;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent, signature::Signer};
+
+/// Program-owned account holding `fields` under `discriminator`, allocated at `len`
+/// the way the legacy program sized it
+fn legacy_account(discriminator: &[u8], fields: &impl AnchorSerialize, len: usize) -> Account {
+    let mut data = discriminator.to_vec();
+    fields.serialize(&mut data).unwrap();
+    assert!(data.len() <= len);
+    data.resize(len, 0);
+    Account {
+        lamports: Rent::default().minimum_balance(len),
+        data,
+        owner: absolute_vault::id(),
+        ..Account::default()
+    }
+}
+
+fn legacy_vault_v1(fixture: &VaultFixture) -> VaultStateLayoutV1 {
+    VaultStateLayoutV1 {
+        authority: fixture.authority.pubkey(),
+        keeper_authority: fixture.keeper.pubkey(),
+        owner_wallet: fixture.owner.pubkey(),
+        token_mint: fixture.token_mint,
+        reward_exclusions: vec![fixture.authority.pubkey(), fixture.vault],
+        harvest_threshold: 1_000,
+        total_fees_harvested: 5_000,
+        total_rewards_distributed: 4_000,
+        distribution_id: 3,
+        pending_withheld: 7,
+        last_harvest_time: 1_700_000_000,
+        last_harvest_amount: 1_000,
+        last_distribution_time: 1_700_000_100,
+        launch_timestamp: 1_690_000_000,
+    }
+}
+
+async fn start(
+    fixture: &VaultFixture,
+    legacy_accounts: Vec<(Pubkey, Account)>,
+) -> ProgramTestContext {
+    let mut pt: ProgramTest = program_test();
+    for (address, account) in legacy_accounts {
+        pt.add_account(address, account);
+    }
+    fixture.add_signers(&mut pt);
+    pt.start_with_context().await
+}
+
+#[tokio::test]
+async fn launch_layout_vault_migrates_in_place() {
+    let fixture = VaultFixture::new(Pubkey::new_unique());
+    let v1 = legacy_vault_v1(&fixture);
+    let v0 = VaultStateLayoutV0 {
+        authority: v1.authority,
+        keeper_authority: v1.keeper_authority,
+        owner_wallet: v1.owner_wallet,
+        token_mint: v1.token_mint,
+        reward_exclusions: v1.reward_exclusions.clone(),
+        harvest_threshold: v1.harvest_threshold,
+        total_fees_harvested: v1.total_fees_harvested,
+        total_rewards_distributed: v1.total_rewards_distributed,
+        distribution_id: v1.distribution_id,
+        pending_withheld: v1.pending_withheld,
+        last_harvest_time: v1.last_harvest_time,
+        last_distribution_time: v1.last_distribution_time,
+        launch_timestamp: v1.launch_timestamp,
+    };
+    let mut ctx = start(
+        &fixture,
+        vec![(
+            fixture.vault,
+            legacy_account(VaultState::DISCRIMINATOR, &v0, VAULT_LAYOUT_V0_LEN),
+        )],
+    )
+    .await;
+
+    send(
+        &mut ctx,
+        &[fixture.ix.migrate_vault(&fixture.authority.pubkey())],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+
+    let account = ctx
+        .banks_client
+        .get_account(fixture.vault)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), 8 + VaultState::INIT_SPACE);
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault).await;
+    assert_eq!(vault.version, VAULT_STATE_VERSION);
+    assert_eq!(vault.authority, v0.authority);
+    assert_eq!(vault.reward_exclusions, v0.reward_exclusions);
+    assert_eq!(vault.total_fees_harvested, v0.total_fees_harvested);
+    assert_eq!(vault.distribution_id, v0.distribution_id);
+    assert_eq!(vault.last_harvest_amount, 0);
+    assert_eq!(vault.last_distribution_time, v0.last_distribution_time);
+    assert_eq!(vault.launch_timestamp, v0.launch_timestamp);
+    assert_eq!(vault.emergency_cooldown, EMERGENCY_COOLDOWN);
+}
+
+#[tokio::test]
+async fn unversioned_vault_registry_and_log_migrate_in_place() {
+    let fixture = VaultFixture::new(Pubkey::new_unique());
+    let v1 = legacy_vault_v1(&fixture);
+    let pools = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let registry = PoolRegistryLayoutV0 {
+        vault: fixture.vault,
+        pools: pools.clone(),
+    };
+    let keeper_log = pda::keeper_log_pda(&fixture.vault);
+    let log = KeeperWorkLogLayoutV0 {
+        vault: fixture.vault,
+        entries: vec![KeeperWorkEntry {
+            timestamp: 1_700_000_000,
+            work_type: KeeperWorkType::HarvestFees,
+            amount: 1_000,
+            details: "harvest".to_string(),
+        }],
+    };
+    let mut ctx = start(
+        &fixture,
+        vec![
+            (
+                fixture.vault,
+                legacy_account(VaultState::DISCRIMINATOR, &v1, VAULT_LAYOUT_V1_LEN),
+            ),
+            (
+                fixture.pool_registry,
+                legacy_account(
+                    PoolRegistry::DISCRIMINATOR,
+                    &registry,
+                    POOL_REGISTRY_LAYOUT_V0_LEN,
+                ),
+            ),
+            (
+                keeper_log,
+                legacy_account(
+                    KeeperWorkLog::DISCRIMINATOR,
+                    &log,
+                    KEEPER_WORK_LOG_LAYOUT_V0_LEN,
+                ),
+            ),
+        ],
+    )
+    .await;
+
+    let authority = fixture.authority.pubkey();
+    let migrate_registry = fixture.ix.migrate_pool_registry(&authority);
+    let migrate_log = fixture.ix.migrate_keeper_work_log(&authority);
+
+    // Unmigrated accounts are rejected rather than misread
+    let err = send(
+        &mut ctx,
+        &[fixture.update_pool_registry_ix(vec![Pubkey::new_unique()])],
+        &[&fixture.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert!(error_code(err).is_some());
+
+    send(
+        &mut ctx,
+        &[
+            fixture.ix.migrate_vault(&authority),
+            migrate_registry,
+            migrate_log,
+        ],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault).await;
+    assert_eq!(vault.version, VAULT_STATE_VERSION);
+    assert_eq!(vault.keeper_authority, v1.keeper_authority);
+    assert_eq!(
+        vault.total_rewards_distributed,
+        v1.total_rewards_distributed
+    );
+    assert_eq!(vault.last_harvest_amount, v1.last_harvest_amount);
+    assert_eq!(vault.pending_withheld, v1.pending_withheld);
+
+    let migrated_registry: PoolRegistry = fetch(&mut ctx, &fixture.pool_registry).await;
+    assert_eq!(migrated_registry.version, POOL_REGISTRY_VERSION);
+    assert_eq!(migrated_registry.pools, pools);
+
+    let migrated_log: KeeperWorkLog = fetch_zero_copy(&mut ctx, &keeper_log).await;
+    assert_eq!(migrated_log.version, KEEPER_WORK_LOG_VERSION);
+    let entries = migrated_log.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].work_type, KeeperWorkType::HarvestFees);
+    assert_eq!(entries[0].details, "harvest");
+
+    // Migrated accounts work with the current instructions
+    let new_pool = Pubkey::new_unique();
+    send(
+        &mut ctx,
+        &[fixture.update_pool_registry_ix(vec![new_pool])],
+        &[&fixture.keeper],
+    )
+    .await
+    .unwrap();
+    let migrated_registry: PoolRegistry = fetch(&mut ctx, &fixture.pool_registry).await;
+    assert_eq!(migrated_registry.pools, [pools, vec![new_pool]].concat());
+}
+
+#[tokio::test]
+async fn unversioned_asset_ledger_migrates_into_its_ring_buffer() {
+    let fixture = VaultFixture::new(Pubkey::new_unique());
+    let v1 = legacy_vault_v1(&fixture);
+    let mint = Pubkey::new_unique();
+    let asset_ledger = pda::asset_ledger_pda(&fixture.vault, &mint);
+    let entries = vec![
+        LedgerEntry {
+            timestamp: 1_700_000_000,
+            distribution_id: 1,
+            kind: LedgerEntryKind::SwapIn,
+            amount: 900,
+        },
+        LedgerEntry {
+            timestamp: 1_700_000_100,
+            distribution_id: 1,
+            kind: LedgerEntryKind::DistributionOut,
+            amount: 400,
+        },
+    ];
+    let ledger = AssetLedgerLayoutV0 {
+        vault: fixture.vault,
+        mint,
+        token_account: Pubkey::new_unique(),
+        opening_balance: 100,
+        total_inflow: 900,
+        total_outflow: 400,
+        opened_at: 1_690_000_000,
+        last_reconciled_at: 1_700_000_100,
+        entries: entries.clone(),
+    };
+    let mut ctx = start(
+        &fixture,
+        vec![
+            (
+                fixture.vault,
+                legacy_account(VaultState::DISCRIMINATOR, &v1, VAULT_LAYOUT_V1_LEN),
+            ),
+            (
+                asset_ledger,
+                legacy_account(
+                    AssetLedger::DISCRIMINATOR,
+                    &ledger,
+                    ASSET_LEDGER_LAYOUT_V0_LEN,
+                ),
+            ),
+        ],
+    )
+    .await;
+
+    let authority = fixture.authority.pubkey();
+    send(
+        &mut ctx,
+        &[
+            fixture.ix.migrate_vault(&authority),
+            fixture.ix.migrate_asset_ledger(&authority, &mint),
+        ],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+
+    let migrated: AssetLedger = fetch_zero_copy(&mut ctx, &asset_ledger).await;
+    assert_eq!(migrated.version, ASSET_LEDGER_VERSION);
+    assert_eq!(migrated.token_account, ledger.token_account);
+    assert_eq!(migrated.total_inflow, ledger.total_inflow);
+    assert_eq!(migrated.total_outflow, ledger.total_outflow);
+    assert_eq!(migrated.balance().unwrap(), 600);
+    assert_eq!(migrated.entries(), entries);
+
+    // A second migration finds nothing to migrate
+    let err = send(
+        &mut ctx,
+        &[fixture.ix.migrate_asset_ledger(&authority, &mint)],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap_err();
+    assert!(error_code(err).is_some());
+}
+
+#[tokio::test]
+async fn migration_requires_authority_and_a_legacy_layout() {
+    let fixture = VaultFixture::new(Pubkey::new_unique());
+    let v1 = legacy_vault_v1(&fixture);
+    let mut ctx = start(
+        &fixture,
+        vec![(
+            fixture.vault,
+            legacy_account(VaultState::DISCRIMINATOR, &v1, VAULT_LAYOUT_V1_LEN),
+        )],
+    )
+    .await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.ix.migrate_vault(&fixture.keeper.pubkey())],
+        &[&fixture.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::Unauthorized.into()));
+
+    send(
+        &mut ctx,
+        &[fixture.ix.migrate_vault(&fixture.authority.pubkey())],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(
+        &mut ctx,
+        &[fixture.ix.migrate_vault(&fixture.authority.pubkey())],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::UnsupportedAccountLayout.into())
+    );
+}
+
+#[tokio::test]
+async fn current_instructions_reject_an_unmigrated_registry() {
+    let fixture = VaultFixture::new(Pubkey::new_unique());
+    let v1 = legacy_vault_v1(&fixture);
+    // Current layout with the version byte never stamped
+    let registry = PoolRegistry {
+        version: 0,
+        vault: fixture.vault,
+        pools: vec![],
+        reserved: [0; 64],
+    };
+    let mut data = Vec::new();
+    registry.try_serialize(&mut data).unwrap();
+    let len = 8 + PoolRegistry::INIT_SPACE;
+    data.resize(len, 0);
+    let registry_account = Account {
+        lamports: Rent::default().minimum_balance(len),
+        data,
+        owner: absolute_vault::id(),
+        ..Account::default()
+    };
+    let mut ctx = start(
+        &fixture,
+        vec![
+            (
+                fixture.vault,
+                legacy_account(VaultState::DISCRIMINATOR, &v1, VAULT_LAYOUT_V1_LEN),
+            ),
+            (fixture.pool_registry, registry_account),
+        ],
+    )
+    .await;
+    send(
+        &mut ctx,
+        &[fixture.ix.migrate_vault(&fixture.authority.pubkey())],
+        &[&fixture.authority],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.update_pool_registry_ix(vec![Pubkey::new_unique()])],
+        &[&fixture.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::AccountNotMigrated.into()));
+}
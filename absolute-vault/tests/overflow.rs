@@ -0,0 +1,166 @@
+use absolute_vault::{
+    AssetLedger, KeeperWorkEntry, KeeperWorkLog, KeeperWorkType, LedgerEntry, LedgerEntryKind,
+    VaultError, VaultState, ASSET_LEDGER_VERSION, MAX_KEEPER_WORK_ENTRIES, MAX_LEDGER_ENTRIES,
+    MAX_WORK_DETAILS_LEN,
+};
+use anchor_lang::{error::Error, AccountDeserialize};
+use bytemuck::Zeroable;
+use proptest::prelude::*;
+use solana_sdk::pubkey::Pubkey;
+
+/// Freshly allocated state: every counter at zero and every list empty
+fn zeroed<T: AccountDeserialize>() -> T {
+    T::try_deserialize_unchecked(&mut vec![0u8; 16 * 1024].as_slice()).unwrap()
+}
+
+fn overflow() -> Error {
+    VaultError::MathOverflow.into()
+}
+
+/// Counters start within a million of their limit so small and large steps both cross it
+fn near_max() -> impl Strategy<Value = u64> {
+    (u64::MAX - 1_000_000)..=u64::MAX
+}
+
+proptest! {
+    #[test]
+    fn harvest_totals_fail_instead_of_saturating(
+        harvested in near_max(),
+        pending_burn in near_max(),
+        burn_share_bps in 0u16..=10_000,
+        amount in any::<u64>(),
+    ) {
+        let mut vault: VaultState = zeroed();
+        vault.total_fees_harvested = harvested;
+        vault.pending_burn_amount = pending_burn;
+        vault.burn_share_bps = burn_share_bps;
+
+        let burn_amount = (amount as u128 * burn_share_bps as u128 / 10_000) as u64;
+        let fits = harvested.checked_add(amount).is_some()
+            && pending_burn.checked_add(burn_amount).is_some();
+
+        match vault.record_harvest(amount, 1) {
+            Ok(burned) => {
+                prop_assert!(fits);
+                prop_assert_eq!(burned, burn_amount);
+                prop_assert_eq!(vault.total_fees_harvested, harvested + amount);
+                prop_assert_eq!(vault.pending_burn_amount, pending_burn + burn_amount);
+            }
+            Err(err) => {
+                prop_assert!(!fits);
+                prop_assert_eq!(err, overflow());
+                prop_assert_eq!(vault.total_fees_harvested, harvested);
+                prop_assert_eq!(vault.pending_burn_amount, pending_burn);
+            }
+        }
+    }
+
+    #[test]
+    fn distribution_counters_fail_instead_of_saturating(
+        distribution_id in near_max(),
+        distributed in near_max(),
+        amount in any::<u64>(),
+    ) {
+        let mut vault: VaultState = zeroed();
+        vault.distribution_id = distribution_id;
+        vault.total_rewards_distributed = distributed;
+
+        let fits = distribution_id < u64::MAX && distributed.checked_add(amount).is_some();
+
+        match vault.record_distribution(amount, 1) {
+            Ok(id) => {
+                prop_assert!(fits);
+                prop_assert_eq!(id, distribution_id + 1);
+                prop_assert_eq!(vault.total_rewards_distributed, distributed + amount);
+            }
+            Err(err) => {
+                prop_assert!(!fits);
+                prop_assert_eq!(err, overflow());
+                prop_assert_eq!(vault.distribution_id, distribution_id);
+                prop_assert_eq!(vault.total_rewards_distributed, distributed);
+            }
+        }
+    }
+
+    #[test]
+    fn ledger_totals_fail_instead_of_saturating(
+        inflow in near_max(),
+        outflow in near_max(),
+        amount in any::<u64>(),
+        is_inflow in any::<bool>(),
+    ) {
+        let mut ledger = AssetLedger::zeroed();
+        ledger.version = ASSET_LEDGER_VERSION;
+        ledger.total_inflow = inflow;
+        ledger.total_outflow = outflow;
+
+        let (kind, total) = if is_inflow {
+            (LedgerEntryKind::FeeHarvest, inflow)
+        } else {
+            (LedgerEntryKind::DistributionOut, outflow)
+        };
+        let entry = LedgerEntry {
+            timestamp: 1,
+            distribution_id: 1,
+            kind,
+            amount,
+        };
+
+        match ledger.record(&entry) {
+            Ok(()) => {
+                prop_assert!(total.checked_add(amount).is_some());
+                prop_assert_eq!(ledger.len().unwrap(), 1);
+            }
+            Err(err) => {
+                prop_assert!(total.checked_add(amount).is_none());
+                prop_assert_eq!(err, overflow());
+                prop_assert!(ledger.is_empty());
+            }
+        }
+    }
+}
+
+#[test]
+fn work_log_overwrites_its_oldest_entries() {
+    let mut log = KeeperWorkLog::zeroed();
+    let vault = Pubkey::new_unique();
+    let total = MAX_KEEPER_WORK_ENTRIES as u64 + 7;
+    for amount in 0..total {
+        let entry = KeeperWorkEntry {
+            timestamp: amount as i64,
+            work_type: KeeperWorkType::HarvestFees,
+            amount,
+            // Multi-byte chars straddle the details limit
+            details: "€".repeat(MAX_WORK_DETAILS_LEN),
+        };
+        log.record(vault, &entry).unwrap();
+    }
+
+    assert_eq!(log.vault, vault);
+    assert_eq!(log.len().unwrap(), MAX_KEEPER_WORK_ENTRIES);
+    let entries = log.entries();
+    let amounts: Vec<u64> = entries.iter().map(|entry| entry.amount).collect();
+    assert_eq!(amounts, (7..total).collect::<Vec<_>>());
+    assert_eq!(entries[0].details, "€".repeat(MAX_WORK_DETAILS_LEN / 3));
+}
+
+#[test]
+fn asset_ledger_overwrites_its_oldest_entries() {
+    let mut ledger = AssetLedger::zeroed();
+    ledger.version = ASSET_LEDGER_VERSION;
+    let total = MAX_LEDGER_ENTRIES as u64 + 5;
+    for amount in 0..total {
+        let entry = LedgerEntry {
+            timestamp: amount as i64,
+            distribution_id: amount,
+            kind: LedgerEntryKind::FeeHarvest,
+            amount,
+        };
+        ledger.record(&entry).unwrap();
+    }
+
+    assert_eq!(ledger.len().unwrap(), MAX_LEDGER_ENTRIES);
+    assert_eq!(ledger.total_inflow, (0..total).sum::<u64>());
+    let amounts: Vec<u64> = ledger.entries().iter().map(|entry| entry.amount).collect();
+    assert_eq!(amounts, (5..total).collect::<Vec<_>>());
+}
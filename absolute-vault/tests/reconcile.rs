@@ -0,0 +1,66 @@
+mod common;
+
+use absolute_vault::ReconciliationReport;
+use common::*;
+use solana_program_test::ProgramTestContext;
+
+const WITHHELD: u64 = 1_000_000;
+
+/// Vault with `WITHHELD` harvested, a fifth of it earmarked for burning
+async fn start() -> (ProgramTestContext, DistributionFixture) {
+    let (mut ctx, fixture) = DistributionFixture::start(0, WITHHELD).await;
+    fixture.harvest(&mut ctx, 2_000).await;
+    (ctx, fixture)
+}
+
+async fn reconcile(ctx: &mut ProgramTestContext, vault: &VaultFixture) -> ReconciliationReport {
+    // New blockhash so repeated runs are not deduplicated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    send(ctx, &[vault.ix.reconcile()], &[]).await.unwrap();
+    fetch_vault(ctx, &vault.vault).await.last_reconciliation
+}
+
+#[tokio::test]
+async fn reconcile_passes_while_every_outflow_is_accounted() {
+    let (mut ctx, fixture) = start().await;
+    let burn_amount = WITHHELD / 5;
+
+    let report = reconcile(&mut ctx, &fixture.vault).await;
+    assert!(report.passed);
+    assert_eq!(report.expected_balance, WITHHELD);
+    assert_eq!(report.actual_balance, WITHHELD);
+
+    send(
+        &mut ctx,
+        &[
+            fixture.vault.ix.burn_harvested_fees(),
+            fixture.withdraw_ix(&distribution_plan(1, WITHHELD - burn_amount, 0)),
+        ],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let report = reconcile(&mut ctx, &fixture.vault).await;
+    assert!(report.passed);
+    assert_eq!(report.expected_balance, 0);
+    assert_eq!(report.actual_balance, 0);
+}
+
+#[tokio::test]
+async fn reconcile_reports_drift_deltas() {
+    let (mut ctx, fixture) = start().await;
+
+    // Tokens arriving outside the harvest path are surplus, not a failure
+    set_token_balance(&mut ctx, &fixture.vault.vault_token_account, WITHHELD + 25).await;
+    let report = reconcile(&mut ctx, &fixture.vault).await;
+    assert!(report.passed);
+    assert_eq!((report.surplus, report.deficit), (25, 0));
+
+    // Tokens leaving without a recorded outflow fail the check
+    set_token_balance(&mut ctx, &fixture.vault.vault_token_account, WITHHELD - 40).await;
+    let report = reconcile(&mut ctx, &fixture.vault).await;
+    assert!(!report.passed);
+    assert_eq!((report.surplus, report.deficit), (0, 40));
+    assert_eq!(report.overdrawn, 0);
+}
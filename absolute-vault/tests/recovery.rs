@@ -0,0 +1,302 @@
+mod common;
+
+use absolute_vault::{DistributionRecord, DistributionStatus, VaultError};
+use anchor_lang::AccountSerialize;
+use common::*;
+use miko_client::{pda, vault::TokenRecovery};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, rent::Rent, signature::Signer,
+};
+use solana_system_interface::instruction as system_instruction;
+
+const STRAY_AMOUNT: u64 = 42_000;
+
+struct RecoveryFixture {
+    vault: VaultFixture,
+    stray_mint: Pubkey,
+    vault_stray_account: Pubkey,
+    vault_miko_account: Pubkey,
+    recipient: Pubkey,
+    recipient_stray_account: Pubkey,
+}
+
+impl RecoveryFixture {
+    /// The stray mint lives under the legacy SPL Token program
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let token_mint = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let fixture = Self {
+            vault: VaultFixture::new(token_mint),
+            stray_mint: Pubkey::new_unique(),
+            vault_stray_account: Pubkey::new_unique(),
+            vault_miko_account: Pubkey::new_unique(),
+            recipient,
+            recipient_stray_account: Pubkey::new_unique(),
+        };
+
+        let legacy = |mut account: solana_sdk::account::Account| {
+            account.owner = anchor_spl::token::ID;
+            account
+        };
+        let mint_authority = Pubkey::new_unique();
+        pt.add_account(token_mint, mint_account(MIKO_DECIMALS, &mint_authority));
+        pt.add_account(
+            fixture.vault_miko_account,
+            token_account(&token_mint, &fixture.vault.vault, STRAY_AMOUNT),
+        );
+        pt.add_account(fixture.stray_mint, legacy(mint_account(6, &mint_authority)));
+        pt.add_account(
+            fixture.vault_stray_account,
+            legacy(token_account(
+                &fixture.stray_mint,
+                &fixture.vault.vault,
+                STRAY_AMOUNT,
+            )),
+        );
+        pt.add_account(
+            fixture.recipient_stray_account,
+            legacy(token_account(&fixture.stray_mint, &recipient, 0)),
+        );
+        add_dial(&mut pt, &Pubkey::new_unique());
+        fixture.vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        fixture.vault.initialize(&mut ctx).await;
+        (ctx, fixture)
+    }
+
+    fn recover_token_ix(
+        &self,
+        mint: Pubkey,
+        source: Pubkey,
+        destination: Pubkey,
+        token_program: Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let recovery = TokenRecovery {
+            mint,
+            vault_token_account: source,
+            destination_token_account: destination,
+            token_program,
+            amount,
+        };
+        self.vault
+            .ix
+            .recover_tokens(&self.vault.authority.pubkey(), &recovery, 0)
+    }
+
+    fn recover_lamports_ix(&self, amount: u64) -> Instruction {
+        self.vault
+            .ix
+            .recover_lamports(&self.vault.authority.pubkey(), &self.recipient, amount)
+    }
+}
+
+#[tokio::test]
+async fn stray_spl_tokens_are_recovered() {
+    let (mut ctx, fixture) = RecoveryFixture::start().await;
+
+    send(
+        &mut ctx,
+        &[fixture.recover_token_ix(
+            fixture.stray_mint,
+            fixture.vault_stray_account,
+            fixture.recipient_stray_account,
+            anchor_spl::token::ID,
+            STRAY_AMOUNT,
+        )],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.recipient_stray_account).await,
+        STRAY_AMOUNT
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_stray_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn vault_mint_cannot_be_recovered() {
+    let (mut ctx, fixture) = RecoveryFixture::start().await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.recover_token_ix(
+            fixture.vault.token_mint,
+            fixture.vault_miko_account,
+            fixture.vault_miko_account,
+            spl_token_2022::id(),
+            1,
+        )],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::CannotRecoverVaultMint.into())
+    );
+}
+
+#[tokio::test]
+async fn only_lamports_above_rent_are_recovered() {
+    let (mut ctx, fixture) = RecoveryFixture::start().await;
+    let stray = 1_000_000;
+    let payer = ctx.payer.pubkey();
+    send(
+        &mut ctx,
+        &[system_instruction::transfer(
+            &payer,
+            &fixture.vault.vault,
+            stray,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.recover_lamports_ix(stray + 1)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InsufficientRecoverableBalance.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.recover_lamports_ix(stray)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        ctx.banks_client
+            .get_balance(fixture.recipient)
+            .await
+            .unwrap(),
+        stray
+    );
+}
+
+#[tokio::test]
+async fn token_recovery_needs_every_token_account() {
+    let (mut ctx, fixture) = RecoveryFixture::start().await;
+
+    let recover_stray = || {
+        fixture.recover_token_ix(
+            fixture.stray_mint,
+            fixture.vault_stray_account,
+            fixture.recipient_stray_account,
+            anchor_spl::token::ID,
+            STRAY_AMOUNT,
+        )
+    };
+    let reward_index = pda::reward_index_pda(&fixture.vault.vault, &fixture.stray_mint);
+    // Omitted optional accounts are passed as the program id
+    let mut partial = recover_stray();
+    for meta in &mut partial.accounts {
+        if meta.pubkey == reward_index || meta.pubkey == anchor_spl::token::ID {
+            meta.pubkey = absolute_vault::id();
+        }
+    }
+    // The reward index must be the PDA of the recovered mint
+    let mut wrong_index = recover_stray();
+    for meta in &mut wrong_index.accounts {
+        if meta.pubkey == reward_index {
+            meta.pubkey = Pubkey::new_unique();
+        }
+    }
+
+    for ix in [partial, wrong_index] {
+        let err = send(&mut ctx, &[ix], &[&fixture.vault.authority])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error_code(err),
+            Some(VaultError::InvalidRecoveryAccounts.into())
+        );
+    }
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_stray_account).await,
+        STRAY_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn reward_mints_cannot_be_recovered() {
+    let (mut ctx, fixture) = RecoveryFixture::start().await;
+    let recover_stray = || {
+        fixture.recover_token_ix(
+            fixture.stray_mint,
+            fixture.vault_stray_account,
+            fixture.recipient_stray_account,
+            anchor_spl::token::ID,
+            STRAY_AMOUNT,
+        )
+    };
+
+    // Selected by the dial
+    set_dial(&mut ctx, &fixture.stray_mint);
+    let err = send(&mut ctx, &[recover_stray()], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::CannotRecoverRewardMint.into())
+    );
+
+    // Swapped into by the latest distribution, after the dial moved on
+    set_dial(&mut ctx, &Pubkey::new_unique());
+    let record = DistributionRecord {
+        vault: fixture.vault.vault,
+        distribution_id: 0,
+        keeper: fixture.vault.keeper.pubkey(),
+        withdrawn_miko_amount: STRAY_AMOUNT,
+        expected_minimum_reward_amount: STRAY_AMOUNT,
+        reward_token_account: fixture.vault_stray_account,
+        reward_mint: fixture.stray_mint,
+        reward_balance_snapshot: 0,
+        reward_amount_received: 0,
+        status: DistributionStatus::Pending,
+        created_at: 0,
+        settled_at: 0,
+        revenue_split: Vec::new(),
+    };
+    let mut data = Vec::new();
+    record.try_serialize(&mut data).unwrap();
+    let record_account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: absolute_vault::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    ctx.set_account(
+        &pda::distribution_pda(&fixture.vault.vault, 0),
+        &record_account.into(),
+    );
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(&mut ctx, &[recover_stray()], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::CannotRecoverRewardMint.into())
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_stray_account).await,
+        STRAY_AMOUNT
+    );
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use absolute_vault::{RevenuePayee, RevenueSplit, VaultError, CONFIG_TIMELOCK};
+use common::*;
+use miko_client::pda;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+fn payee(label: &str, destination: Pubkey, share_bps: u16) -> RevenuePayee {
+    RevenuePayee {
+        label: label.to_string(),
+        destination,
+        share_bps,
+    }
+}
+
+fn propose_ix(vault: &VaultFixture, payees: Vec<RevenuePayee>) -> Instruction {
+    vault
+        .ix
+        .propose_revenue_split(&vault.authority.pubkey(), payees)
+}
+
+#[tokio::test]
+async fn default_split_matches_legacy_shares() {
+    let (mut ctx, vault) = VaultFixture::start().await;
+
+    let split: RevenueSplit = fetch(&mut ctx, &vault.revenue_split).await;
+    assert_eq!(
+        split.payees,
+        vec![
+            payee("owner", vault.owner.pubkey(), 2500),
+            payee("holders", vault.vault, 7500),
+        ]
+    );
+    assert_eq!(split.pending_eta, 0);
+}
+
+#[tokio::test]
+async fn shares_must_sum_to_full_basis_points() {
+    let (mut ctx, vault) = VaultFixture::start().await;
+
+    let payees = vec![
+        payee("owner", vault.owner.pubkey(), 2500),
+        payee("holders", vault.vault, 7000),
+    ];
+    let err = send(&mut ctx, &[propose_ix(&vault, payees)], &[&vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidDistributionSplit.into())
+    );
+}
+
+#[tokio::test]
+async fn proposed_split_applies_only_after_timelock() {
+    let (mut ctx, vault) = VaultFixture::start().await;
+    let marketing = Pubkey::new_unique();
+    let payees = vec![
+        payee("owner", vault.owner.pubkey(), 2000),
+        payee("marketing", marketing, 1000),
+        payee("holders", vault.vault, 7000),
+    ];
+
+    send(
+        &mut ctx,
+        &[propose_ix(&vault, payees.clone())],
+        &[&vault.authority],
+    )
+    .await
+    .unwrap();
+
+    let err = send(&mut ctx, &[vault.ix.apply_revenue_split()], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::TimelockNotElapsed.into()));
+
+    warp_by(&mut ctx, CONFIG_TIMELOCK).await;
+    send(&mut ctx, &[vault.ix.apply_revenue_split()], &[])
+        .await
+        .unwrap();
+
+    let split: RevenueSplit = fetch(&mut ctx, &vault.revenue_split).await;
+    assert_eq!(split.payees, payees);
+    assert!(split.pending_payees.is_empty());
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(&mut ctx, &[vault.ix.apply_revenue_split()], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::NoPendingConfigChange.into())
+    );
+}
+
+#[tokio::test]
+async fn settlement_applies_the_split_to_raw_distributions() {
+    const DISTRIBUTED: u64 = 1_000_000;
+    const REWARDS: u64 = 40_000;
+    let (mut ctx, fixture) = DistributionFixture::start(DISTRIBUTED, 0).await;
+    let owner = &fixture.vault.owner;
+    let owner_share =
+        pda::owner_share_pda(&fixture.vault.vault, &owner.pubkey(), &fixture.reward_mint);
+
+    send(
+        &mut ctx,
+        &[fixture.withdraw_ix(&distribution_plan(1, DISTRIBUTED, 0))],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    // The keeper swaps off-chain into its reward account
+    set_token_balance(&mut ctx, &fixture.keeper_reward_token_account, REWARDS).await;
+
+    let without_owner_share = fixture.vault.ix.settle_distribution(
+        &fixture.vault.primary_keeper(),
+        1,
+        &fixture.keeper_reward_token_account,
+        &fixture.reward_mint,
+        &spl_token_2022::id(),
+        &[],
+    );
+    let err = send(&mut ctx, &[without_owner_share], &[&fixture.vault.keeper])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidOwnerShareAccounts.into())
+    );
+
+    send(&mut ctx, &[fixture.settle_ix(1)], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut ctx, &owner_share).await, REWARDS / 4);
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.keeper_reward_token_account).await,
+        REWARDS * 3 / 4
+    );
+
+    let owner_account = Pubkey::new_unique();
+    ctx.set_account(
+        &owner_account,
+        &token_account(&fixture.reward_mint, &owner.pubkey(), 0).into(),
+    );
+    let claim = fixture.vault.ix.claim_owner_share(
+        &owner.pubkey(),
+        &fixture.reward_mint,
+        &owner_account,
+        &spl_token_2022::id(),
+    );
+    send(&mut ctx, &[claim], &[owner]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, &owner_account).await, REWARDS / 4);
+    assert_eq!(token_balance(&mut ctx, &owner_share).await, 0);
+}
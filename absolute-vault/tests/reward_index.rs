@@ -0,0 +1,636 @@
+mod common;
+
+use absolute_vault::{RewardIndex, RewardPosition, VaultError};
+use common::*;
+use miko_client::{pda, vault::TokenRecovery};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const REWARD_FUNDS: u64 = 1_000_000;
+
+struct Holder {
+    wallet: Keypair,
+    miko_account: Pubkey,
+    reward_account: Pubkey,
+}
+
+struct RewardFixture {
+    vault: VaultFixture,
+    reward_mint: Pubkey,
+    vault_reward_token_account: Pubkey,
+    holders: Vec<Holder>,
+}
+
+impl RewardFixture {
+    async fn start(balances: &[u64]) -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let token_mint = Pubkey::new_unique();
+        let reward_mint = Pubkey::new_unique();
+        add_dial(&mut pt, &Pubkey::new_unique());
+        let vault = VaultFixture::new(token_mint);
+        let vault_reward_token_account = Pubkey::new_unique();
+
+        let mint_authority = Pubkey::new_unique();
+        pt.add_account(token_mint, mint_account(MIKO_DECIMALS, &mint_authority));
+        pt.add_account(reward_mint, mint_account(6, &mint_authority));
+        pt.add_account(
+            vault_reward_token_account,
+            token_account(&reward_mint, &vault.vault, REWARD_FUNDS),
+        );
+
+        let holders = balances
+            .iter()
+            .map(|&balance| {
+                let holder = Holder {
+                    wallet: Keypair::new(),
+                    miko_account: Pubkey::new_unique(),
+                    reward_account: Pubkey::new_unique(),
+                };
+                pt.add_account(holder.wallet.pubkey(), funded_account());
+                add_hooked_token_account(
+                    &mut pt,
+                    holder.miko_account,
+                    &token_mint,
+                    &holder.wallet.pubkey(),
+                    balance,
+                );
+                pt.add_account(
+                    holder.reward_account,
+                    token_account(&reward_mint, &holder.wallet.pubkey(), 0),
+                );
+                holder
+            })
+            .collect();
+
+        vault.add_signers(&mut pt);
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+
+        let fixture = Self {
+            vault,
+            reward_mint,
+            vault_reward_token_account,
+            holders,
+        };
+
+        let init_index = fixture.vault.ix.initialize_reward_index(
+            &fixture.vault.primary_keeper(),
+            &reward_mint,
+            &vault_reward_token_account,
+        );
+        send(&mut ctx, &[init_index], &[&fixture.vault.keeper])
+            .await
+            .unwrap();
+
+        (ctx, fixture)
+    }
+
+    fn reward_index(&self) -> Pubkey {
+        pda::reward_index_pda(&self.vault.vault, &self.reward_mint)
+    }
+
+    fn register_ix(&self, holder: &Pubkey, payer: &Pubkey) -> Instruction {
+        self.vault.ix.register_position(holder, payer)
+    }
+
+    fn checkpoint_ix(&self, holder: &Holder, payer: &Pubkey) -> Instruction {
+        self.vault.ix.checkpoint_position(
+            &holder.wallet.pubkey(),
+            &self.reward_mint,
+            &holder.miko_account,
+            payer,
+            false,
+        )
+    }
+
+    /// Move all of a holder's MIKO away and checkpoint the emptied stake
+    async fn unstake(&self, ctx: &mut ProgramTestContext, holder: &Holder) {
+        let elsewhere = Pubkey::new_unique();
+        set_hooked_token_account(
+            ctx,
+            elsewhere,
+            &self.vault.token_mint,
+            &Pubkey::new_unique(),
+            0,
+        );
+        let balance = token_balance(ctx, &holder.miko_account).await;
+        hooked_transfer(ctx, &holder.miko_account, &elsewhere, balance).await;
+        let payer = ctx.payer.pubkey();
+        send(ctx, &[self.checkpoint_ix(holder, &payer)], &[])
+            .await
+            .unwrap();
+    }
+
+    fn fund_ix(&self, amount: u64) -> Instruction {
+        self.vault.ix.fund_reward_index(
+            &self.vault.primary_keeper(),
+            &self.reward_mint,
+            &self.vault_reward_token_account,
+            amount,
+        )
+    }
+
+    fn claim_ix(&self, holder: &Holder) -> Instruction {
+        self.vault.ix.claim_rewards(
+            &holder.wallet.pubkey(),
+            &self.reward_mint,
+            &holder.miko_account,
+            &self.vault_reward_token_account,
+            &holder.reward_account,
+            &spl_token_2022::id(),
+        )
+    }
+
+    fn close_ix(&self, dust_receiver: &Pubkey) -> Instruction {
+        let authority = self.vault.authority.pubkey();
+        self.vault.ix.close_reward_index(
+            &authority,
+            &self.reward_mint,
+            &self.vault_reward_token_account,
+            dust_receiver,
+            &spl_token_2022::id(),
+            &authority,
+        )
+    }
+
+    fn report_ix(&self, holder: &Holder) -> Instruction {
+        self.vault.ix.report_holder_weight(
+            &holder.wallet.pubkey(),
+            &self.reward_mint,
+            &holder.miko_account,
+        )
+    }
+
+    async fn position(&self, ctx: &mut ProgramTestContext, holder: &Holder) -> RewardPosition {
+        let address = pda::reward_position_pda(
+            &self.vault.vault,
+            &holder.wallet.pubkey(),
+            &self.reward_mint,
+        );
+        fetch(ctx, &address).await
+    }
+
+    async fn register_and_checkpoint(&self, ctx: &mut ProgramTestContext, holder: &Holder) {
+        let wallet = holder.wallet.pubkey();
+        let instructions = [
+            self.register_ix(&wallet, &wallet),
+            self.checkpoint_ix(holder, &wallet),
+        ];
+        send(ctx, &instructions, &[&holder.wallet]).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn rewards_are_shared_pro_rata_to_checkpointed_balances() {
+    let (mut ctx, fixture) = RewardFixture::start(&[300, 100]).await;
+    for holder in &fixture.holders {
+        fixture.register_and_checkpoint(&mut ctx, holder).await;
+    }
+
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(400_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    for holder in &fixture.holders {
+        send(&mut ctx, &[fixture.claim_ix(holder)], &[&holder.wallet])
+            .await
+            .unwrap();
+    }
+
+    let first = &fixture.holders[0];
+    let second = &fixture.holders[1];
+    assert_eq!(
+        token_balance(&mut ctx, &first.reward_account).await,
+        300_000
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &second.reward_account).await,
+        100_000
+    );
+
+    let index: RewardIndex = fetch(&mut ctx, &fixture.reward_index()).await;
+    assert_eq!(index.total_staked, 400);
+    assert_eq!(index.total_funded, 400_000);
+    assert_eq!(index.total_claimed, 400_000);
+
+    let err = send(&mut ctx, &[fixture.claim_ix(first)], &[&first.wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NothingToClaim.into()));
+}
+
+#[tokio::test]
+async fn funding_cannot_exceed_vault_reward_balance() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    fixture
+        .register_and_checkpoint(&mut ctx, &fixture.holders[0])
+        .await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.fund_ix(REWARD_FUNDS + 1)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InsufficientRewardBalance.into())
+    );
+}
+
+#[tokio::test]
+async fn excluded_wallets_cannot_register() {
+    let (mut ctx, fixture) = RewardFixture::start(&[]).await;
+    let keeper = fixture.vault.keeper.pubkey();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.register_ix(&keeper, &keeper)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::HolderExcluded.into()));
+}
+
+#[tokio::test]
+async fn funded_rewards_cannot_be_recovered_as_foreign_tokens() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    fixture
+        .register_and_checkpoint(&mut ctx, &fixture.holders[0])
+        .await;
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(400_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    // Not even the unfunded remainder: it may be swap proceeds awaiting funding
+    let recovery = TokenRecovery {
+        mint: fixture.reward_mint,
+        vault_token_account: fixture.vault_reward_token_account,
+        destination_token_account: fixture.holders[0].reward_account,
+        token_program: spl_token_2022::id(),
+        amount: REWARD_FUNDS - 400_000,
+    };
+    let recover = fixture
+        .vault
+        .ix
+        .recover_tokens(&fixture.vault.authority.pubkey(), &recovery, 0);
+    let err = send(&mut ctx, &[recover], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::CannotRecoverRewardMint.into())
+    );
+}
+
+#[tokio::test]
+async fn funding_requires_an_amount_and_staked_holders() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+
+    let err = send(&mut ctx, &[fixture.fund_ix(0)], &[&fixture.vault.keeper])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::InvalidAmount.into()));
+
+    let err = send(&mut ctx, &[fixture.fund_ix(1)], &[&fixture.vault.keeper])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NoStakedBalance.into()));
+}
+
+#[tokio::test]
+async fn checkpoint_rejects_another_holders_token_account() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100, 200]).await;
+    let first = &fixture.holders[0];
+    let wallet = first.wallet.pubkey();
+    send(
+        &mut ctx,
+        &[fixture.register_ix(&wallet, &wallet)],
+        &[&first.wallet],
+    )
+    .await
+    .unwrap();
+
+    let borrowed = Holder {
+        wallet: first.wallet.insecure_clone(),
+        miko_account: fixture.holders[1].miko_account,
+        reward_account: first.reward_account,
+    };
+    let err = send(
+        &mut ctx,
+        &[fixture.checkpoint_ix(&borrowed, &wallet)],
+        &[&first.wallet],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidHolderTokenAccount.into())
+    );
+}
+
+#[tokio::test]
+async fn checkpoint_moves_a_position_to_another_account_only_for_its_holder() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    let holder = &fixture.holders[0];
+    let wallet = holder.wallet.pubkey();
+    fixture.register_and_checkpoint(&mut ctx, holder).await;
+
+    // Anyone can open an empty MIKO account owned by the holder, checkpoint included
+    let decoy = Holder {
+        wallet: holder.wallet.insecure_clone(),
+        miko_account: Pubkey::new_unique(),
+        reward_account: holder.reward_account,
+    };
+    set_hooked_token_account(
+        &mut ctx,
+        decoy.miko_account,
+        &fixture.vault.token_mint,
+        &wallet,
+        0,
+    );
+
+    let payer = ctx.payer.pubkey();
+    let err = send(&mut ctx, &[fixture.checkpoint_ix(&decoy, &payer)], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidHolderTokenAccount.into())
+    );
+
+    let signed = fixture.vault.ix.checkpoint_position(
+        &wallet,
+        &fixture.reward_mint,
+        &decoy.miko_account,
+        &payer,
+        true,
+    );
+    send(&mut ctx, &[signed], &[&holder.wallet]).await.unwrap();
+    let position: RewardPosition = fetch(
+        &mut ctx,
+        &pda::reward_position_pda(&fixture.vault.vault, &wallet, &fixture.reward_mint),
+    )
+    .await;
+    assert_eq!(position.token_account, decoy.miko_account);
+}
+
+#[tokio::test]
+async fn reward_index_cannot_close_with_unclaimed_rewards() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    let holder = &fixture.holders[0];
+    fixture.register_and_checkpoint(&mut ctx, holder).await;
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(400_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let close_ix = fixture.close_ix(&holder.reward_account);
+    let err = send(
+        &mut ctx,
+        std::slice::from_ref(&close_ix),
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::RewardsOutstanding.into()));
+
+    send(&mut ctx, &[fixture.claim_ix(holder)], &[&holder.wallet])
+        .await
+        .unwrap();
+
+    // A staked position may still earn from rounding leftovers, so it blocks too
+    let err = send(
+        &mut ctx,
+        std::slice::from_ref(&close_ix),
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::RewardsOutstanding.into()));
+
+    fixture.unstake(&mut ctx, holder).await;
+    send(&mut ctx, &[close_ix], &[&fixture.vault.authority])
+        .await
+        .unwrap();
+    assert!(ctx
+        .banks_client
+        .get_account(fixture.reward_index())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn reward_index_closes_over_rounding_dust_of_uneven_shares() {
+    let (mut ctx, fixture) = RewardFixture::start(&[1, 1, 1]).await;
+    for holder in &fixture.holders {
+        fixture.register_and_checkpoint(&mut ctx, holder).await;
+    }
+    send(&mut ctx, &[fixture.fund_ix(100)], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    for holder in &fixture.holders {
+        send(&mut ctx, &[fixture.claim_ix(holder)], &[&holder.wallet])
+            .await
+            .unwrap();
+        assert_eq!(token_balance(&mut ctx, &holder.reward_account).await, 33);
+        fixture.unstake(&mut ctx, holder).await;
+    }
+
+    let index: RewardIndex = fetch(&mut ctx, &fixture.reward_index()).await;
+    assert_eq!(index.open_positions, 0);
+    assert_eq!(index.outstanding().unwrap(), 1);
+
+    let dust_receiver = Pubkey::new_unique();
+    ctx.set_account(
+        &dust_receiver,
+        &token_account(&fixture.reward_mint, &fixture.vault.authority.pubkey(), 0).into(),
+    );
+    send(
+        &mut ctx,
+        &[fixture.close_ix(&dust_receiver)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut ctx, &dust_receiver).await, 1);
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_reward_token_account).await,
+        REWARD_FUNDS - 100
+    );
+}
+
+#[tokio::test]
+async fn transferred_miko_earns_only_for_its_new_holder() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100, 0]).await;
+    for holder in &fixture.holders {
+        fixture.register_and_checkpoint(&mut ctx, holder).await;
+    }
+    let seller = &fixture.holders[0];
+    let buyer = &fixture.holders[1];
+
+    // The seller's stake is left in place while the buyer checkpoints the same MIKO
+    warp_by(&mut ctx, 10).await;
+    hooked_transfer(&mut ctx, &seller.miko_account, &buyer.miko_account, 100).await;
+    send(
+        &mut ctx,
+        &[fixture.checkpoint_ix(buyer, &buyer.wallet.pubkey())],
+        &[&buyer.wallet],
+    )
+    .await
+    .unwrap();
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(200_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    warp_by(&mut ctx, 10).await;
+
+    let err = send(&mut ctx, &[fixture.claim_ix(seller)], &[&seller.wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NothingToClaim.into()));
+    send(&mut ctx, &[fixture.claim_ix(buyer)], &[&buyer.wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &buyer.reward_account).await,
+        100_000
+    );
+
+    // Poking the seller releases the share it earned after selling
+    send(
+        &mut ctx,
+        &[fixture.checkpoint_ix(seller, &buyer.wallet.pubkey())],
+        &[&buyer.wallet],
+    )
+    .await
+    .unwrap();
+    let index: RewardIndex = fetch(&mut ctx, &fixture.reward_index()).await;
+    assert_eq!(index.total_staked, 100);
+    assert_eq!(index.total_forfeited, 100_000);
+    assert_eq!(index.outstanding().unwrap(), 0);
+    assert_eq!(token_balance(&mut ctx, &seller.reward_account).await, 0);
+}
+
+#[tokio::test]
+async fn weight_report_credits_a_holder_that_kept_its_stake() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    let holder = &fixture.holders[0];
+    fixture.register_and_checkpoint(&mut ctx, holder).await;
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(100_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    warp_by(&mut ctx, 10).await;
+    send(&mut ctx, &[fixture.report_ix(holder)], &[])
+        .await
+        .unwrap();
+    let position = fixture.position(&mut ctx, holder).await;
+    assert_eq!(position.balance, 100);
+    assert_eq!(position.accrued, 100_000);
+
+    send(&mut ctx, &[fixture.claim_ix(holder)], &[&holder.wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &holder.reward_account).await,
+        100_000
+    );
+}
+
+#[tokio::test]
+async fn weight_report_lowers_the_stake_of_a_seller() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100, 0]).await;
+    for holder in &fixture.holders {
+        fixture.register_and_checkpoint(&mut ctx, holder).await;
+    }
+    let seller = &fixture.holders[0];
+    let buyer = &fixture.holders[1];
+
+    warp_by(&mut ctx, 10).await;
+    hooked_transfer(&mut ctx, &seller.miko_account, &buyer.miko_account, 60).await;
+    send(&mut ctx, &[fixture.report_ix(seller)], &[])
+        .await
+        .unwrap();
+    let position = fixture.position(&mut ctx, seller).await;
+    assert_eq!(position.balance, 40);
+    let index: RewardIndex = fetch(&mut ctx, &fixture.reward_index()).await;
+    assert_eq!(index.total_staked, 40);
+
+    // Reporting never raises a stake: the buyer's MIKO counts once checkpointed
+    send(&mut ctx, &[fixture.report_ix(buyer)], &[])
+        .await
+        .unwrap();
+    assert_eq!(fixture.position(&mut ctx, buyer).await.balance, 0);
+
+    send(
+        &mut ctx,
+        &[fixture.fund_ix(40_000)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+    warp_by(&mut ctx, 10).await;
+    send(&mut ctx, &[fixture.claim_ix(seller)], &[&seller.wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut ctx, &seller.reward_account).await,
+        40_000
+    );
+}
+
+#[tokio::test]
+async fn weight_report_rejects_a_checkpoint_the_position_is_not_staked_on() {
+    let (mut ctx, fixture) = RewardFixture::start(&[100]).await;
+    let first = &fixture.holders[0];
+    fixture.register_and_checkpoint(&mut ctx, first).await;
+
+    let other_account = Holder {
+        wallet: first.wallet.insecure_clone(),
+        miko_account: Pubkey::new_unique(),
+        reward_account: first.reward_account,
+    };
+    ctx.set_account(
+        &pda::holder_checkpoint_pda(&other_account.miko_account),
+        &holder_checkpoint_account(
+            &fixture.vault.token_mint,
+            &other_account.miko_account,
+            &first.wallet.pubkey(),
+            1_000,
+        )
+        .into(),
+    );
+    let err = send(&mut ctx, &[fixture.report_ix(&other_account)], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidHolderTokenAccount.into())
+    );
+}
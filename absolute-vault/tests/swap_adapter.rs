@@ -0,0 +1,459 @@
+mod common;
+
+use absolute_vault::{
+    DistributionRecord, DistributionStatus, RevenueSplit, VaultError, HOLDERS_TAX_SHARE,
+    OWNER_TAX_SHARE, RAYDIUM_CPMM_PROGRAM_ID,
+};
+use common::*;
+use miko_client::{
+    pda,
+    vault::{ConfigUpdate, DistributionPlan, RaydiumCpmmSwap},
+};
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// Stand-in for Raydium CPMM that honours the `swap_base_input` account layout.
+/// It pays out at a fixed rate stored in the pool account and deliberately ignores
+/// `minimum_amount_out`, so slippage protection has to come from the vault.
+mod mock_amm {
+    use solana_sdk::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    };
+    use spl_token_2022::{
+        extension::StateWithExtensions, instruction::transfer_checked, state::Mint,
+    };
+
+    pub const AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+
+    fn decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+        let data = mint.try_borrow_data()?;
+        Ok(StateWithExtensions::<Mint>::unpack(&data)?.base.decimals)
+    }
+
+    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let [payer, authority, _amm_config, pool_state, input_token_account, output_token_account, input_vault, output_vault, input_token_program, output_token_program, input_mint, output_mint, _observation_state] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let amount_in = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let (numerator, denominator) = {
+            let pool = pool_state.try_borrow_data()?;
+            (
+                u64::from_le_bytes(pool[0..8].try_into().unwrap()),
+                u64::from_le_bytes(pool[8..16].try_into().unwrap()),
+            )
+        };
+        let amount_out = (amount_in as u128 * numerator as u128 / denominator as u128) as u64;
+
+        invoke(
+            &transfer_checked(
+                input_token_program.key,
+                input_token_account.key,
+                input_mint.key,
+                input_vault.key,
+                payer.key,
+                &[],
+                amount_in,
+                decimals(input_mint)?,
+            )?,
+            &[
+                input_token_account.clone(),
+                input_mint.clone(),
+                input_vault.clone(),
+                payer.clone(),
+            ],
+        )?;
+
+        let (_, bump) = Pubkey::find_program_address(&[AUTH_SEED], program_id);
+        invoke_signed(
+            &transfer_checked(
+                output_token_program.key,
+                output_vault.key,
+                output_mint.key,
+                output_token_account.key,
+                authority.key,
+                &[],
+                amount_out,
+                decimals(output_mint)?,
+            )?,
+            &[
+                output_vault.clone(),
+                output_mint.clone(),
+                output_token_account.clone(),
+                authority.clone(),
+            ],
+            &[&[AUTH_SEED, &[bump]]],
+        )
+    }
+}
+
+const HARVESTED: u64 = 1_000_000_000_000;
+const REWARD_LIQUIDITY: u64 = 10_000_000_000_000;
+
+struct SwapFixture {
+    vault: VaultFixture,
+    reward_mint: Pubkey,
+    vault_reward_token_account: Pubkey,
+    swap: RaydiumCpmmSwap,
+    owner_reward_token_account: Pubkey,
+}
+
+impl SwapFixture {
+    /// Vault holding `HARVESTED` MIKO next to a mock pool paying `numerator / denominator`
+    /// reward tokens per MIKO
+    async fn start(numerator: u64, denominator: u64) -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        pt.add_program(
+            "mock_amm",
+            RAYDIUM_CPMM_PROGRAM_ID,
+            processor!(mock_amm::process),
+        );
+
+        let reward_mint = Pubkey::new_unique();
+        let (amm_authority, _) =
+            Pubkey::find_program_address(&[mock_amm::AUTH_SEED], &RAYDIUM_CPMM_PROGRAM_ID);
+        let fixture = Self {
+            vault: VaultFixture::new(Pubkey::new_unique()),
+            reward_mint,
+            vault_reward_token_account: Pubkey::new_unique(),
+            swap: RaydiumCpmmSwap {
+                authority: amm_authority,
+                amm_config: Pubkey::new_unique(),
+                pool_state: Pubkey::new_unique(),
+                input_vault: Pubkey::new_unique(),
+                output_vault: Pubkey::new_unique(),
+                output_token_program: spl_token_2022::id(),
+                output_token_mint: reward_mint,
+                observation_state: Pubkey::new_unique(),
+            },
+            owner_reward_token_account: Pubkey::new_unique(),
+        };
+        let vault = &fixture.vault;
+        let token_mint = vault.token_mint;
+
+        let mint_authority = Pubkey::new_unique();
+        pt.add_account(token_mint, mint_account(MIKO_DECIMALS, &mint_authority));
+        pt.add_account(reward_mint, mint_account(6, &mint_authority));
+        add_dial(&mut pt, &reward_mint);
+        vault.add_vault_token_account(&mut pt, HARVESTED);
+        pt.add_account(
+            fixture.vault_reward_token_account,
+            token_account(&reward_mint, &vault.vault, 0),
+        );
+        pt.add_account(
+            fixture.owner_reward_token_account,
+            token_account(&reward_mint, &vault.owner.pubkey(), 0),
+        );
+        pt.add_account(
+            fixture.swap.input_vault,
+            token_account(&token_mint, &amm_authority, 0),
+        );
+        pt.add_account(
+            fixture.swap.output_vault,
+            token_account(&reward_mint, &amm_authority, REWARD_LIQUIDITY),
+        );
+
+        let mut pool_data = numerator.to_le_bytes().to_vec();
+        pool_data.extend_from_slice(&denominator.to_le_bytes());
+        pt.add_account(
+            fixture.swap.pool_state,
+            Account {
+                lamports: 1_000_000_000,
+                data: pool_data,
+                owner: RAYDIUM_CPMM_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        vault.add_signers(&mut pt);
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+        vault.initialize_owner_share(&mut ctx, &reward_mint).await;
+
+        (ctx, fixture)
+    }
+
+    fn swap_ix(&self, amount_to_swap: u64, expected_minimum_reward_amount: u64) -> Instruction {
+        let plan = DistributionPlan {
+            distribution_hash: [7; 32],
+            ..distribution_plan(1, amount_to_swap, expected_minimum_reward_amount)
+        };
+        self.vault.ix.swap_and_report_distribution_plan(
+            &self.vault.primary_keeper(),
+            &plan,
+            &self.swap,
+            &self.vault_reward_token_account,
+            &[self.vault.owner.pubkey()],
+        )
+    }
+
+    fn claim_owner_share_ix(
+        &self,
+        owner: &Pubkey,
+        owner_reward_token_account: &Pubkey,
+    ) -> Instruction {
+        self.vault.ix.claim_owner_share(
+            owner,
+            &self.reward_mint,
+            owner_reward_token_account,
+            &spl_token_2022::id(),
+        )
+    }
+}
+
+#[tokio::test]
+async fn swap_lands_rewards_in_vault_and_settles_record() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 2).await;
+    let register = fixture
+        .vault
+        .update_pool_registry_ix(vec![fixture.swap.pool_state]);
+    send(&mut ctx, &[register], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+
+    send(
+        &mut ctx,
+        &[fixture.swap_ix(HARVESTED, HARVESTED / 2)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        0
+    );
+    // The owner's share leaves custody; the holders' share stays for reward indexes
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_reward_token_account).await,
+        HARVESTED / 2 * HOLDERS_TAX_SHARE / 100
+    );
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.distribution_id, 1);
+    assert_eq!(vault.total_rewards_distributed, HARVESTED);
+    assert_eq!(vault.unsettled_distribution_id, 0);
+
+    let record: DistributionRecord =
+        fetch(&mut ctx, &pda::distribution_pda(&fixture.vault.vault, 1)).await;
+    assert_eq!(record.status, DistributionStatus::Settled);
+    assert_eq!(record.reward_amount_received, HARVESTED / 2);
+    assert_eq!(record.reward_mint, fixture.reward_mint);
+    let split: RevenueSplit = fetch(&mut ctx, &fixture.vault.revenue_split).await;
+    assert_eq!(record.revenue_split, split.payees);
+}
+
+#[tokio::test]
+async fn swap_below_expected_minimum_is_rejected() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 2).await;
+    let register = fixture
+        .vault
+        .update_pool_registry_ix(vec![fixture.swap.pool_state]);
+    send(&mut ctx, &[register], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[fixture.swap_ix(HARVESTED, HARVESTED / 2 + 1)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::SlippageExceeded.into()));
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        HARVESTED
+    );
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.distribution_id, 0);
+}
+
+#[tokio::test]
+async fn swap_through_unregistered_pool_is_rejected() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 1).await;
+
+    let err = send(
+        &mut ctx,
+        &[fixture.swap_ix(HARVESTED, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::PoolNotRegistered.into()));
+}
+
+#[tokio::test]
+async fn owner_share_stays_claimable_by_previous_owner() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 2).await;
+    let register = fixture
+        .vault
+        .update_pool_registry_ix(vec![fixture.swap.pool_state]);
+    send(&mut ctx, &[register], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    send(
+        &mut ctx,
+        &[fixture.swap_ix(HARVESTED, HARVESTED / 2)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let owner_share = HARVESTED / 2 * OWNER_TAX_SHARE / 100;
+    let previous_owner = &fixture.vault.owner;
+    let owner_share_account = pda::owner_share_pda(
+        &fixture.vault.vault,
+        &previous_owner.pubkey(),
+        &fixture.reward_mint,
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &owner_share_account).await,
+        owner_share
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault_reward_token_account).await,
+        HARVESTED / 2 - owner_share
+    );
+
+    let new_owner = Keypair::new();
+    let update_config = fixture.vault.update_config_ix(ConfigUpdate {
+        new_owner_wallet: Some(new_owner.pubkey()),
+        ..ConfigUpdate::default()
+    });
+    send(&mut ctx, &[update_config], &[&fixture.vault.authority])
+        .await
+        .unwrap();
+
+    let new_owner_account = fixture.owner_reward_token_account;
+    let new_owner_share = fixture.vault.ix.initialize_owner_share(
+        &new_owner.pubkey(),
+        &fixture.reward_mint,
+        &spl_token_2022::id(),
+        &ctx.payer.pubkey(),
+    );
+    send(&mut ctx, &[new_owner_share], &[]).await.unwrap();
+    let err = send(
+        &mut ctx,
+        &[fixture.claim_owner_share_ix(&new_owner.pubkey(), &new_owner_account)],
+        &[&new_owner],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::NothingToClaim.into()));
+
+    send(
+        &mut ctx,
+        &[fixture.claim_owner_share_ix(
+            &previous_owner.pubkey(),
+            &fixture.owner_reward_token_account,
+        )],
+        &[previous_owner],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.owner_reward_token_account).await,
+        owner_share
+    );
+    assert_eq!(token_balance(&mut ctx, &owner_share_account).await, 0);
+}
+
+/// `ix` with its first account `from` swapped for `to`
+fn replace_account(mut ix: Instruction, from: Pubkey, to: Pubkey) -> Instruction {
+    let meta = ix
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == from)
+        .unwrap();
+    meta.pubkey = to;
+    ix
+}
+
+#[tokio::test]
+async fn swap_accounts_must_match_the_adapter_and_vault() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 1).await;
+    let register = fixture
+        .vault
+        .update_pool_registry_ix(vec![fixture.swap.pool_state]);
+    send(&mut ctx, &[register], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+
+    let swap_ix = || fixture.swap_ix(HARVESTED, 0);
+    // Dropping the owner share account leaves the AMM accounts whole
+    let mut without_owner_share = swap_ix();
+    without_owner_share.accounts.pop();
+    let mut truncated = without_owner_share.clone();
+    truncated.accounts.pop();
+
+    let cases = [
+        (
+            replace_account(swap_ix(), RAYDIUM_CPMM_PROGRAM_ID, spl_token_2022::id()),
+            VaultError::InvalidSwapProgram,
+        ),
+        (truncated, VaultError::InvalidSwapAccounts),
+        (without_owner_share, VaultError::InvalidOwnerShareAccounts),
+        (
+            replace_account(swap_ix(), fixture.vault.token_mint, fixture.reward_mint),
+            VaultError::InvalidMint,
+        ),
+        (
+            replace_account(
+                swap_ix(),
+                fixture.vault_reward_token_account,
+                fixture.owner_reward_token_account,
+            ),
+            VaultError::InvalidRewardTokenAccount,
+        ),
+    ];
+    for (ix, expected) in cases {
+        let err = send(&mut ctx, &[ix], &[&fixture.vault.keeper])
+            .await
+            .unwrap_err();
+        assert_eq!(error_code(err), Some(expected.into()));
+    }
+
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        HARVESTED
+    );
+}
+
+#[tokio::test]
+async fn swap_into_a_token_the_dial_did_not_select_is_rejected() {
+    let (mut ctx, fixture) = SwapFixture::start(1, 1).await;
+    let register = fixture
+        .vault
+        .update_pool_registry_ix(vec![fixture.swap.pool_state]);
+    send(&mut ctx, &[register], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    set_dial(&mut ctx, &Pubkey::new_unique());
+
+    let err = send(
+        &mut ctx,
+        &[fixture.swap_ix(HARVESTED, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidRewardTokenAccount.into())
+    );
+}
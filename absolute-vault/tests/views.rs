@@ -0,0 +1,107 @@
+mod common;
+
+use absolute_vault::VaultError;
+use common::*;
+use miko_client::VaultInstructions;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const VAULT_BALANCE: u64 = 250;
+const WITHHELD: u64 = 1_000;
+
+struct ViewFixture {
+    vault: VaultFixture,
+    other_token_account: Pubkey,
+}
+
+impl ViewFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = program_test();
+        let fixture = Self {
+            vault: VaultFixture::new(Pubkey::new_unique()),
+            other_token_account: Pubkey::new_unique(),
+        };
+        let vault = &fixture.vault;
+
+        pt.add_account(
+            vault.token_mint,
+            fee_mint_account(MIKO_DECIMALS, &Pubkey::new_unique(), &vault.vault, WITHHELD),
+        );
+        vault.add_vault_token_account(&mut pt, VAULT_BALANCE);
+        pt.add_account(
+            fixture.other_token_account,
+            token_account(&vault.token_mint, &Pubkey::new_unique(), VAULT_BALANCE),
+        );
+        vault.add_signers(&mut pt);
+
+        let mut ctx = pt.start_with_context().await;
+        vault.initialize(&mut ctx).await;
+        (ctx, fixture)
+    }
+
+    fn summary_ix(&self, vault_token_account: Pubkey) -> Instruction {
+        VaultInstructions {
+            vault_token_account,
+            ..self.vault.ix
+        }
+        .get_vault_summary()
+    }
+}
+
+// The native test runtime does not surface return data, so the views are executed
+// for their account checks and their answers are checked against the same helpers
+
+#[tokio::test]
+async fn vault_summary_reports_balances_and_pending_fees() {
+    let (mut ctx, fixture) = ViewFixture::start().await;
+
+    send(
+        &mut ctx,
+        &[fixture.summary_ix(fixture.vault.vault_token_account)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    let summary = vault.summary(VAULT_BALANCE, WITHHELD);
+    assert_eq!(summary.token_mint, fixture.vault.token_mint);
+    assert_eq!(summary.vault_balance, VAULT_BALANCE);
+    assert_eq!(summary.mint_withheld, WITHHELD);
+    assert_eq!(summary.harvest_threshold, vault.harvest_threshold);
+    assert_eq!(
+        summary.exclusion_count,
+        vault.reward_exclusions.len() as u32
+    );
+
+    let err = send(
+        &mut ctx,
+        &[fixture.summary_ix(fixture.other_token_account)],
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidVaultTokenAccount.into())
+    );
+}
+
+#[tokio::test]
+async fn is_excluded_matches_the_exclusion_list() {
+    let (mut ctx, fixture) = ViewFixture::start().await;
+    let outsider = Pubkey::new_unique();
+
+    let is_excluded = |wallet| fixture.vault.ix.is_excluded(wallet);
+    send(
+        &mut ctx,
+        &[is_excluded(fixture.vault.vault), is_excluded(outsider)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert!(vault.is_excluded(&fixture.vault.vault));
+    assert!(!vault.is_excluded(&outsider));
+}
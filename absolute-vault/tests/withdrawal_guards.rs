@@ -0,0 +1,384 @@
+mod common;
+
+use absolute_vault::{DistributionRecord, DistributionStatus, VaultError};
+use common::*;
+use miko_client::{pda, vault::ConfigUpdate, VaultInstructions};
+use smart_dial::DIAL_STATE_LEN;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+const VAULT_BALANCE: u64 = 1_000_000;
+
+async fn start() -> (ProgramTestContext, DistributionFixture) {
+    DistributionFixture::start(VAULT_BALANCE, 0).await
+}
+
+fn guards_ix(
+    fixture: &DistributionFixture,
+    min_distribution_interval: i64,
+    withdrawal_window: i64,
+    max_withdrawal_per_window: u64,
+    max_threshold_multiple: u64,
+) -> Instruction {
+    fixture.vault.ix.update_withdrawal_guards(
+        &fixture.vault.authority.pubkey(),
+        min_distribution_interval,
+        withdrawal_window,
+        max_withdrawal_per_window,
+        max_threshold_multiple,
+    )
+}
+
+fn withdraw_ix(fixture: &DistributionFixture, distribution_id: u64, amount: u64) -> Instruction {
+    fixture.withdraw_ix(&distribution_plan(distribution_id, amount, 0))
+}
+
+async fn set_guards(
+    ctx: &mut ProgramTestContext,
+    fixture: &DistributionFixture,
+    min_interval: i64,
+    window: i64,
+    window_cap: u64,
+    threshold_multiple: u64,
+) {
+    let ix = guards_ix(
+        fixture,
+        min_interval,
+        window,
+        window_cap,
+        threshold_multiple,
+    );
+    send(ctx, &[ix], &[&fixture.vault.authority]).await.unwrap();
+}
+
+#[tokio::test]
+async fn distributions_respect_minimum_interval() {
+    let (mut ctx, fixture) = start().await;
+    set_guards(&mut ctx, &fixture, 3_600, 0, 0, 0).await;
+
+    send(
+        &mut ctx,
+        &[
+            withdraw_ix(&fixture, 1, VAULT_BALANCE),
+            fixture.settle_ix(1),
+        ],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 2, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::DistributionTooSoon.into())
+    );
+}
+
+#[tokio::test]
+async fn withdrawal_above_threshold_multiple_is_rejected() {
+    let (mut ctx, fixture) = start().await;
+    let update_threshold = fixture.vault.update_config_ix(ConfigUpdate {
+        new_harvest_threshold: Some(VAULT_BALANCE / 4),
+        ..ConfigUpdate::default()
+    });
+    send(&mut ctx, &[update_threshold], &[&fixture.vault.authority])
+        .await
+        .unwrap();
+    set_guards(&mut ctx, &fixture, 0, 0, 0, 2).await;
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::WithdrawalAboveThresholdCap.into())
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        VAULT_BALANCE
+    );
+}
+
+#[tokio::test]
+async fn withdrawals_are_capped_per_window() {
+    let (mut ctx, fixture) = start().await;
+    set_guards(&mut ctx, &fixture, 0, 86_400, VAULT_BALANCE - 1, 0).await;
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::WindowCapExceeded.into()));
+
+    set_guards(&mut ctx, &fixture, 0, 86_400, VAULT_BALANCE, 0).await;
+    // New blockhash so the identical retry is not deduplicated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let vault = fetch_vault(&mut ctx, &fixture.vault.vault).await;
+    assert_eq!(vault.window_withdrawn, VAULT_BALANCE);
+}
+
+#[tokio::test]
+async fn withdrawal_must_take_the_full_vault_balance() {
+    let (mut ctx, fixture) = start().await;
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE - 1)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::MustWithdrawFullAmount.into())
+    );
+}
+
+#[tokio::test]
+async fn guards_reject_negative_intervals_and_caps_without_a_window() {
+    let (mut ctx, fixture) = start().await;
+
+    for ix in [
+        guards_ix(&fixture, -1, 0, 0, 0),
+        guards_ix(&fixture, 0, -1, 0, 0),
+        guards_ix(&fixture, 0, 0, VAULT_BALANCE, 0),
+    ] {
+        let err = send(&mut ctx, &[ix], &[&fixture.vault.authority])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error_code(err),
+            Some(VaultError::InvalidWithdrawalGuards.into())
+        );
+    }
+}
+
+#[tokio::test]
+async fn unsettled_distribution_blocks_the_next_withdrawal() {
+    let (mut ctx, fixture) = start().await;
+    send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 2, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::PreviousDistributionNotSettled.into())
+    );
+
+    let authority = fixture.vault.authority.pubkey();
+    let close_record = fixture
+        .vault
+        .ix
+        .close_distribution_record(&authority, 1, &authority);
+    let err = send(&mut ctx, &[close_record], &[&fixture.vault.authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::DistributionStillOpen.into())
+    );
+
+    send(&mut ctx, &[fixture.settle_ix(1)], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(&mut ctx, &[fixture.settle_ix(1)], &[&fixture.vault.keeper])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::DistributionNotPending.into())
+    );
+
+    send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 2, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn shortfall_blocks_withdrawals_until_acknowledged() {
+    let (mut ctx, fixture) = start().await;
+    send(
+        &mut ctx,
+        &[
+            fixture.withdraw_ix(&distribution_plan(1, VAULT_BALANCE, 1)),
+            fixture.settle_ix(1),
+        ],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 2, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::UnacknowledgedShortfall.into())
+    );
+
+    send(
+        &mut ctx,
+        &[fixture.acknowledge_ix(1)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap();
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let err = send(
+        &mut ctx,
+        &[fixture.acknowledge_ix(1)],
+        &[&fixture.vault.authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::NoShortfallToAcknowledge.into())
+    );
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 2, 0)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn withdrawal_pays_out_only_in_the_dial_reward_token() {
+    let (mut ctx, fixture) = start().await;
+    set_dial(&mut ctx, &Pubkey::new_unique());
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidRewardTokenAccount.into())
+    );
+    assert_eq!(
+        token_balance(&mut ctx, &fixture.vault.vault_token_account).await,
+        VAULT_BALANCE
+    );
+}
+
+#[tokio::test]
+async fn pending_distribution_settles_after_the_dial_moves_on() {
+    let (mut ctx, fixture) = start().await;
+    send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap();
+
+    // Settlement checks the reward mint recorded at withdrawal, not the dial's current one
+    set_dial(&mut ctx, &Pubkey::new_unique());
+    send(&mut ctx, &[fixture.settle_ix(1)], &[&fixture.vault.keeper])
+        .await
+        .unwrap();
+    let record: DistributionRecord =
+        fetch(&mut ctx, &pda::distribution_pda(&fixture.vault.vault, 1)).await;
+    assert_eq!(record.status, DistributionStatus::Settled);
+    assert_eq!(record.reward_mint, fixture.reward_mint);
+}
+
+#[tokio::test]
+async fn unmigrated_dial_is_rejected() {
+    let (mut ctx, fixture) = start().await;
+    // A dial still in its Borsh layout is shorter than the zero-copy account
+    let mut dial = dial_account(&fixture.reward_mint);
+    dial.data.truncate(DIAL_STATE_LEN - 43);
+    ctx.set_account(&pda::dial_state_pda(), &dial.into());
+
+    let err = send(
+        &mut ctx,
+        &[withdraw_ix(&fixture, 1, VAULT_BALANCE)],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(VaultError::AccountNotMigrated.into()));
+}
+
+#[tokio::test]
+async fn withdrawal_only_draws_on_the_vault_miko_account() {
+    let (mut ctx, fixture) = start().await;
+    // Vault-owned reward custody, holding rewards funded to holders
+    let custody = Pubkey::new_unique();
+    ctx.set_account(
+        &custody,
+        &token_account(&fixture.reward_mint, &fixture.vault.vault, VAULT_BALANCE).into(),
+    );
+
+    let from_custody = VaultInstructions {
+        vault_token_account: custody,
+        ..fixture.vault.ix
+    };
+    let err = send(
+        &mut ctx,
+        &[from_custody.withdraw_and_report_distribution_plan(
+            &fixture.vault.primary_keeper(),
+            &distribution_plan(1, VAULT_BALANCE, 0),
+            &fixture.keeper_token_account,
+            &fixture.keeper_reward_token_account,
+            &[],
+        )],
+        &[&fixture.vault.keeper],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(VaultError::InvalidVaultTokenAccount.into())
+    );
+    assert_eq!(token_balance(&mut ctx, &custody).await, VAULT_BALANCE);
+}
@@ -0,0 +1,94 @@
+//! Human-readable output of program state and events
+
+use absolute_vault::{DistributionPlanReport, KeeperWorkEntry, PoolRegistry, VaultState};
+use anchor_lang::prelude::Pubkey;
+use smart_dial::DialState;
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn print_vault(address: &Pubkey, vault: &VaultState, registry: Option<&PoolRegistry>) {
+    println!("Vault: {address}");
+    println!("  Version: {}", vault.version);
+    println!("  Token mint: {}", vault.token_mint);
+    println!("  Authority: {}", vault.authority);
+    println!("  Keeper authority: {}", vault.keeper_authority);
+    println!("  Owner wallet: {}", vault.owner_wallet);
+    println!("  Guardian: {}", vault.guardian);
+    println!("  Harvest threshold: {}", vault.harvest_threshold);
+    println!("  Burn share (bps): {}", vault.burn_share_bps);
+    println!("  Launch timestamp: {}", vault.launch_timestamp);
+    println!("  Total fees harvested: {}", vault.total_fees_harvested);
+    println!("  Total burned: {}", vault.total_burned);
+    println!(
+        "  Total rewards distributed: {}",
+        vault.total_rewards_distributed
+    );
+    println!(
+        "  Total emergency withdrawn: {}",
+        vault.total_emergency_withdrawn
+    );
+    println!("  Pending burn: {}", vault.pending_burn_amount);
+    println!("  Distribution id: {}", vault.distribution_id);
+    println!(
+        "  Unsettled distribution: {}",
+        vault.unsettled_distribution_id
+    );
+    println!(
+        "  Unacknowledged shortfall: {}",
+        vault.unacknowledged_shortfall_id
+    );
+    println!("  Last harvest: {}", vault.last_harvest_time);
+    println!("  Last distribution: {}", vault.last_distribution_time);
+    println!("  Last keeper heartbeat: {}", vault.last_keeper_heartbeat);
+    println!("  Reward exclusions ({}):", vault.reward_exclusions.len());
+    for wallet in &vault.reward_exclusions {
+        println!("    {wallet}");
+    }
+    match registry {
+        Some(registry) => {
+            println!("  Registered pools ({}):", registry.pools.len());
+            for pool in &registry.pools {
+                println!("    {pool}");
+            }
+        }
+        None => println!("  Pool registry: not initialized"),
+    }
+}
+
+pub fn print_dial(address: &Pubkey, dial: &DialState) {
+    println!("Dial: {address}");
+    println!("  Version: {}", dial.version);
+    println!("  Authority: {}", dial.authority);
+    println!("  Current reward token: {}", dial.current_reward_token);
+    println!("  Launch timestamp: {}", dial.launch_timestamp);
+    println!("  Last update: {}", dial.last_update);
+    println!("  Update count: {}", dial.update_count);
+    println!("  Update history ({}):", dial.update_history().count());
+    for record in dial.update_history() {
+        println!(
+            "    #{} at {}: {} -> {}",
+            record.update_number, record.timestamp, record.old_token, record.new_token
+        );
+    }
+}
+
+pub fn print_keeper_entry(entry: &KeeperWorkEntry) {
+    println!(
+        "{} {:?} {} {}",
+        entry.timestamp, entry.work_type, entry.amount, entry.details
+    );
+}
+
+pub fn print_plan_report(report: &DistributionPlanReport) {
+    println!("Distribution plan {}", report.distribution_id);
+    println!("  Timestamp: {}", report.timestamp);
+    println!("  Keeper: {}", report.keeper);
+    println!("  Withdrawn MIKO: {}", report.withdrawn_miko_amount);
+    println!(
+        "  Expected minimum reward: {}",
+        report.expected_minimum_reward_amount
+    );
+    println!("  Distribution hash: {}", hex(&report.distribution_hash));
+}
@@ -0,0 +1,460 @@
+//! `miko`: operator CLI for the MIKO absolute vault and smart dial
+
+mod display;
+mod transaction;
+
+use std::{thread, time::Duration};
+
+use absolute_vault::{
+    DistributionPlanReport, ExclusionAction, KeeperWorkEntry, KeeperWorkLog, PoolRegistry,
+    VaultState,
+};
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use miko_client::{
+    dial, pda,
+    state::{decode, decode_events, decode_zero_copy},
+    vault::ConfigUpdate,
+    Keeper, VaultInstructions,
+};
+use smart_dial::DialState;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::transaction::{send_encoded, submit, OfflineArgs, SignerArg};
+
+#[derive(Parser)]
+#[command(
+    name = "miko",
+    version,
+    about = "Operate the MIKO absolute vault and smart dial"
+)]
+struct Cli {
+    /// RPC endpoint; defaults to a local validator
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "MIKO_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Fee payer: a keypair file, or a pubkey when signing offline
+    #[arg(
+        long,
+        short = 'k',
+        global = true,
+        default_value = "~/.config/solana/id.json"
+    )]
+    keypair: String,
+
+    #[command(flatten)]
+    offline: OfflineArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Absolute vault administration
+    Vault {
+        /// MIKO mint the vault was created for
+        #[arg(long, env = "MIKO_MINT")]
+        mint: Pubkey,
+
+        #[command(subcommand)]
+        command: VaultCommand,
+    },
+    /// Smart dial administration
+    Dial {
+        #[command(subcommand)]
+        command: DialCommand,
+    },
+    /// Print the distribution plan reports emitted by a transaction
+    DecodeReport { signature: Signature },
+    /// Send a transaction produced with --sign-only or --dump-unsigned
+    SendTransaction {
+        /// Base64 transaction
+        transaction: String,
+
+        /// Signature collected offline
+        #[arg(long = "signer", value_name = "PUBKEY=SIGNATURE")]
+        signers: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VaultCommand {
+    /// Create the vault with its pool registry and revenue split
+    Init {
+        #[arg(long)]
+        owner_wallet: Pubkey,
+
+        #[arg(long)]
+        keeper_authority: Pubkey,
+
+        /// Vault authority; defaults to the fee payer
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+    /// Print the vault state and registered pools
+    Show,
+    /// Exclude a wallet from reward distributions
+    AddExclusion {
+        wallet: Pubkey,
+
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+    /// Make an excluded wallet eligible for rewards again
+    RemoveExclusion {
+        wallet: Pubkey,
+
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+    /// Change vault settings; omitted settings are kept
+    UpdateConfig {
+        #[arg(long)]
+        owner_wallet: Option<Pubkey>,
+
+        #[arg(long)]
+        harvest_threshold: Option<u64>,
+
+        #[arg(long)]
+        new_authority: Option<Pubkey>,
+
+        #[arg(long)]
+        keeper_authority: Option<Pubkey>,
+
+        #[arg(long)]
+        burn_share_bps: Option<u16>,
+
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+    /// Add pools to the pool registry
+    RegisterPools {
+        #[arg(required = true)]
+        pools: Vec<Pubkey>,
+
+        /// Keeper signing the update; defaults to the fee payer
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        keeper: Option<String>,
+
+        /// The keeper signs through a pool-reporter role grant
+        #[arg(long)]
+        granted: bool,
+    },
+    /// Print the latest keeper work log entries
+    KeeperLog {
+        #[arg(long, short = 'n', default_value_t = 10)]
+        lines: usize,
+
+        /// Keep polling for new entries
+        #[arg(long, short = 'f')]
+        follow: bool,
+
+        /// Seconds between polls when following
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum DialCommand {
+    /// Create the dial
+    Init {
+        #[arg(long)]
+        launch_timestamp: i64,
+
+        /// Dial authority; defaults to the fee payer
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+    /// Print the dial state and update history
+    Show,
+    /// Select the reward token for a cycle
+    UpdateRewardToken {
+        token: Pubkey,
+
+        #[arg(long)]
+        cycle_start: i64,
+
+        #[arg(long)]
+        next_cycle_start: i64,
+
+        #[arg(long, value_name = "KEYPAIR_OR_PUBKEY")]
+        authority: Option<String>,
+    },
+}
+
+struct Context {
+    rpc: RpcClient,
+    keypair: String,
+    offline: OfflineArgs,
+}
+
+impl Context {
+    fn payer(&self) -> Result<SignerArg> {
+        SignerArg::parse(&self.keypair)
+    }
+
+    fn signer(source: Option<&str>) -> Result<Option<SignerArg>> {
+        source.map(SignerArg::parse).transpose()
+    }
+
+    fn fetch<T: anchor_lang::AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let data = self.rpc.get_account_data(address)?;
+        decode(&data).map_err(|err| anyhow!("decoding {address}: {err}"))
+    }
+
+    fn fetch_zero_copy<T: anchor_lang::ZeroCopy>(&self, address: &Pubkey) -> Result<T> {
+        let data = self.rpc.get_account_data(address)?;
+        decode_zero_copy(&data).map_err(|err| anyhow!("decoding {address}: {err}"))
+    }
+
+    fn fetch_optional<T: anchor_lang::AccountDeserialize>(
+        &self,
+        address: &Pubkey,
+    ) -> Result<Option<T>> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value;
+        account
+            .map(|account| decode(&account.data))
+            .transpose()
+            .map_err(|err| anyhow!("decoding {address}: {err}"))
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let ctx = Context {
+        rpc: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        keypair: cli.keypair,
+        offline: cli.offline,
+    };
+
+    match cli.command {
+        Command::Vault { mint, command } => run_vault(&ctx, mint, command),
+        Command::Dial { command } => run_dial(&ctx, command),
+        Command::DecodeReport { signature } => decode_report(&ctx, &signature),
+        Command::SendTransaction {
+            transaction,
+            signers,
+        } => send_encoded(&ctx.rpc, &transaction, &signers),
+    }
+}
+
+fn run_vault(ctx: &Context, mint: Pubkey, command: VaultCommand) -> Result<()> {
+    // None of these commands touch the vault token account
+    let vault = VaultInstructions::new(mint, Pubkey::default());
+
+    match command {
+        VaultCommand::Init {
+            owner_wallet,
+            keeper_authority,
+            authority,
+        } => {
+            let payer = ctx.payer()?;
+            let authority = Context::signer(authority.as_deref())?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instructions = [
+                vault.initialize(
+                    &authority.pubkey,
+                    &payer.pubkey,
+                    owner_wallet,
+                    keeper_authority,
+                ),
+                vault.initialize_pool_registry(&payer.pubkey),
+                vault.initialize_revenue_split(&authority.pubkey, &payer.pubkey),
+            ];
+            submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[authority])
+        }
+        VaultCommand::Show => {
+            let state: VaultState = ctx.fetch(&vault.vault)?;
+            let registry: Option<PoolRegistry> =
+                ctx.fetch_optional(&pda::pool_registry_pda(&vault.vault))?;
+            display::print_vault(&vault.vault, &state, registry.as_ref());
+            Ok(())
+        }
+        VaultCommand::AddExclusion { wallet, authority } => {
+            manage_exclusion(ctx, &vault, ExclusionAction::Add, wallet, authority)
+        }
+        VaultCommand::RemoveExclusion { wallet, authority } => {
+            manage_exclusion(ctx, &vault, ExclusionAction::Remove, wallet, authority)
+        }
+        VaultCommand::UpdateConfig {
+            owner_wallet,
+            harvest_threshold,
+            new_authority,
+            keeper_authority,
+            burn_share_bps,
+            authority,
+        } => {
+            let payer = ctx.payer()?;
+            let authority = Context::signer(authority.as_deref())?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let update = ConfigUpdate {
+                new_owner_wallet: owner_wallet,
+                new_harvest_threshold: harvest_threshold,
+                new_authority,
+                new_keeper_authority: keeper_authority,
+                new_burn_share_bps: burn_share_bps,
+            };
+            let instructions = [vault.update_config(&authority.pubkey, &update)];
+            submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[authority])
+        }
+        VaultCommand::RegisterPools {
+            pools,
+            keeper,
+            granted,
+        } => {
+            let payer = ctx.payer()?;
+            let keeper = Context::signer(keeper.as_deref())?;
+            let keeper = keeper.as_ref().unwrap_or(&payer);
+            let signer = if granted {
+                Keeper::granted(keeper.pubkey)
+            } else {
+                Keeper::primary(keeper.pubkey)
+            };
+            let instructions = [vault.update_pool_registry(&signer, pools)];
+            submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[keeper])
+        }
+        VaultCommand::KeeperLog {
+            lines,
+            follow,
+            interval,
+        } => tail_keeper_log(ctx, &vault.vault, lines, follow, interval),
+    }
+}
+
+fn manage_exclusion(
+    ctx: &Context,
+    vault: &VaultInstructions,
+    action: ExclusionAction,
+    wallet: Pubkey,
+    authority: Option<String>,
+) -> Result<()> {
+    let payer = ctx.payer()?;
+    let authority = Context::signer(authority.as_deref())?;
+    let authority = authority.as_ref().unwrap_or(&payer);
+    let instructions = [vault.manage_exclusions(&authority.pubkey, action, wallet)];
+    submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[authority])
+}
+
+/// Entries from sequence number `next` on; those already overwritten in the ring are gone
+fn entries_since(log: &KeeperWorkLog, next: u64) -> Vec<KeeperWorkEntry> {
+    let skip = next.saturating_sub(log.head) as usize;
+    log.entries().into_iter().skip(skip).collect()
+}
+
+fn tail_keeper_log(
+    ctx: &Context,
+    vault: &Pubkey,
+    lines: usize,
+    follow: bool,
+    interval: u64,
+) -> Result<()> {
+    let address = pda::keeper_log_pda(vault);
+    let log = ctx.fetch_zero_copy::<KeeperWorkLog>(&address)?;
+    let entries = log.entries();
+    let start = entries.len().saturating_sub(lines);
+    entries[start..]
+        .iter()
+        .for_each(display::print_keeper_entry);
+
+    if !follow {
+        return Ok(());
+    }
+
+    let mut next = log.tail;
+    loop {
+        thread::sleep(Duration::from_secs(interval));
+        let log = ctx.fetch_zero_copy::<KeeperWorkLog>(&address)?;
+        // A closed and recreated log starts over from sequence zero
+        if log.tail < next {
+            next = log.head;
+        }
+        if log.head > next {
+            eprintln!(
+                "{} entries were overwritten before they could be shown",
+                log.head - next
+            );
+        }
+        entries_since(&log, next)
+            .iter()
+            .for_each(display::print_keeper_entry);
+        next = log.tail;
+    }
+}
+
+fn run_dial(ctx: &Context, command: DialCommand) -> Result<()> {
+    match command {
+        DialCommand::Init {
+            launch_timestamp,
+            authority,
+        } => {
+            let payer = ctx.payer()?;
+            let authority = Context::signer(authority.as_deref())?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instructions = [dial::initialize(
+                &authority.pubkey,
+                &payer.pubkey,
+                launch_timestamp,
+            )];
+            submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[authority])
+        }
+        DialCommand::Show => {
+            let address = pda::dial_state_pda();
+            let state: DialState = ctx.fetch_zero_copy(&address)?;
+            display::print_dial(&address, &state);
+            Ok(())
+        }
+        DialCommand::UpdateRewardToken {
+            token,
+            cycle_start,
+            next_cycle_start,
+            authority,
+        } => {
+            let payer = ctx.payer()?;
+            let authority = Context::signer(authority.as_deref())?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instructions = [dial::update_reward_token(
+                &authority.pubkey,
+                token,
+                cycle_start,
+                next_cycle_start,
+            )];
+            submit(&ctx.rpc, &ctx.offline, &instructions, &payer, &[authority])
+        }
+    }
+}
+
+fn decode_report(ctx: &Context, signature: &Signature) -> Result<()> {
+    let tx = ctx.rpc.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(ctx.rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let logs: Option<Vec<String>> = tx
+        .transaction
+        .meta
+        .and_then(|meta| meta.log_messages.into());
+    let logs = logs.ok_or_else(|| anyhow!("{signature} has no log messages"))?;
+
+    let reports: Vec<DistributionPlanReport> = decode_events(&logs);
+    if reports.is_empty() {
+        return Err(anyhow!("{signature} reported no distribution plan"));
+    }
+    reports.iter().for_each(display::print_plan_report);
+    Ok(())
+}
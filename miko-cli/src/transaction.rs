@@ -0,0 +1,241 @@
+//! Signing, offline signing and submission of CLI transactions
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Args;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+#[derive(Args, Debug)]
+pub struct OfflineArgs {
+    /// Use this blockhash instead of fetching one; required to sign offline
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+
+    /// Sign with the local keypairs and print the transaction instead of sending it
+    #[arg(
+        long,
+        global = true,
+        requires = "blockhash",
+        conflicts_with = "dump_unsigned"
+    )]
+    pub sign_only: bool,
+
+    /// Print the unsigned transaction instead of sending it
+    #[arg(long, global = true)]
+    pub dump_unsigned: bool,
+}
+
+impl OfflineArgs {
+    fn is_offline(&self) -> bool {
+        self.sign_only || self.dump_unsigned
+    }
+}
+
+/// A signer given on the command line as a keypair file, or as a bare pubkey when its
+/// signature is collected offline
+pub struct SignerArg {
+    pub pubkey: Pubkey,
+    keypair: Option<Keypair>,
+}
+
+impl SignerArg {
+    pub fn parse(source: &str) -> Result<Self> {
+        if let Ok(pubkey) = Pubkey::from_str(source) {
+            return Ok(Self {
+                pubkey,
+                keypair: None,
+            });
+        }
+        let path = expand_home(source);
+        let keypair =
+            read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {path}: {err}"))?;
+        Ok(Self {
+            pubkey: keypair.pubkey(),
+            keypair: Some(keypair),
+        })
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+pub fn encode_transaction(tx: &Transaction) -> Result<String> {
+    Ok(STANDARD.encode(bincode::serialize(tx)?))
+}
+
+pub fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .context("transaction is not base64")?;
+    bincode::deserialize(&bytes).context("not a serialized transaction")
+}
+
+/// Sign `instructions` with every local keypair among `payer` and `signers`, then send,
+/// print signed or print unsigned depending on the offline flags
+pub fn submit(
+    rpc: &RpcClient,
+    offline: &OfflineArgs,
+    instructions: &[Instruction],
+    payer: &SignerArg,
+    signers: &[&SignerArg],
+) -> Result<()> {
+    let mut tx = Transaction::new_with_payer(instructions, Some(&payer.pubkey));
+    if let Some(blockhash) = offline.blockhash {
+        tx.message.recent_blockhash = blockhash;
+    }
+
+    if offline.dump_unsigned {
+        println!("{}", encode_transaction(&tx)?);
+        return Ok(());
+    }
+
+    let blockhash = match offline.blockhash {
+        Some(blockhash) => blockhash,
+        None => rpc.get_latest_blockhash()?,
+    };
+    let keypairs: Vec<&Keypair> = std::iter::once(payer)
+        .chain(signers.iter().copied())
+        .filter_map(|signer| signer.keypair.as_ref())
+        .collect();
+    tx.try_partial_sign(&keypairs, blockhash)?;
+
+    if offline.is_offline() {
+        print_signatures(&tx);
+        println!("{}", encode_transaction(&tx)?);
+        return Ok(());
+    }
+
+    send(rpc, &tx)
+}
+
+/// Send a transaction signed elsewhere, adding any signatures collected separately
+pub fn send_encoded(rpc: &RpcClient, encoded: &str, extra_signatures: &[String]) -> Result<()> {
+    let mut tx = decode_transaction(encoded)?;
+    add_signatures(&mut tx, extra_signatures)?;
+    send(rpc, &tx)
+}
+
+/// Place `PUBKEY=SIGNATURE` entries at their signer's position in `tx`
+fn add_signatures(tx: &mut Transaction, extra_signatures: &[String]) -> Result<()> {
+    for entry in extra_signatures {
+        let (pubkey, signature) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected PUBKEY=SIGNATURE, got {entry}"))?;
+        let pubkey = Pubkey::from_str(pubkey)?;
+        let signature = Signature::from_str(signature)?;
+        let position = required_signers(tx)
+            .iter()
+            .position(|key| *key == pubkey)
+            .ok_or_else(|| anyhow!("{pubkey} is not a signer of this transaction"))?;
+        tx.signatures[position] = signature;
+    }
+    Ok(())
+}
+
+fn required_signers(tx: &Transaction) -> &[Pubkey] {
+    let required = tx.message.header.num_required_signatures as usize;
+    &tx.message.account_keys[..required]
+}
+
+fn print_signatures(tx: &Transaction) {
+    for (pubkey, signature) in required_signers(tx).iter().zip(&tx.signatures) {
+        if *signature == Signature::default() {
+            println!("Absent signer: {pubkey}");
+        } else {
+            println!("Signer: {pubkey}={signature}");
+        }
+    }
+}
+
+fn send(rpc: &RpcClient, tx: &Transaction) -> Result<()> {
+    if tx.verify().is_err() {
+        print_signatures(tx);
+        bail!("transaction is missing signatures");
+    }
+    let signature = rpc.send_and_confirm_transaction(tx)?;
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    /// A transaction needing the payer's and a co-signer's signatures
+    fn two_signer_transaction(payer: &Keypair, cosigner: &Keypair) -> Transaction {
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new_readonly(cosigner.pubkey(), true)],
+        );
+        let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        tx.message.recent_blockhash = Hash::new_unique();
+        tx
+    }
+
+    fn signature_entry(signer: &Keypair, tx: &Transaction) -> String {
+        let signature = signer.sign_message(&tx.message_data());
+        format!("{}={}", signer.pubkey(), signature)
+    }
+
+    #[test]
+    fn encoded_transactions_round_trip() {
+        let payer = Keypair::new();
+        let cosigner = Keypair::new();
+        let mut tx = two_signer_transaction(&payer, &cosigner);
+        tx.try_partial_sign(&[&payer], tx.message.recent_blockhash)
+            .unwrap();
+
+        let encoded = encode_transaction(&tx).unwrap();
+        assert_eq!(decode_transaction(&format!("{encoded}\n")).unwrap(), tx);
+    }
+
+    #[test]
+    fn decoding_rejects_malformed_input() {
+        assert!(decode_transaction("not base64!").is_err());
+        assert!(decode_transaction(&STANDARD.encode([0xff; 4])).is_err());
+    }
+
+    #[test]
+    fn offline_signatures_complete_a_partially_signed_transaction() {
+        let payer = Keypair::new();
+        let cosigner = Keypair::new();
+        let mut tx = two_signer_transaction(&payer, &cosigner);
+        tx.try_partial_sign(&[&payer], tx.message.recent_blockhash)
+            .unwrap();
+        assert!(tx.verify().is_err());
+
+        let entry = signature_entry(&cosigner, &tx);
+        add_signatures(&mut tx, &[entry]).unwrap();
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn signatures_of_non_signers_and_malformed_entries_are_rejected() {
+        let payer = Keypair::new();
+        let cosigner = Keypair::new();
+        let mut tx = two_signer_transaction(&payer, &cosigner);
+        let before = tx.signatures.clone();
+
+        let outsider = signature_entry(&Keypair::new(), &tx);
+        let without_separator = signature_entry(&cosigner, &tx).replace('=', "");
+        for entry in [outsider, without_separator] {
+            assert!(add_signatures(&mut tx, &[entry]).is_err());
+        }
+        assert_eq!(tx.signatures, before);
+    }
+}
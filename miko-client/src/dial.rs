@@ -0,0 +1,101 @@
+//! Instruction builders for the smart dial
+
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, system_program, InstructionData,
+    ToAccountMetas,
+};
+use smart_dial::{accounts, instruction as dial_instruction};
+
+use crate::pda;
+
+fn dial_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: smart_dial::id(),
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize(authority: &Pubkey, payer: &Pubkey, launch_timestamp: i64) -> Instruction {
+    dial_ix(
+        accounts::Initialize {
+            dial_state: pda::dial_state_pda(),
+            authority: *authority,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        dial_instruction::Initialize { launch_timestamp },
+    )
+}
+
+pub fn update_reward_token(
+    authority: &Pubkey,
+    new_reward_token: Pubkey,
+    cycle_start: i64,
+    next_cycle_start: i64,
+) -> Instruction {
+    dial_ix(
+        accounts::UpdateRewardToken {
+            dial_state: pda::dial_state_pda(),
+            authority: *authority,
+        },
+        dial_instruction::UpdateRewardToken {
+            new_reward_token,
+            cycle_start,
+            next_cycle_start,
+        },
+    )
+}
+
+pub fn update_authority(authority: &Pubkey, new_authority: Pubkey) -> Instruction {
+    dial_ix(
+        accounts::UpdateAuthority {
+            dial_state: pda::dial_state_pda(),
+            authority: *authority,
+        },
+        dial_instruction::UpdateAuthority { new_authority },
+    )
+}
+
+pub fn sync_launch_timestamp(authority: &Pubkey, new_launch_timestamp: i64) -> Instruction {
+    dial_ix(
+        accounts::SyncLaunchTimestamp {
+            dial_state: pda::dial_state_pda(),
+            authority: *authority,
+        },
+        dial_instruction::SyncLaunchTimestamp {
+            new_launch_timestamp,
+        },
+    )
+}
+
+/// View; the reward token comes back as Borsh return data
+pub fn get_current_reward_token() -> Instruction {
+    dial_ix(
+        accounts::ViewDial {
+            dial_state: pda::dial_state_pda(),
+        },
+        dial_instruction::GetCurrentRewardToken {},
+    )
+}
+
+/// View; a `CycleInfo` comes back as Borsh return data
+pub fn get_cycle_info() -> Instruction {
+    dial_ix(
+        accounts::ViewDial {
+            dial_state: pda::dial_state_pda(),
+        },
+        dial_instruction::GetCycleInfo {},
+    )
+}
+
+pub fn migrate(authority: &Pubkey) -> Instruction {
+    dial_ix(
+        accounts::Migrate {
+            dial_state: pda::dial_state_pda(),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        dial_instruction::Migrate {},
+    )
+}
@@ -0,0 +1,14 @@
+//! Instruction builders, PDA helpers and account decoders for the MIKO programs
+//!
+//! Nothing here talks to a cluster: builders return `Instruction`s and decoders take raw
+//! account or return data, so keepers, scripts and tests bring their own client.
+
+pub mod dial;
+pub mod pda;
+pub mod state;
+pub mod vault;
+
+pub use absolute_vault::ID as VAULT_PROGRAM_ID;
+pub use miko_transfer_hook::ID as TRANSFER_HOOK_PROGRAM_ID;
+pub use smart_dial::ID as DIAL_PROGRAM_ID;
+pub use vault::{Keeper, VaultInstructions};
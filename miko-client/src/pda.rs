@@ -0,0 +1,114 @@
+//! Program-derived addresses of every account the MIKO programs own
+
+use absolute_vault::{
+    KeeperRoleKind, ASSET_LEDGER_SEED, DISTRIBUTION_SEED, HOLDER_POSITION_SEED, KEEPER_LOG_SEED,
+    KEEPER_RESERVE_SEED, KEEPER_ROLE_SEED, OWNER_SHARE_SEED, POOL_REGISTRY_SEED,
+    REVENUE_SPLIT_SEED, REWARD_INDEX_SEED, REWARD_POSITION_SEED, VAULT_SEED, VAULT_TOMBSTONE_SEED,
+};
+use anchor_lang::prelude::Pubkey;
+use miko_transfer_hook::{CHECKPOINT_SEED, EXTRA_ACCOUNT_METAS_SEED};
+use smart_dial::DIAL_STATE_SEED;
+
+fn vault_program_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &absolute_vault::id()).0
+}
+
+pub fn vault_pda(token_mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[VAULT_SEED, token_mint.as_ref()])
+}
+
+pub fn vault_tombstone_pda(token_mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[VAULT_TOMBSTONE_SEED, token_mint.as_ref()])
+}
+
+pub fn pool_registry_pda(vault: &Pubkey) -> Pubkey {
+    vault_program_address(&[POOL_REGISTRY_SEED, vault.as_ref()])
+}
+
+/// Record of distribution `distribution_id`; a new plan is recorded under
+/// `vault.distribution_id + 1`
+pub fn distribution_pda(vault: &Pubkey, distribution_id: u64) -> Pubkey {
+    vault_program_address(&[
+        DISTRIBUTION_SEED,
+        vault.as_ref(),
+        &distribution_id.to_le_bytes(),
+    ])
+}
+
+pub fn revenue_split_pda(vault: &Pubkey) -> Pubkey {
+    vault_program_address(&[REVENUE_SPLIT_SEED, vault.as_ref()])
+}
+
+pub fn reward_index_pda(vault: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[REWARD_INDEX_SEED, vault.as_ref(), reward_mint.as_ref()])
+}
+
+pub fn holder_position_pda(vault: &Pubkey, holder: &Pubkey) -> Pubkey {
+    vault_program_address(&[HOLDER_POSITION_SEED, vault.as_ref(), holder.as_ref()])
+}
+
+pub fn reward_position_pda(vault: &Pubkey, holder: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[
+        REWARD_POSITION_SEED,
+        vault.as_ref(),
+        holder.as_ref(),
+        reward_mint.as_ref(),
+    ])
+}
+
+/// Signer of every owner share account of a vault
+pub fn owner_share_authority_pda(vault: &Pubkey) -> Pubkey {
+    vault_program_address(&[OWNER_SHARE_SEED, vault.as_ref()])
+}
+
+/// Token account holding `owner`'s revenue share of `reward_mint`
+pub fn owner_share_pda(vault: &Pubkey, owner: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[
+        OWNER_SHARE_SEED,
+        vault.as_ref(),
+        owner.as_ref(),
+        reward_mint.as_ref(),
+    ])
+}
+
+pub fn keeper_log_pda(vault: &Pubkey) -> Pubkey {
+    vault_program_address(&[KEEPER_LOG_SEED, vault.as_ref()])
+}
+
+pub fn keeper_reserve_pda(vault: &Pubkey) -> Pubkey {
+    vault_program_address(&[KEEPER_RESERVE_SEED, vault.as_ref()])
+}
+
+pub fn keeper_role_pda(vault: &Pubkey, keeper: &Pubkey, role: KeeperRoleKind) -> Pubkey {
+    vault_program_address(&[
+        KEEPER_ROLE_SEED,
+        vault.as_ref(),
+        keeper.as_ref(),
+        &[role as u8],
+    ])
+}
+
+pub fn asset_ledger_pda(vault: &Pubkey, mint: &Pubkey) -> Pubkey {
+    vault_program_address(&[ASSET_LEDGER_SEED, vault.as_ref(), mint.as_ref()])
+}
+
+pub fn dial_state_pda() -> Pubkey {
+    Pubkey::find_program_address(&[DIAL_STATE_SEED], &smart_dial::id()).0
+}
+
+/// Transfer-hook checkpoint of a MIKO token account
+pub fn holder_checkpoint_pda(token_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[CHECKPOINT_SEED, token_account.as_ref()],
+        &miko_transfer_hook::id(),
+    )
+    .0
+}
+
+pub fn extra_account_metas_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()],
+        &miko_transfer_hook::id(),
+    )
+    .0
+}
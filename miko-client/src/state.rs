@@ -0,0 +1,97 @@
+//! Decoders for program accounts, view return data and events
+
+use absolute_vault::{
+    AssetLedger, DistributionRecord, HolderPosition, KeeperReserve, KeeperRole, KeeperWorkLog,
+    PoolRegistry, RevenueSplit, RewardIndex, RewardPosition, VaultState, VaultTombstone,
+};
+use anchor_lang::{
+    error::ErrorCode, AccountDeserialize, AnchorDeserialize, Discriminator, Result, ZeroCopy,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Decode an account of type `T`, checking its discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Decode a zero-copy account of type `T`, checking its discriminator and size
+/// Accounts still in a legacy Borsh layout have a different size and fail to decode
+pub fn decode_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<T> {
+    let fields = data
+        .strip_prefix(T::DISCRIMINATOR)
+        .ok_or(ErrorCode::AccountDiscriminatorMismatch)?;
+    if fields.len() != std::mem::size_of::<T>() {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok(bytemuck::pod_read_unaligned(fields))
+}
+
+/// Decode what a view instruction passed to `set_return_data`
+pub fn decode_return_data<T: AnchorDeserialize>(data: &[u8]) -> std::io::Result<T> {
+    T::try_from_slice(data)
+}
+
+/// Events of type `T` found in a transaction's log messages
+pub fn decode_events<T: Discriminator + AnchorDeserialize>(log_messages: &[String]) -> Vec<T> {
+    log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter_map(|data| {
+            let payload = data.strip_prefix(T::DISCRIMINATOR)?;
+            T::try_from_slice(payload).ok()
+        })
+        .collect()
+}
+
+/// Any account owned by the absolute vault, told apart by its discriminator
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum VaultAccount {
+    Vault(VaultState),
+    PoolRegistry(PoolRegistry),
+    Distribution(DistributionRecord),
+    RevenueSplit(RevenueSplit),
+    RewardIndex(RewardIndex),
+    HolderPosition(HolderPosition),
+    RewardPosition(RewardPosition),
+    KeeperWorkLog(KeeperWorkLog),
+    KeeperRole(KeeperRole),
+    KeeperReserve(KeeperReserve),
+    AssetLedger(AssetLedger),
+    VaultTombstone(VaultTombstone),
+}
+
+impl VaultAccount {
+    /// Accounts still in a legacy layout fail to decode until migrated
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let account = if data.starts_with(VaultState::DISCRIMINATOR) {
+            Self::Vault(decode(data)?)
+        } else if data.starts_with(PoolRegistry::DISCRIMINATOR) {
+            Self::PoolRegistry(decode(data)?)
+        } else if data.starts_with(DistributionRecord::DISCRIMINATOR) {
+            Self::Distribution(decode(data)?)
+        } else if data.starts_with(RevenueSplit::DISCRIMINATOR) {
+            Self::RevenueSplit(decode(data)?)
+        } else if data.starts_with(RewardIndex::DISCRIMINATOR) {
+            Self::RewardIndex(decode(data)?)
+        } else if data.starts_with(HolderPosition::DISCRIMINATOR) {
+            Self::HolderPosition(decode(data)?)
+        } else if data.starts_with(RewardPosition::DISCRIMINATOR) {
+            Self::RewardPosition(decode(data)?)
+        } else if data.starts_with(KeeperWorkLog::DISCRIMINATOR) {
+            Self::KeeperWorkLog(decode_zero_copy(data)?)
+        } else if data.starts_with(KeeperRole::DISCRIMINATOR) {
+            Self::KeeperRole(decode(data)?)
+        } else if data.starts_with(KeeperReserve::DISCRIMINATOR) {
+            Self::KeeperReserve(decode(data)?)
+        } else if data.starts_with(AssetLedger::DISCRIMINATOR) {
+            Self::AssetLedger(decode_zero_copy(data)?)
+        } else if data.starts_with(VaultTombstone::DISCRIMINATOR) {
+            Self::VaultTombstone(decode(data)?)
+        } else {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        };
+        Ok(account)
+    }
+}
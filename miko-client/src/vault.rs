@@ -0,0 +1,1243 @@
+//! Instruction builders for the absolute vault
+
+use absolute_vault::{
+    accounts, instruction as vault_instruction, ExclusionAction, KeeperRoleKind, KeeperWorkType,
+    RevenuePayee, SwapAdapter, RAYDIUM_CPMM_PROGRAM_ID,
+};
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::{AccountMeta, Instruction},
+    system_program, InstructionData, ToAccountMetas,
+};
+
+use crate::pda;
+
+/// Largest batch of token accounts `harvest_fees` and `emergency_withdraw_withheld` accept
+pub const MAX_HARVEST_ACCOUNTS: usize = 20;
+
+fn vault_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: absolute_vault::id(),
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn vault_ix_with_remaining(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut ix = vault_ix(accounts, data);
+    ix.accounts.extend_from_slice(remaining_accounts);
+    ix
+}
+
+/// Remaining accounts of `harvest_fees` and `emergency_withdraw_withheld`: the token
+/// accounts holding withheld fees, writable and in the order of the `accounts` argument
+pub fn withheld_source_accounts(token_accounts: &[Pubkey]) -> Vec<AccountMeta> {
+    token_accounts
+        .iter()
+        .map(|account| AccountMeta::new(*account, false))
+        .collect()
+}
+
+/// Signer of a keeper instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keeper {
+    pub authority: Pubkey,
+    /// Signs through a `grant_keeper_role` grant rather than as `vault.keeper_authority`
+    pub granted: bool,
+}
+
+impl Keeper {
+    /// `vault.keeper_authority`, which holds every keeper role
+    pub fn primary(authority: Pubkey) -> Self {
+        Self {
+            authority,
+            granted: false,
+        }
+    }
+
+    /// Key holding the role each instruction requires
+    pub fn granted(authority: Pubkey) -> Self {
+        Self {
+            authority,
+            granted: true,
+        }
+    }
+
+    fn role(&self, vault: &Pubkey, role: KeeperRoleKind) -> Option<Pubkey> {
+        self.granted
+            .then(|| pda::keeper_role_pda(vault, &self.authority, role))
+    }
+}
+
+/// Distribution a keeper reports when withdrawing or swapping harvested fees
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DistributionPlan {
+    /// Id the plan is recorded under, `vault.distribution_id + 1`
+    pub distribution_id: u64,
+    pub amount: u64,
+    pub expected_minimum_reward_amount: u64,
+    pub distribution_hash: [u8; 32],
+}
+
+/// Raydium CPMM pool accounts the vault swaps through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaydiumCpmmSwap {
+    pub authority: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    pub output_token_program: Pubkey,
+    pub output_token_mint: Pubkey,
+    pub observation_state: Pubkey,
+}
+
+impl RaydiumCpmmSwap {
+    /// Remaining accounts in the order `SwapAdapter::RaydiumCpmm` expects
+    pub fn remaining_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new_readonly(self.amm_config, false),
+            AccountMeta::new(self.pool_state, false),
+            AccountMeta::new(self.input_vault, false),
+            AccountMeta::new(self.output_vault, false),
+            AccountMeta::new_readonly(self.output_token_program, false),
+            AccountMeta::new_readonly(self.output_token_mint, false),
+            AccountMeta::new(self.observation_state, false),
+        ]
+    }
+}
+
+/// Arguments of `update_config`; `None` leaves a setting unchanged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigUpdate {
+    pub new_owner_wallet: Option<Pubkey>,
+    pub new_harvest_threshold: Option<u64>,
+    pub new_authority: Option<Pubkey>,
+    pub new_keeper_authority: Option<Pubkey>,
+    pub new_burn_share_bps: Option<u16>,
+}
+
+/// Limits on how often and how much the top-up instruction pays the keeper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeeperReserveConfig {
+    pub min_keeper_balance: u64,
+    pub max_top_up_per_period: u64,
+    pub top_up_period: i64,
+}
+
+/// Transfer out of a vault-owned token account by `emergency_withdraw_vault`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmergencyTransfer {
+    pub mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    /// SPL Token or Token-2022, whichever owns `mint`
+    pub token_program: Pubkey,
+    pub amount: u64,
+    pub reason_code: u16,
+}
+
+/// Stray tokens moved out of a vault-owned token account by `recover_foreign_asset`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenRecovery {
+    pub mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    /// SPL Token or Token-2022, whichever owns `mint`
+    pub token_program: Pubkey,
+    pub amount: u64,
+}
+
+/// Instruction builders for the vault of one MIKO mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultInstructions {
+    pub token_mint: Pubkey,
+    pub vault: Pubkey,
+    /// Vault-owned MIKO account harvested fees are withdrawn into
+    pub vault_token_account: Pubkey,
+    /// Pass the keeper reserve so permissionless cranks are paid their bounty
+    pub keeper_reserve: bool,
+}
+
+impl VaultInstructions {
+    pub fn new(token_mint: Pubkey, vault_token_account: Pubkey) -> Self {
+        Self {
+            token_mint,
+            vault: pda::vault_pda(&token_mint),
+            vault_token_account,
+            keeper_reserve: false,
+        }
+    }
+
+    /// Use once `initialize_keeper_reserve` has run
+    pub fn with_keeper_reserve(mut self) -> Self {
+        self.keeper_reserve = true;
+        self
+    }
+
+    fn asset_ledger(&self, mint: &Pubkey) -> Pubkey {
+        pda::asset_ledger_pda(&self.vault, mint)
+    }
+
+    fn keeper_reserve_account(&self) -> Option<Pubkey> {
+        self.keeper_reserve
+            .then(|| pda::keeper_reserve_pda(&self.vault))
+    }
+
+    fn reward_index(&self, reward_mint: &Pubkey) -> Pubkey {
+        pda::reward_index_pda(&self.vault, reward_mint)
+    }
+
+    // Setup
+
+    pub fn initialize(
+        &self,
+        authority: &Pubkey,
+        payer: &Pubkey,
+        owner_wallet: Pubkey,
+        keeper_authority: Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::Initialize {
+                vault: self.vault,
+                authority: *authority,
+                token_mint: self.token_mint,
+                vault_tombstone: pda::vault_tombstone_pda(&self.token_mint),
+                vault_program: absolute_vault::id(),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            vault_instruction::Initialize {
+                owner_wallet,
+                keeper_authority,
+            },
+        )
+    }
+
+    pub fn initialize_pool_registry(&self, payer: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::InitializePoolRegistry {
+                pool_registry: pda::pool_registry_pda(&self.vault),
+                vault: self.vault,
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            vault_instruction::InitializePoolRegistry {},
+        )
+    }
+
+    pub fn set_launch_time(&self) -> Instruction {
+        vault_ix(
+            accounts::SetLaunchTime { vault: self.vault },
+            vault_instruction::SetLaunchTime {},
+        )
+    }
+
+    pub fn update_pool_registry(&self, keeper: &Keeper, pools_to_add: Vec<Pubkey>) -> Instruction {
+        vault_ix(
+            accounts::UpdatePoolRegistry {
+                pool_registry: pda::pool_registry_pda(&self.vault),
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::PoolReporter),
+            },
+            vault_instruction::UpdatePoolRegistry { pools_to_add },
+        )
+    }
+
+    // Fee collection
+
+    /// Harvest withheld fees of up to `MAX_HARVEST_ACCOUNTS` token accounts to the mint
+    pub fn harvest_fees(&self, keeper: &Keeper, token_accounts: &[Pubkey]) -> Instruction {
+        vault_ix_with_remaining(
+            accounts::HarvestFees {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                token_mint: self.token_mint,
+                token_program: spl_token_2022::id(),
+                keeper_reserve: self.keeper_reserve_account(),
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Harvester),
+            },
+            vault_instruction::HarvestFees {
+                accounts: token_accounts.to_vec(),
+            },
+            &withheld_source_accounts(token_accounts),
+        )
+    }
+
+    /// One `harvest_fees` per batch of `MAX_HARVEST_ACCOUNTS` token accounts
+    pub fn harvest_fees_batches(
+        &self,
+        keeper: &Keeper,
+        token_accounts: &[Pubkey],
+    ) -> Vec<Instruction> {
+        token_accounts
+            .chunks(MAX_HARVEST_ACCOUNTS)
+            .map(|batch| self.harvest_fees(keeper, batch))
+            .collect()
+    }
+
+    pub fn withdraw_fees_from_mint(&self, keeper: &Keeper) -> Instruction {
+        vault_ix(
+            accounts::WithdrawFeesFromMint {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+                token_program: spl_token_2022::id(),
+                keeper_reserve: self.keeper_reserve_account(),
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Harvester),
+                asset_ledger: self.asset_ledger(&self.token_mint),
+            },
+            vault_instruction::WithdrawFeesFromMint {},
+        )
+    }
+
+    pub fn burn_harvested_fees(&self) -> Instruction {
+        vault_ix(
+            accounts::BurnHarvestedFees {
+                vault: self.vault,
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+                token_program: spl_token_2022::id(),
+                asset_ledger: self.asset_ledger(&self.token_mint),
+            },
+            vault_instruction::BurnHarvestedFees {},
+        )
+    }
+
+    // Distribution
+
+    /// `hook_accounts` are the extra transfer-hook accounts of the MIKO mint, if any
+    pub fn withdraw_and_report_distribution_plan(
+        &self,
+        keeper: &Keeper,
+        plan: &DistributionPlan,
+        keeper_token_account: &Pubkey,
+        keeper_reward_token_account: &Pubkey,
+        hook_accounts: &[AccountMeta],
+    ) -> Instruction {
+        vault_ix_with_remaining(
+            accounts::WithdrawAndReport {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                distribution_record: pda::distribution_pda(&self.vault, plan.distribution_id),
+                revenue_split: pda::revenue_split_pda(&self.vault),
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+                keeper_token_account: *keeper_token_account,
+                keeper_reward_token_account: *keeper_reward_token_account,
+                dial_state: pda::dial_state_pda(),
+                token_program: spl_token_2022::id(),
+                system_program: system_program::ID,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+                asset_ledger: self.asset_ledger(&self.token_mint),
+            },
+            vault_instruction::WithdrawAndReportDistributionPlan {
+                amount_to_withdraw: plan.amount,
+                expected_minimum_reward_amount: plan.expected_minimum_reward_amount,
+                distribution_hash: plan.distribution_hash,
+            },
+            hook_accounts,
+        )
+    }
+
+    /// `payees` are the revenue split destinations other than the vault, in split order
+    pub fn swap_and_report_distribution_plan(
+        &self,
+        keeper: &Keeper,
+        plan: &DistributionPlan,
+        swap: &RaydiumCpmmSwap,
+        vault_reward_token_account: &Pubkey,
+        payees: &[Pubkey],
+    ) -> Instruction {
+        let mut remaining_accounts = swap.remaining_accounts();
+        remaining_accounts.extend(self.owner_share_accounts(payees, &swap.output_token_mint));
+        vault_ix_with_remaining(
+            accounts::SwapAndReport {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                pool_registry: pda::pool_registry_pda(&self.vault),
+                distribution_record: pda::distribution_pda(&self.vault, plan.distribution_id),
+                revenue_split: pda::revenue_split_pda(&self.vault),
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+                vault_reward_token_account: *vault_reward_token_account,
+                reward_mint: swap.output_token_mint,
+                dial_state: pda::dial_state_pda(),
+                amm_program: RAYDIUM_CPMM_PROGRAM_ID,
+                token_program: spl_token_2022::id(),
+                reward_token_program: swap.output_token_program,
+                system_program: system_program::ID,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+                asset_ledger: self.asset_ledger(&self.token_mint),
+                reward_ledger: self.asset_ledger(&swap.output_token_mint),
+            },
+            vault_instruction::SwapAndReportDistributionPlan {
+                adapter: SwapAdapter::RaydiumCpmm,
+                amount_to_swap: plan.amount,
+                expected_minimum_reward_amount: plan.expected_minimum_reward_amount,
+                distribution_hash: plan.distribution_hash,
+            },
+            &remaining_accounts,
+        )
+    }
+
+    /// `payees` are the destinations other than the vault of the revenue split
+    /// recorded with the distribution, in split order
+    pub fn settle_distribution(
+        &self,
+        keeper: &Keeper,
+        distribution_id: u64,
+        reward_token_account: &Pubkey,
+        reward_mint: &Pubkey,
+        reward_token_program: &Pubkey,
+        payees: &[Pubkey],
+    ) -> Instruction {
+        vault_ix_with_remaining(
+            accounts::SettleDistribution {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                distribution_record: pda::distribution_pda(&self.vault, distribution_id),
+                reward_token_account: *reward_token_account,
+                reward_mint: *reward_mint,
+                reward_token_program: *reward_token_program,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+            },
+            vault_instruction::SettleDistribution {},
+            &self.owner_share_accounts(payees, reward_mint),
+        )
+    }
+
+    pub fn acknowledge_shortfall(&self, authority: &Pubkey, distribution_id: u64) -> Instruction {
+        vault_ix(
+            accounts::AcknowledgeShortfall {
+                vault: self.vault,
+                authority: *authority,
+                distribution_record: pda::distribution_pda(&self.vault, distribution_id),
+            },
+            vault_instruction::AcknowledgeShortfall {},
+        )
+    }
+
+    // Holder rewards
+
+    pub fn initialize_reward_index(
+        &self,
+        keeper: &Keeper,
+        reward_mint: &Pubkey,
+        vault_reward_token_account: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::InitializeRewardIndex {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                reward_index: self.reward_index(reward_mint),
+                reward_mint: *reward_mint,
+                vault_reward_token_account: *vault_reward_token_account,
+                system_program: system_program::ID,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+            },
+            vault_instruction::InitializeRewardIndex {},
+        )
+    }
+
+    pub fn fund_reward_index(
+        &self,
+        keeper: &Keeper,
+        reward_mint: &Pubkey,
+        vault_reward_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        vault_ix(
+            accounts::FundRewardIndex {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                reward_index: self.reward_index(reward_mint),
+                vault_reward_token_account: *vault_reward_token_account,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+            },
+            vault_instruction::FundRewardIndex { amount },
+        )
+    }
+
+    pub fn register_position(&self, holder: &Pubkey, payer: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::RegisterPosition {
+                vault: self.vault,
+                holder: *holder,
+                holder_position: pda::holder_position_pda(&self.vault, holder),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            vault_instruction::RegisterPosition {},
+        )
+    }
+
+    /// The holder must co-sign (`holder_signs`) to move an existing position to another
+    /// token account
+    pub fn checkpoint_position(
+        &self,
+        holder: &Pubkey,
+        reward_mint: &Pubkey,
+        holder_token_account: &Pubkey,
+        payer: &Pubkey,
+        holder_signs: bool,
+    ) -> Instruction {
+        vault_ix(
+            accounts::CheckpointPosition {
+                vault: self.vault,
+                holder_position: pda::holder_position_pda(&self.vault, holder),
+                reward_index: self.reward_index(reward_mint),
+                reward_position: pda::reward_position_pda(&self.vault, holder, reward_mint),
+                holder_token_account: *holder_token_account,
+                holder_checkpoint: pda::holder_checkpoint_pda(holder_token_account),
+                payer: *payer,
+                system_program: system_program::ID,
+                holder: holder_signs.then_some(*holder),
+            },
+            vault_instruction::CheckpointPosition {},
+        )
+    }
+
+    /// `holder_token_account` is the MIKO account the position was checkpointed with
+    pub fn claim_rewards(
+        &self,
+        holder: &Pubkey,
+        reward_mint: &Pubkey,
+        holder_token_account: &Pubkey,
+        vault_reward_token_account: &Pubkey,
+        holder_reward_token_account: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::ClaimRewards {
+                vault: self.vault,
+                holder: *holder,
+                reward_index: self.reward_index(reward_mint),
+                reward_position: pda::reward_position_pda(&self.vault, holder, reward_mint),
+                holder_checkpoint: pda::holder_checkpoint_pda(holder_token_account),
+                reward_mint: *reward_mint,
+                vault_reward_token_account: *vault_reward_token_account,
+                holder_reward_token_account: *holder_reward_token_account,
+                token_program: *token_program,
+                asset_ledger: self.asset_ledger(reward_mint),
+            },
+            vault_instruction::ClaimRewards {},
+        )
+    }
+
+    /// Apply the weight the transfer hook tracked for `holder_token_account` to the
+    /// holder's reward position
+    pub fn report_holder_weight(
+        &self,
+        holder: &Pubkey,
+        reward_mint: &Pubkey,
+        holder_token_account: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::ReportHolderWeight {
+                vault: self.vault,
+                holder_checkpoint: pda::holder_checkpoint_pda(holder_token_account),
+                reward_index: self.reward_index(reward_mint),
+                reward_position: pda::reward_position_pda(&self.vault, holder, reward_mint),
+            },
+            vault_instruction::ReportHolderWeight {},
+        )
+    }
+
+    // Revenue split
+
+    /// Writable owner share accounts of `payees` for `reward_mint`, as swaps and
+    /// settlements take them
+    pub fn owner_share_accounts(
+        &self,
+        payees: &[Pubkey],
+        reward_mint: &Pubkey,
+    ) -> Vec<AccountMeta> {
+        payees
+            .iter()
+            .map(|payee| {
+                AccountMeta::new(pda::owner_share_pda(&self.vault, payee, reward_mint), false)
+            })
+            .collect()
+    }
+
+    pub fn initialize_owner_share(
+        &self,
+        owner: &Pubkey,
+        reward_mint: &Pubkey,
+        token_program: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::InitializeOwnerShare {
+                vault: self.vault,
+                owner: *owner,
+                reward_mint: *reward_mint,
+                owner_share_authority: pda::owner_share_authority_pda(&self.vault),
+                owner_share: pda::owner_share_pda(&self.vault, owner, reward_mint),
+                payer: *payer,
+                token_program: *token_program,
+                system_program: system_program::ID,
+            },
+            vault_instruction::InitializeOwnerShare {},
+        )
+    }
+
+    pub fn claim_owner_share(
+        &self,
+        owner: &Pubkey,
+        reward_mint: &Pubkey,
+        owner_reward_token_account: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::ClaimOwnerShare {
+                vault: self.vault,
+                owner: *owner,
+                owner_share_authority: pda::owner_share_authority_pda(&self.vault),
+                reward_mint: *reward_mint,
+                owner_share: pda::owner_share_pda(&self.vault, owner, reward_mint),
+                owner_reward_token_account: *owner_reward_token_account,
+                token_program: *token_program,
+            },
+            vault_instruction::ClaimOwnerShare {},
+        )
+    }
+
+    pub fn initialize_revenue_split(&self, authority: &Pubkey, payer: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::InitializeRevenueSplit {
+                vault: self.vault,
+                authority: *authority,
+                revenue_split: pda::revenue_split_pda(&self.vault),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            vault_instruction::InitializeRevenueSplit {},
+        )
+    }
+
+    pub fn propose_revenue_split(
+        &self,
+        authority: &Pubkey,
+        payees: Vec<RevenuePayee>,
+    ) -> Instruction {
+        vault_ix(
+            self.propose_revenue_split_accounts(authority),
+            vault_instruction::ProposeRevenueSplit { payees },
+        )
+    }
+
+    pub fn apply_revenue_split(&self) -> Instruction {
+        vault_ix(
+            accounts::ApplyRevenueSplit {
+                vault: self.vault,
+                revenue_split: pda::revenue_split_pda(&self.vault),
+            },
+            vault_instruction::ApplyRevenueSplit {},
+        )
+    }
+
+    pub fn cancel_revenue_split(&self, authority: &Pubkey) -> Instruction {
+        vault_ix(
+            self.propose_revenue_split_accounts(authority),
+            vault_instruction::CancelRevenueSplit {},
+        )
+    }
+
+    fn propose_revenue_split_accounts(&self, authority: &Pubkey) -> accounts::ProposeRevenueSplit {
+        accounts::ProposeRevenueSplit {
+            vault: self.vault,
+            authority: *authority,
+            revenue_split: pda::revenue_split_pda(&self.vault),
+        }
+    }
+
+    // Keepers
+
+    pub fn log_keeper_work(
+        &self,
+        keeper: &Keeper,
+        work_type: KeeperWorkType,
+        amount: u64,
+        details: String,
+    ) -> Instruction {
+        vault_ix(
+            accounts::LogKeeperWork {
+                keeper_work_log: pda::keeper_log_pda(&self.vault),
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                system_program: system_program::ID,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Logger),
+            },
+            vault_instruction::LogKeeperWork {
+                work_type,
+                amount,
+                details,
+            },
+        )
+    }
+
+    pub fn grant_keeper_role(
+        &self,
+        authority: &Pubkey,
+        grantee: &Pubkey,
+        role: KeeperRoleKind,
+    ) -> Instruction {
+        vault_ix(
+            accounts::GrantKeeperRole {
+                vault: self.vault,
+                authority: *authority,
+                grantee: *grantee,
+                keeper_role: pda::keeper_role_pda(&self.vault, grantee, role),
+                system_program: system_program::ID,
+            },
+            vault_instruction::GrantKeeperRole { role },
+        )
+    }
+
+    /// Revoke `role` from `grantee`, returning the grant's rent to the authority
+    pub fn revoke_keeper_role(
+        &self,
+        authority: &Pubkey,
+        grantee: &Pubkey,
+        role: KeeperRoleKind,
+    ) -> Instruction {
+        vault_ix(
+            accounts::RevokeKeeperRole {
+                vault: self.vault,
+                authority: *authority,
+                keeper_role: pda::keeper_role_pda(&self.vault, grantee, role),
+            },
+            vault_instruction::RevokeKeeperRole {},
+        )
+    }
+
+    pub fn keeper_heartbeat(&self, keeper: &Keeper) -> Instruction {
+        vault_ix(
+            accounts::KeeperHeartbeat {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Harvester),
+            },
+            vault_instruction::KeeperHeartbeat {},
+        )
+    }
+
+    pub fn update_liveness_config(
+        &self,
+        authority: &Pubkey,
+        keeper_inactivity_period: i64,
+        crank_bounty_lamports: u64,
+    ) -> Instruction {
+        vault_ix(
+            self.update_config_accounts(authority),
+            vault_instruction::UpdateLivenessConfig {
+                keeper_inactivity_period,
+                crank_bounty_lamports,
+            },
+        )
+    }
+
+    pub fn initialize_keeper_reserve(
+        &self,
+        authority: &Pubkey,
+        config: &KeeperReserveConfig,
+    ) -> Instruction {
+        vault_ix(
+            accounts::InitializeKeeperReserve {
+                vault: self.vault,
+                authority: *authority,
+                keeper_reserve: pda::keeper_reserve_pda(&self.vault),
+                system_program: system_program::ID,
+            },
+            vault_instruction::InitializeKeeperReserve {
+                min_keeper_balance: config.min_keeper_balance,
+                max_top_up_per_period: config.max_top_up_per_period,
+                top_up_period: config.top_up_period,
+            },
+        )
+    }
+
+    pub fn update_keeper_reserve(
+        &self,
+        authority: &Pubkey,
+        config: &KeeperReserveConfig,
+    ) -> Instruction {
+        vault_ix(
+            accounts::UpdateKeeperReserve {
+                vault: self.vault,
+                authority: *authority,
+                keeper_reserve: pda::keeper_reserve_pda(&self.vault),
+            },
+            vault_instruction::UpdateKeeperReserve {
+                min_keeper_balance: config.min_keeper_balance,
+                max_top_up_per_period: config.max_top_up_per_period,
+                top_up_period: config.top_up_period,
+            },
+        )
+    }
+
+    pub fn deposit_to_reserve(&self, depositor: &Pubkey, amount: u64) -> Instruction {
+        vault_ix(
+            accounts::DepositToReserve {
+                vault: self.vault,
+                keeper_reserve: pda::keeper_reserve_pda(&self.vault),
+                depositor: *depositor,
+                system_program: system_program::ID,
+            },
+            vault_instruction::DepositToReserve { amount },
+        )
+    }
+
+    /// Signed by `vault.keeper_authority`; role grants cannot top up
+    pub fn top_up_keeper(&self, keeper_authority: &Pubkey, amount: u64) -> Instruction {
+        vault_ix(
+            accounts::TopUpKeeper {
+                vault: self.vault,
+                keeper_authority: *keeper_authority,
+                keeper_reserve: pda::keeper_reserve_pda(&self.vault),
+                keeper_work_log: pda::keeper_log_pda(&self.vault),
+                system_program: system_program::ID,
+            },
+            vault_instruction::TopUpKeeper { amount },
+        )
+    }
+
+    // Administration
+
+    pub fn manage_exclusions(
+        &self,
+        authority: &Pubkey,
+        action: ExclusionAction,
+        wallet: Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::ManageExclusions {
+                vault: self.vault,
+                authority: *authority,
+            },
+            vault_instruction::ManageExclusions { action, wallet },
+        )
+    }
+
+    pub fn update_config(&self, authority: &Pubkey, update: &ConfigUpdate) -> Instruction {
+        vault_ix(
+            self.update_config_accounts(authority),
+            vault_instruction::UpdateConfig {
+                new_owner_wallet: update.new_owner_wallet,
+                new_harvest_threshold: update.new_harvest_threshold,
+                new_authority: update.new_authority,
+                new_keeper_authority: update.new_keeper_authority,
+                new_burn_share_bps: update.new_burn_share_bps,
+            },
+        )
+    }
+
+    pub fn update_withdrawal_guards(
+        &self,
+        authority: &Pubkey,
+        min_distribution_interval: i64,
+        withdrawal_window: i64,
+        max_withdrawal_per_window: u64,
+        max_threshold_multiple: u64,
+    ) -> Instruction {
+        vault_ix(
+            self.update_config_accounts(authority),
+            vault_instruction::UpdateWithdrawalGuards {
+                min_distribution_interval,
+                withdrawal_window,
+                max_withdrawal_per_window,
+                max_threshold_multiple,
+            },
+        )
+    }
+
+    fn update_config_accounts(&self, authority: &Pubkey) -> accounts::UpdateConfig {
+        accounts::UpdateConfig {
+            vault: self.vault,
+            authority: *authority,
+        }
+    }
+
+    /// `current_guardian` must co-sign once a guardian is set
+    pub fn update_emergency_policy(
+        &self,
+        authority: &Pubkey,
+        current_guardian: Option<Pubkey>,
+        guardian: Pubkey,
+        destinations: Vec<Pubkey>,
+        max_bps: u16,
+        cooldown: i64,
+    ) -> Instruction {
+        vault_ix(
+            accounts::UpdateEmergencyPolicy {
+                vault: self.vault,
+                authority: *authority,
+                guardian: current_guardian,
+            },
+            vault_instruction::UpdateEmergencyPolicy {
+                guardian,
+                destinations,
+                max_bps,
+                cooldown,
+            },
+        )
+    }
+
+    // Accounting
+
+    pub fn initialize_asset_ledger(
+        &self,
+        keeper: &Keeper,
+        mint: &Pubkey,
+        vault_token_account: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::InitializeAssetLedger {
+                vault: self.vault,
+                keeper_authority: keeper.authority,
+                mint: *mint,
+                vault_token_account: *vault_token_account,
+                asset_ledger: pda::asset_ledger_pda(&self.vault, mint),
+                system_program: system_program::ID,
+                keeper_role: keeper.role(&self.vault, KeeperRoleKind::Distributor),
+            },
+            vault_instruction::InitializeAssetLedger {},
+        )
+    }
+
+    pub fn reconcile_asset_ledger(
+        &self,
+        mint: &Pubkey,
+        vault_token_account: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::ReconcileAssetLedger {
+                vault: self.vault,
+                asset_ledger: pda::asset_ledger_pda(&self.vault, mint),
+                vault_token_account: *vault_token_account,
+            },
+            vault_instruction::ReconcileAssetLedger {},
+        )
+    }
+
+    pub fn reconcile(&self) -> Instruction {
+        vault_ix(
+            accounts::Reconcile {
+                vault: self.vault,
+                vault_token_account: self.vault_token_account,
+            },
+            vault_instruction::Reconcile {},
+        )
+    }
+
+    // Views; results come back as Borsh return data
+
+    pub fn is_excluded(&self, wallet: Pubkey) -> Instruction {
+        vault_ix(
+            accounts::ViewVault { vault: self.vault },
+            vault_instruction::IsExcluded { wallet },
+        )
+    }
+
+    pub fn get_vault_summary(&self) -> Instruction {
+        vault_ix(
+            accounts::GetVaultSummary {
+                vault: self.vault,
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+            },
+            vault_instruction::GetVaultSummary {},
+        )
+    }
+
+    // Migration
+
+    pub fn migrate_vault(&self, authority: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::MigrateVault {
+                vault: self.vault,
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            vault_instruction::MigrateVault {},
+        )
+    }
+
+    pub fn migrate_pool_registry(&self, authority: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::MigratePoolRegistry {
+                vault: self.vault,
+                pool_registry: pda::pool_registry_pda(&self.vault),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            vault_instruction::MigratePoolRegistry {},
+        )
+    }
+
+    pub fn migrate_keeper_work_log(&self, authority: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::MigrateKeeperWorkLog {
+                vault: self.vault,
+                keeper_work_log: pda::keeper_log_pda(&self.vault),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            vault_instruction::MigrateKeeperWorkLog {},
+        )
+    }
+
+    pub fn migrate_asset_ledger(&self, authority: &Pubkey, mint: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::MigrateAssetLedger {
+                vault: self.vault,
+                mint: *mint,
+                asset_ledger: pda::asset_ledger_pda(&self.vault, mint),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            vault_instruction::MigrateAssetLedger {},
+        )
+    }
+
+    // Closing
+
+    pub fn close_keeper_work_log(&self, authority: &Pubkey, rent_receiver: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::CloseKeeperWorkLog {
+                vault: self.vault,
+                authority: *authority,
+                keeper_work_log: pda::keeper_log_pda(&self.vault),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::CloseKeeperWorkLog {},
+        )
+    }
+
+    pub fn close_pool_registry(&self, authority: &Pubkey, rent_receiver: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::ClosePoolRegistry {
+                vault: self.vault,
+                authority: *authority,
+                pool_registry: pda::pool_registry_pda(&self.vault),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::ClosePoolRegistry {},
+        )
+    }
+
+    pub fn close_revenue_split(&self, authority: &Pubkey, rent_receiver: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::CloseRevenueSplit {
+                vault: self.vault,
+                authority: *authority,
+                revenue_split: pda::revenue_split_pda(&self.vault),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::CloseRevenueSplit {},
+        )
+    }
+
+    pub fn close_distribution_record(
+        &self,
+        authority: &Pubkey,
+        distribution_id: u64,
+        rent_receiver: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::CloseDistributionRecord {
+                vault: self.vault,
+                authority: *authority,
+                distribution_record: pda::distribution_pda(&self.vault, distribution_id),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::CloseDistributionRecord {},
+        )
+    }
+
+    /// Rounding dust left in the index is swept to `dust_receiver`
+    pub fn close_reward_index(
+        &self,
+        authority: &Pubkey,
+        reward_mint: &Pubkey,
+        vault_reward_token_account: &Pubkey,
+        dust_receiver: &Pubkey,
+        token_program: &Pubkey,
+        rent_receiver: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::CloseRewardIndex {
+                vault: self.vault,
+                authority: *authority,
+                reward_index: self.reward_index(reward_mint),
+                rent_receiver: *rent_receiver,
+                reward_mint: *reward_mint,
+                vault_reward_token_account: *vault_reward_token_account,
+                dust_receiver: *dust_receiver,
+                token_program: *token_program,
+                asset_ledger: self.asset_ledger(reward_mint),
+            },
+            vault_instruction::CloseRewardIndex {},
+        )
+    }
+
+    pub fn close_asset_ledger(
+        &self,
+        authority: &Pubkey,
+        mint: &Pubkey,
+        rent_receiver: &Pubkey,
+    ) -> Instruction {
+        vault_ix(
+            accounts::CloseAssetLedger {
+                vault: self.vault,
+                authority: *authority,
+                asset_ledger: pda::asset_ledger_pda(&self.vault, mint),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::CloseAssetLedger {},
+        )
+    }
+
+    pub fn close_keeper_reserve(&self, authority: &Pubkey, rent_receiver: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::CloseKeeperReserve {
+                vault: self.vault,
+                authority: *authority,
+                keeper_reserve: pda::keeper_reserve_pda(&self.vault),
+                rent_receiver: *rent_receiver,
+            },
+            vault_instruction::CloseKeeperReserve {},
+        )
+    }
+
+    pub fn close_vault(&self, authority: &Pubkey, rent_receiver: &Pubkey) -> Instruction {
+        vault_ix(
+            accounts::CloseVault {
+                vault: self.vault,
+                authority: *authority,
+                vault_token_account: self.vault_token_account,
+                vault_tombstone: pda::vault_tombstone_pda(&self.token_mint),
+                rent_receiver: *rent_receiver,
+                system_program: system_program::ID,
+            },
+            vault_instruction::CloseVault {},
+        )
+    }
+
+    // Recovery
+
+    /// Recover lamports above the vault's rent floor
+    pub fn recover_lamports(
+        &self,
+        authority: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        vault_ix(
+            accounts::RecoverForeignAsset {
+                vault: self.vault,
+                authority: *authority,
+                destination: *destination,
+                mint: None,
+                vault_foreign_token_account: None,
+                reward_index: None,
+                token_program: None,
+                dial_state: None,
+                distribution_record: None,
+            },
+            vault_instruction::RecoverForeignAsset { amount },
+        )
+    }
+
+    /// Recover tokens of a non-MIKO, non-reward mint. `distribution_id` is the vault's
+    /// latest one, whose reward mint is refused.
+    pub fn recover_tokens(
+        &self,
+        authority: &Pubkey,
+        recovery: &TokenRecovery,
+        distribution_id: u64,
+    ) -> Instruction {
+        vault_ix(
+            accounts::RecoverForeignAsset {
+                vault: self.vault,
+                authority: *authority,
+                destination: recovery.destination_token_account,
+                mint: Some(recovery.mint),
+                vault_foreign_token_account: Some(recovery.vault_token_account),
+                reward_index: Some(self.reward_index(&recovery.mint)),
+                token_program: Some(recovery.token_program),
+                dial_state: Some(pda::dial_state_pda()),
+                distribution_record: Some(pda::distribution_pda(&self.vault, distribution_id)),
+            },
+            vault_instruction::RecoverForeignAsset {
+                amount: recovery.amount,
+            },
+        )
+    }
+
+    /// `guardian` co-signs to lift the allowlist, cap and cooldown; `hook_accounts` are the
+    /// extra transfer-hook accounts of the mint, if any
+    pub fn emergency_withdraw_vault(
+        &self,
+        authority: &Pubkey,
+        guardian: Option<Pubkey>,
+        transfer: &EmergencyTransfer,
+        hook_accounts: &[AccountMeta],
+    ) -> Instruction {
+        vault_ix_with_remaining(
+            accounts::EmergencyWithdraw {
+                vault: self.vault,
+                authority: *authority,
+                vault_token_account: transfer.vault_token_account,
+                destination_token_account: transfer.destination_token_account,
+                token_mint: transfer.mint,
+                token_program: transfer.token_program,
+                guardian,
+                asset_ledger: self.asset_ledger(&transfer.mint),
+            },
+            vault_instruction::EmergencyWithdrawVault {
+                amount: transfer.amount,
+                reason_code: transfer.reason_code,
+            },
+            hook_accounts,
+        )
+    }
+
+    /// Withdraw fees withheld in up to `MAX_HARVEST_ACCOUNTS` token accounts straight to
+    /// `destination_token_account`, capped against the vault MIKO holdings
+    pub fn emergency_withdraw_withheld(
+        &self,
+        authority: &Pubkey,
+        guardian: Option<Pubkey>,
+        destination_token_account: &Pubkey,
+        token_accounts: &[Pubkey],
+        reason_code: u16,
+    ) -> Instruction {
+        vault_ix_with_remaining(
+            accounts::EmergencyWithdrawWithheld {
+                vault: self.vault,
+                authority: *authority,
+                token_mint: self.token_mint,
+                vault_token_account: self.vault_token_account,
+                destination_token_account: *destination_token_account,
+                token_program: spl_token_2022::id(),
+                guardian,
+                asset_ledger: self.asset_ledger(&self.token_mint),
+            },
+            vault_instruction::EmergencyWithdrawWithheld {
+                accounts: token_accounts.to_vec(),
+                reason_code,
+            },
+            &withheld_source_accounts(token_accounts),
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use absolute_vault::{KeeperRole, KeeperRoleKind, RAYDIUM_CPMM_PROGRAM_ID};
+use anchor_lang::{prelude::Pubkey, AccountSerialize};
+use miko_client::{
+    pda,
+    state::VaultAccount,
+    vault::{DistributionPlan, RaydiumCpmmSwap, MAX_HARVEST_ACCOUNTS},
+    Keeper, VaultInstructions,
+};
+
+fn vault() -> VaultInstructions {
+    VaultInstructions::new(Pubkey::new_unique(), Pubkey::new_unique())
+}
+
+#[test]
+fn harvest_batches_carry_their_accounts_as_writable_remaining_accounts() {
+    let vault = vault();
+    let keeper = Keeper::primary(Pubkey::new_unique());
+    let token_accounts: Vec<Pubkey> = (0..45).map(|_| Pubkey::new_unique()).collect();
+
+    let batches = vault.harvest_fees_batches(&keeper, &token_accounts);
+    assert_eq!(batches.len(), 3);
+
+    let single = vault.harvest_fees(&keeper, &token_accounts[..1]);
+    let fixed_accounts = single.accounts.len() - 1;
+
+    for (ix, batch) in batches
+        .iter()
+        .zip(token_accounts.chunks(MAX_HARVEST_ACCOUNTS))
+    {
+        let remaining = &ix.accounts[fixed_accounts..];
+        assert_eq!(remaining.len(), batch.len());
+        for (meta, account) in remaining.iter().zip(batch) {
+            assert_eq!(meta.pubkey, *account);
+            assert!(meta.is_writable);
+            assert!(!meta.is_signer);
+        }
+    }
+}
+
+#[test]
+fn granted_keeper_passes_the_role_the_instruction_requires() {
+    let vault = vault();
+    let authority = Pubkey::new_unique();
+
+    let primary = vault.keeper_heartbeat(&Keeper::primary(authority));
+    // Anchor stands the program id in for an omitted optional account
+    assert_eq!(
+        primary.accounts.last().unwrap().pubkey,
+        absolute_vault::id()
+    );
+
+    let harvester = pda::keeper_role_pda(&vault.vault, &authority, KeeperRoleKind::Harvester);
+    let granted = vault.keeper_heartbeat(&Keeper::granted(authority));
+    assert_eq!(granted.accounts.last().unwrap().pubkey, harvester);
+
+    let distributor = pda::keeper_role_pda(&vault.vault, &authority, KeeperRoleKind::Distributor);
+    let owner = Pubkey::new_unique();
+    let plan = DistributionPlan {
+        distribution_id: 1,
+        amount: 1_000,
+        expected_minimum_reward_amount: 900,
+        distribution_hash: [7; 32],
+    };
+    let swap = RaydiumCpmmSwap {
+        authority: Pubkey::new_unique(),
+        amm_config: Pubkey::new_unique(),
+        pool_state: Pubkey::new_unique(),
+        input_vault: Pubkey::new_unique(),
+        output_vault: Pubkey::new_unique(),
+        output_token_program: Pubkey::new_unique(),
+        output_token_mint: Pubkey::new_unique(),
+        observation_state: Pubkey::new_unique(),
+    };
+    let ix = vault.swap_and_report_distribution_plan(
+        &Keeper::granted(authority),
+        &plan,
+        &swap,
+        &Pubkey::new_unique(),
+        &[owner],
+    );
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert!(keys.contains(&distributor));
+    assert!(keys.contains(&RAYDIUM_CPMM_PROGRAM_ID));
+    assert!(keys.contains(&pda::distribution_pda(&vault.vault, 1)));
+    assert!(keys.ends_with(&[
+        swap.observation_state,
+        pda::owner_share_pda(&vault.vault, &owner, &swap.output_token_mint),
+    ]));
+}
+
+#[test]
+fn vault_accounts_decode_by_discriminator() {
+    let role = KeeperRole {
+        vault: Pubkey::new_unique(),
+        key: Pubkey::new_unique(),
+        role: KeeperRoleKind::Logger,
+        granted_at: 1_700_000_000,
+    };
+    let mut data = Vec::new();
+    role.try_serialize(&mut data).unwrap();
+
+    match VaultAccount::decode(&data).unwrap() {
+        VaultAccount::KeeperRole(decoded) => {
+            assert_eq!(decoded.key, role.key);
+            assert_eq!(decoded.role, KeeperRoleKind::Logger);
+        }
+        _ => panic!("keeper role decoded as another account type"),
+    }
+
+    assert!(VaultAccount::decode(&[0; 64]).is_err());
+}
@@ -0,0 +1,551 @@
+//! Compute-unit budget regression tests
+//!
+//! Runs the SBF builds of both programs, so costs match what validators charge. Each
+//! case sends one instruction at a representative size and compares its compute units
+//! against `tests/compute_units.baseline`. A case fails once it costs more than
+//! `TOLERANCE_BPS` over its baseline, or if it no longer fits the default per
+//! instruction limit the keeper sends transactions with.
+//!
+//! Build both programs with `anchor build`, then run
+//! `cargo test --test compute_units -- --ignored`. Set `MIKO_RECORD_COMPUTE_UNITS=1`
+//! to rewrite the baseline from the current builds instead of checking it.
+
+use absolute_vault::{ExclusionAction, VaultState, MAX_EXCLUSIONS};
+use anchor_lang::prelude::Pubkey;
+use miko_client::{
+    dial, pda,
+    state::decode,
+    vault::{ConfigUpdate, DistributionPlan},
+    Keeper, VaultInstructions,
+};
+use smart_dial::INITIAL_UPDATE_DELAY;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    clock::Clock,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccountState, AccountState, Mint as MintState},
+};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Allowed growth over the baseline, in basis points
+const TOLERANCE_BPS: u64 = 500;
+/// Default limit of a single instruction without a compute budget request
+const DEFAULT_INSTRUCTION_LIMIT: u64 = 200_000;
+const RECORD_VAR: &str = "MIKO_RECORD_COMPUTE_UNITS";
+
+const LAUNCH: i64 = 1_700_000_000;
+const FEE_BPS: u16 = 600;
+/// Largest batch `harvest_fees` accepts
+const HARVEST_BATCH: usize = 20;
+const WITHHELD_PER_ACCOUNT: u64 = 1_000_000;
+/// Dial updates that fit in one setup transaction
+const UPDATES_PER_TX: usize = 13;
+/// Exclusion updates that fit in one setup transaction
+const EXCLUSIONS_PER_TX: usize = 19;
+/// Entries the dial keeps in its update history
+const DIAL_HISTORY: i64 = 52;
+
+fn manifest_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn baseline_path() -> PathBuf {
+    manifest_path("tests/compute_units.baseline")
+}
+
+/// Program account running the SBF build at `relative`
+fn program_account(relative: &str) -> Account {
+    let path = manifest_path(relative);
+    let data = fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "{}: {err}; run `anchor build` in the program first",
+            path.display()
+        )
+    });
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader::id(),
+        executable: true,
+        rent_epoch: 0,
+    }
+}
+
+fn program_test() -> ProgramTest {
+    let mut pt = ProgramTest::default();
+    pt.add_account(
+        absolute_vault::id(),
+        program_account("../absolute-vault/target/deploy/absolute_vault.so"),
+    );
+    pt.add_account(
+        smart_dial::id(),
+        program_account("../smart-dial/target/deploy/smart_dial.so"),
+    );
+    pt
+}
+
+/// `name units` lines; blank lines and `#` comments are skipped
+fn read_baseline() -> BTreeMap<String, u64> {
+    let Ok(contents) = fs::read_to_string(baseline_path()) else {
+        return BTreeMap::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, units) = line
+                .rsplit_once(' ')
+                .unwrap_or_else(|| panic!("malformed baseline line: {line}"));
+            (name.trim().to_string(), units.parse().unwrap())
+        })
+        .collect()
+}
+
+fn write_baseline(measured: &BTreeMap<String, u64>) {
+    let mut contents = String::from(
+        "# Compute units per instruction, measured on the SBF builds\n\
+         # Regenerate with MIKO_RECORD_COMPUTE_UNITS=1 cargo test --test compute_units -- --ignored\n",
+    );
+    for (name, units) in measured {
+        contents.push_str(&format!("{name} {units}\n"));
+    }
+    fs::write(baseline_path(), contents).unwrap();
+}
+
+/// Compute units of every case, by case name
+#[derive(Default)]
+struct Measurements(BTreeMap<String, u64>);
+
+impl Measurements {
+    /// Send `ix` alone and record what it cost; the instruction must succeed
+    async fn measure(
+        &mut self,
+        ctx: &mut ProgramTestContext,
+        name: &str,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) {
+        let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&ctx.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&ctx.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let outcome = ctx
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await
+            .unwrap();
+        let metadata = outcome.metadata.expect("transaction metadata");
+        if let Err(err) = outcome.result {
+            panic!("{name} failed: {err}\n{}", metadata.log_messages.join("\n"));
+        }
+        self.0
+            .insert(name.to_string(), metadata.compute_units_consumed);
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF builds of both programs; run `anchor build` first"]
+async fn compute_units_stay_within_baseline() {
+    let mut measured = Measurements::default();
+    vault_cases(&mut measured).await;
+    dial_cases(&mut measured).await;
+    let measured = measured.0;
+
+    if std::env::var_os(RECORD_VAR).is_some() {
+        write_baseline(&measured);
+        return;
+    }
+
+    let baseline = read_baseline();
+    let mut failures = Vec::new();
+    for (name, &units) in &measured {
+        if units > DEFAULT_INSTRUCTION_LIMIT {
+            failures.push(format!(
+                "{name}: {units} CU exceeds the {DEFAULT_INSTRUCTION_LIMIT} CU default limit"
+            ));
+        }
+        match baseline.get(name) {
+            None => failures.push(format!("{name}: {units} CU has no baseline entry")),
+            Some(&expected) => {
+                let allowed = expected + expected * TOLERANCE_BPS / 10_000;
+                if units > allowed {
+                    failures.push(format!(
+                        "{name}: {units} CU regressed past {expected} CU (allowed {allowed})"
+                    ));
+                }
+            }
+        }
+    }
+    for name in baseline.keys().filter(|name| !measured.contains_key(*name)) {
+        failures.push(format!("{name}: baseline entry has no case"));
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\nRe-record with {RECORD_VAR}=1 if the change is intended",
+        failures.join("\n")
+    );
+}
+
+async fn vault_cases(measured: &mut Measurements) {
+    let mut pt = program_test();
+    let authority = Keypair::new();
+    let keeper = Keypair::new();
+    let owner = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let vault_pda = pda::vault_pda(&token_mint);
+    let vault_token_account = Pubkey::new_unique();
+    let vault = VaultInstructions::new(token_mint, vault_token_account);
+
+    for signer in [&authority, &keeper] {
+        pt.add_account(
+            signer.pubkey(),
+            Account {
+                lamports: 10_000_000_000,
+                ..Account::default()
+            },
+        );
+    }
+    pt.add_account(token_mint, fee_mint_account(&vault_pda));
+    pt.add_account(
+        vault_token_account,
+        fee_token_account(&token_mint, &vault_pda, 0),
+    );
+    let keeper_token_account = Pubkey::new_unique();
+    pt.add_account(
+        keeper_token_account,
+        fee_token_account(&token_mint, &keeper.pubkey(), 0),
+    );
+    let holder_accounts: Vec<Pubkey> = (0..HARVEST_BATCH).map(|_| Pubkey::new_unique()).collect();
+    for account in &holder_accounts {
+        pt.add_account(
+            *account,
+            fee_token_account(&token_mint, &Pubkey::new_unique(), WITHHELD_PER_ACCOUNT),
+        );
+    }
+    let reward_mint = Pubkey::new_unique();
+    let keeper_reward_token_account = Pubkey::new_unique();
+    pt.add_account(reward_mint, reward_mint_account());
+    pt.add_account(
+        keeper_reward_token_account,
+        reward_token_account(&reward_mint, &keeper.pubkey()),
+    );
+
+    let mut ctx = pt.start_with_context().await;
+    set_time(&mut ctx, LAUNCH);
+    let payer = ctx.payer.pubkey();
+    let keeper_role = Keeper::primary(keeper.pubkey());
+
+    measured
+        .measure(
+            &mut ctx,
+            "vault::initialize",
+            vault.initialize(&authority.pubkey(), &payer, owner, keeper.pubkey()),
+            &[&authority],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::initialize_pool_registry",
+            vault.initialize_pool_registry(&payer),
+            &[],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::initialize_revenue_split",
+            vault.initialize_revenue_split(&authority.pubkey(), &payer),
+            &[&authority],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::update_config",
+            vault.update_config(
+                &authority.pubkey(),
+                &ConfigUpdate {
+                    new_harvest_threshold: Some(0),
+                    ..ConfigUpdate::default()
+                },
+            ),
+            &[&authority],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::set_launch_time",
+            vault.set_launch_time(),
+            &[],
+        )
+        .await;
+
+    measured
+        .measure(
+            &mut ctx,
+            "vault::harvest_fees/20 accounts",
+            vault.harvest_fees(&keeper_role, &holder_accounts),
+            &[&keeper],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::withdraw_fees_from_mint",
+            vault.withdraw_fees_from_mint(&keeper_role),
+            &[&keeper],
+        )
+        .await;
+    let plan = DistributionPlan {
+        distribution_id: 1,
+        amount: WITHHELD_PER_ACCOUNT * HARVEST_BATCH as u64,
+        expected_minimum_reward_amount: 0,
+        distribution_hash: [0; 32],
+    };
+    measured
+        .measure(
+            &mut ctx,
+            "vault::withdraw_and_report_distribution_plan",
+            vault.withdraw_and_report_distribution_plan(
+                &keeper_role,
+                &plan,
+                &keeper_token_account,
+                &keeper_reward_token_account,
+                &[],
+            ),
+            &[&keeper],
+        )
+        .await;
+
+    // Fill the exclusion list to one short of capacity, then time the scans over a
+    // full list
+    let account = ctx
+        .banks_client
+        .get_account(vault.vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let initial = decode::<VaultState>(&account.data)
+        .unwrap()
+        .reward_exclusions
+        .len();
+    let wallets: Vec<Pubkey> = (initial..MAX_EXCLUSIONS - 1)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for chunk in wallets.chunks(EXCLUSIONS_PER_TX) {
+        let instructions: Vec<Instruction> = chunk
+            .iter()
+            .map(|wallet| {
+                vault.manage_exclusions(&authority.pubkey(), ExclusionAction::Add, *wallet)
+            })
+            .collect();
+        send(&mut ctx, &instructions, &[&authority]).await;
+    }
+    let last = Pubkey::new_unique();
+    measured
+        .measure(
+            &mut ctx,
+            "vault::manage_exclusions/add 100th key",
+            vault.manage_exclusions(&authority.pubkey(), ExclusionAction::Add, last),
+            &[&authority],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "vault::manage_exclusions/remove from 100 keys",
+            vault.manage_exclusions(&authority.pubkey(), ExclusionAction::Remove, last),
+            &[&authority],
+        )
+        .await;
+}
+
+async fn dial_cases(measured: &mut Measurements) {
+    let mut pt = program_test();
+    let authority = Keypair::new();
+    pt.add_account(
+        authority.pubkey(),
+        Account {
+            lamports: 10_000_000_000,
+            ..Account::default()
+        },
+    );
+    let mut ctx = pt.start_with_context().await;
+    let first_cycle = LAUNCH + INITIAL_UPDATE_DELAY;
+    set_time(&mut ctx, first_cycle + DIAL_HISTORY + 1);
+    let key = authority.pubkey();
+    let payer = ctx.payer.pubkey();
+
+    measured
+        .measure(
+            &mut ctx,
+            "dial::initialize",
+            dial::initialize(&key, &payer, LAUNCH),
+            &[&authority],
+        )
+        .await;
+    let update = |cycle: i64| {
+        let cycle_start = first_cycle + cycle;
+        dial::update_reward_token(&key, Pubkey::new_unique(), cycle_start, cycle_start + 1)
+    };
+    measured
+        .measure(
+            &mut ctx,
+            "dial::update_reward_token/empty history",
+            update(0),
+            &[&authority],
+        )
+        .await;
+
+    // Cycle starts only need to increase, so the history fills without moving the clock
+    let fill: Vec<Instruction> = (1..DIAL_HISTORY).map(update).collect();
+    for chunk in fill.chunks(UPDATES_PER_TX) {
+        send(&mut ctx, chunk, &[&authority]).await;
+    }
+    measured
+        .measure(
+            &mut ctx,
+            "dial::update_reward_token/52 entry history",
+            update(DIAL_HISTORY),
+            &[&authority],
+        )
+        .await;
+    measured
+        .measure(
+            &mut ctx,
+            "dial::update_authority",
+            dial::update_authority(&key, key),
+            &[&authority],
+        )
+        .await;
+}
+
+// Fixtures
+
+async fn send(ctx: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+fn set_time(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    ctx.set_sysvar(&Clock {
+        unix_timestamp,
+        ..Clock::default()
+    });
+}
+
+fn token_program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// MIKO mint charging `FEE_BPS`, withheld fees withdrawable by `withdraw_authority`
+fn fee_mint_account(withdraw_authority: &Pubkey) -> Account {
+    let len =
+        ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0; len];
+    let mut state = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: FEE_BPS.into(),
+    };
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.withdraw_withheld_authority = Some(*withdraw_authority).try_into().unwrap();
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = MintState {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: u64::MAX / 2,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    token_program_account(data)
+}
+
+/// MIKO account holding `withheld` transfer fees and no balance
+fn fee_token_account(mint: &Pubkey, owner: &Pubkey, withheld: u64) -> Account {
+    let len = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let mut data = vec![0; len];
+    let mut state =
+        StateWithExtensionsMut::<TokenAccountState>::unpack_uninitialized(&mut data).unwrap();
+    state
+        .init_extension::<TransferFeeAmount>(true)
+        .unwrap()
+        .withheld_amount = withheld.into();
+    state.base = TokenAccountState {
+        mint: *mint,
+        owner: *owner,
+        state: AccountState::Initialized,
+        ..TokenAccountState::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    token_program_account(data)
+}
+
+fn reward_mint_account() -> Account {
+    let mut data = vec![0; MintState::LEN];
+    MintState {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    token_program_account(data)
+}
+
+fn reward_token_account(mint: &Pubkey, owner: &Pubkey) -> Account {
+    let mut data = vec![0; TokenAccountState::LEN];
+    TokenAccountState {
+        mint: *mint,
+        owner: *owner,
+        state: AccountState::Initialized,
+        ..TokenAccountState::default()
+    }
+    .pack_into_slice(&mut data);
+    token_program_account(data)
+}
@@ -0,0 +1,35 @@
+use absolute_vault::{DistributionPlanReport, DistributionSettled};
+use anchor_lang::{prelude::Pubkey, Event};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use miko_client::state::decode_events;
+
+#[test]
+fn plan_reports_are_picked_out_of_transaction_logs() {
+    let report = DistributionPlanReport {
+        timestamp: 1_700_000_000,
+        distribution_id: 3,
+        withdrawn_miko_amount: 5_000,
+        expected_minimum_reward_amount: 4_000,
+        distribution_hash: [9; 32],
+        keeper: Pubkey::new_unique(),
+    };
+    let settled = DistributionSettled {
+        timestamp: 1_700_000_100,
+        distribution_id: 2,
+        expected_minimum_reward_amount: 10,
+        reward_amount_received: 10,
+        shortfall: false,
+    };
+    let logs = vec![
+        format!("Program {} invoke [1]", absolute_vault::id()),
+        format!("Program data: {}", STANDARD.encode(settled.data())),
+        format!("Program data: {}", STANDARD.encode(report.data())),
+        format!("Program {} success", absolute_vault::id()),
+    ];
+
+    let reports: Vec<DistributionPlanReport> = decode_events(&logs);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].distribution_id, 3);
+    assert_eq!(reports[0].keeper, report.keeper);
+    assert_eq!(reports[0].distribution_hash, [9; 32]);
+}
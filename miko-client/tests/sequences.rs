@@ -0,0 +1,760 @@
+//! Stateful fuzzing of vault and dial instruction sequences
+//!
+//! Each case runs a random sequence of both programs' instructions, signed by random
+//! actors, against one in-process bank holding a real Token-2022 MIKO mint with a 6%
+//! transfer fee. Invariants are checked after every step, whether the step succeeded
+//! or not.
+
+use absolute_vault::{ExclusionAction, VaultState};
+use anchor_lang::prelude::Pubkey;
+use miko_client::{
+    dial, pda,
+    state::{decode, decode_zero_copy},
+    vault::{ConfigUpdate, DistributionPlan},
+    Keeper, VaultInstructions,
+};
+use proptest::prelude::*;
+use smart_dial::{DialState, INITIAL_UPDATE_DELAY, MAX_UPDATE_HISTORY};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{
+            instruction as transfer_fee_instruction, TransferFeeAmount, TransferFeeConfig,
+        },
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    instruction as token_instruction,
+    state::{Account as TokenAccountState, AccountState, Mint as MintState},
+};
+
+const DECIMALS: u8 = 9;
+const FEE_BPS: u16 = 600;
+const MINTED_PER_ACTOR: u64 = 1_000_000_000_000;
+const LAUNCH: i64 = 1_700_000_000;
+const DAY: i64 = 24 * 60 * 60;
+
+/// Authority, keeper, owner and an outsider, in that order at launch
+const ACTORS: usize = 4;
+const AUTHORITY: usize = 0;
+const KEEPER: usize = 1;
+const OWNER: usize = 2;
+
+fn vault_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Anchor's entrypoint wants matching lifetimes on the slice and the infos
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    absolute_vault::entry(program_id, accounts, data)
+}
+
+fn dial_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    smart_dial::entry(program_id, accounts, data)
+}
+
+/// Token-2022 rounds the fee up
+fn fee_of(amount: u64) -> u64 {
+    (amount as u128 * FEE_BPS as u128).div_ceil(10_000) as u64
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Transfer {
+        from: usize,
+        to: usize,
+        amount: u64,
+    },
+    Harvest {
+        signer: usize,
+    },
+    WithdrawFees {
+        signer: usize,
+    },
+    Burn,
+    WithdrawAndReport {
+        signer: usize,
+        full_amount: bool,
+    },
+    Settle {
+        signer: usize,
+    },
+    ManageExclusion {
+        signer: usize,
+        remove: bool,
+        wallet: usize,
+    },
+    UpdateConfig {
+        signer: usize,
+        new_authority: Option<usize>,
+        new_keeper: Option<usize>,
+        burn_share_bps: Option<u16>,
+    },
+    SetLaunchTime,
+    DialUpdate {
+        signer: usize,
+        cycle_day: i64,
+        next_cycle_days: i64,
+    },
+    DialSync {
+        signer: usize,
+        launch_offset: i64,
+    },
+    DialUpdateAuthority {
+        signer: usize,
+        new_authority: usize,
+    },
+    Warp {
+        seconds: i64,
+    },
+}
+
+fn actor() -> impl Strategy<Value = usize> {
+    0..ACTORS
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (actor(), actor(), 0..2 * MINTED_PER_ACTOR)
+            .prop_map(|(from, to, amount)| Op::Transfer { from, to, amount }),
+        2 => actor().prop_map(|signer| Op::Harvest { signer }),
+        2 => actor().prop_map(|signer| Op::WithdrawFees { signer }),
+        1 => Just(Op::Burn),
+        2 => (actor(), any::<bool>())
+            .prop_map(|(signer, full_amount)| Op::WithdrawAndReport { signer, full_amount }),
+        2 => actor().prop_map(|signer| Op::Settle { signer }),
+        2 => (actor(), any::<bool>(), 0..ACTORS + 3)
+            .prop_map(|(signer, remove, wallet)| Op::ManageExclusion { signer, remove, wallet }),
+        2 => (
+            actor(),
+            proptest::option::of(actor()),
+            proptest::option::of(actor()),
+            proptest::option::of(0u16..=10_000),
+        )
+            .prop_map(|(signer, new_authority, new_keeper, burn_share_bps)| Op::UpdateConfig {
+                signer,
+                new_authority,
+                new_keeper,
+                burn_share_bps,
+            }),
+        1 => Just(Op::SetLaunchTime),
+        2 => (actor(), -1i64..14, -1i64..8).prop_map(|(signer, cycle_day, next_cycle_days)| {
+            Op::DialUpdate {
+                signer,
+                cycle_day,
+                next_cycle_days,
+            }
+        }),
+        1 => (actor(), -DAY..DAY)
+            .prop_map(|(signer, launch_offset)| Op::DialSync { signer, launch_offset }),
+        1 => (actor(), actor())
+            .prop_map(|(signer, new_authority)| Op::DialUpdateAuthority { signer, new_authority }),
+        2 => (0i64..3 * DAY).prop_map(|seconds| Op::Warp { seconds }),
+    ]
+}
+
+/// Everything the invariants compare between steps
+struct Snapshot {
+    vault: VaultState,
+    dial: DialState,
+    supply: u64,
+    /// Balances plus fees withheld in the token accounts and the mint
+    accounted: u64,
+}
+
+struct Harness {
+    ctx: ProgramTestContext,
+    actors: Vec<Keypair>,
+    /// MIKO account of each actor
+    token_accounts: Vec<Pubkey>,
+    /// Reward token account of each actor, reported by withdrawals
+    reward_accounts: Vec<Pubkey>,
+    reward_mint: Pubkey,
+    vault: VaultInstructions,
+    outsider_wallet: Pubkey,
+}
+
+impl Harness {
+    async fn start() -> Self {
+        let mut pt = ProgramTest::new(
+            "absolute_vault",
+            absolute_vault::id(),
+            processor!(vault_entry),
+        );
+        pt.add_program("smart_dial", smart_dial::id(), processor!(dial_entry));
+        pt.prefer_bpf(false);
+
+        let actors: Vec<Keypair> = (0..ACTORS).map(|_| Keypair::new()).collect();
+        let reward_mint = Pubkey::new_unique();
+        pt.add_account(reward_mint, reward_mint_account());
+        let mut reward_accounts = Vec::new();
+        for actor in &actors {
+            pt.add_account(
+                actor.pubkey(),
+                Account {
+                    lamports: 10_000_000_000,
+                    ..Account::default()
+                },
+            );
+            let reward_account = Pubkey::new_unique();
+            pt.add_account(
+                reward_account,
+                reward_token_account(&reward_mint, &actor.pubkey()),
+            );
+            reward_accounts.push(reward_account);
+        }
+
+        let mut ctx = pt.start_with_context().await;
+        set_time(&mut ctx, LAUNCH);
+
+        let mint = Keypair::new();
+        let vault_pda = pda::vault_pda(&mint.pubkey());
+        create_fee_mint(&mut ctx, &mint, &actors[AUTHORITY], &vault_pda).await;
+
+        let mut token_accounts = Vec::new();
+        for actor in &actors {
+            let account = create_token_account(&mut ctx, &mint.pubkey(), &actor.pubkey()).await;
+            token_accounts.push(account);
+        }
+        let vault_token_account = create_token_account(&mut ctx, &mint.pubkey(), &vault_pda).await;
+        let mint_to: Vec<Instruction> = token_accounts
+            .iter()
+            .map(|account| {
+                token_instruction::mint_to(
+                    &spl_token_2022::id(),
+                    &mint.pubkey(),
+                    account,
+                    &actors[AUTHORITY].pubkey(),
+                    &[],
+                    MINTED_PER_ACTOR,
+                )
+                .unwrap()
+            })
+            .collect();
+        send(&mut ctx, &mint_to, &[&actors[AUTHORITY]])
+            .await
+            .unwrap();
+
+        let vault = VaultInstructions::new(mint.pubkey(), vault_token_account);
+        let payer = ctx.payer.pubkey();
+        let authority = actors[AUTHORITY].pubkey();
+        let setup = [
+            vault.initialize(
+                &authority,
+                &payer,
+                actors[OWNER].pubkey(),
+                actors[KEEPER].pubkey(),
+            ),
+            vault.initialize_pool_registry(&payer),
+            vault.initialize_revenue_split(&authority, &payer),
+            // Fees reach the mint only through harvests, so any threshold above zero
+            // would keep the first harvest from ever running
+            vault.update_config(
+                &authority,
+                &ConfigUpdate {
+                    new_harvest_threshold: Some(0),
+                    ..ConfigUpdate::default()
+                },
+            ),
+            dial::initialize(&authority, &payer, LAUNCH),
+        ];
+        send(&mut ctx, &setup, &[&actors[AUTHORITY]]).await.unwrap();
+
+        Self {
+            ctx,
+            actors,
+            token_accounts,
+            reward_accounts,
+            reward_mint,
+            vault,
+            outsider_wallet: Pubkey::new_unique(),
+        }
+    }
+
+    /// Actors, the vault PDA, the vault program and a wallet no one controls
+    fn wallet(&self, index: usize) -> Pubkey {
+        match index {
+            i if i < ACTORS => self.actors[i].pubkey(),
+            i if i == ACTORS => self.vault.vault,
+            i if i == ACTORS + 1 => absolute_vault::id(),
+            _ => self.outsider_wallet,
+        }
+    }
+
+    fn holder_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts = self.token_accounts.clone();
+        accounts.push(self.vault.vault_token_account);
+        accounts
+    }
+
+    async fn snapshot(&mut self) -> Snapshot {
+        let (vault_address, mint_address) = (self.vault.vault, self.vault.token_mint);
+        let vault = decode(&self.account_data(&vault_address).await).unwrap();
+        let dial = decode_zero_copy(&self.account_data(&pda::dial_state_pda()).await).unwrap();
+
+        let mint_data = self.account_data(&mint_address).await;
+        let mint = StateWithExtensions::<MintState>::unpack(&mint_data).unwrap();
+        let mut accounted: u64 = mint
+            .get_extension::<TransferFeeConfig>()
+            .unwrap()
+            .withheld_amount
+            .into();
+        for account in self.holder_accounts() {
+            let data = self.account_data(&account).await;
+            let state = StateWithExtensions::<TokenAccountState>::unpack(&data).unwrap();
+            let withheld: u64 = state
+                .get_extension::<TransferFeeAmount>()
+                .unwrap()
+                .withheld_amount
+                .into();
+            accounted += state.base.amount + withheld;
+        }
+
+        Snapshot {
+            vault,
+            dial,
+            supply: mint.base.supply,
+            accounted,
+        }
+    }
+
+    async fn account_data(&mut self, address: &Pubkey) -> Vec<u8> {
+        self.ctx
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+    }
+
+    async fn token_amount(&mut self, address: &Pubkey) -> u64 {
+        let data = self.account_data(address).await;
+        StateWithExtensions::<TokenAccountState>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    /// Run `op`, returning whether its transaction succeeded
+    async fn apply(&mut self, op: &Op, before: &Snapshot) -> bool {
+        let (ix, signer) = match *op {
+            Op::Transfer { from, to, amount } => (
+                transfer_fee_instruction::transfer_checked_with_fee(
+                    &spl_token_2022::id(),
+                    &self.token_accounts[from],
+                    &self.vault.token_mint,
+                    &self.token_accounts[to],
+                    &self.actors[from].pubkey(),
+                    &[],
+                    amount,
+                    DECIMALS,
+                    fee_of(amount),
+                )
+                .unwrap(),
+                Some(from),
+            ),
+            Op::Harvest { signer } => (
+                self.vault.harvest_fees(
+                    &Keeper::primary(self.actors[signer].pubkey()),
+                    &self.holder_accounts(),
+                ),
+                Some(signer),
+            ),
+            Op::WithdrawFees { signer } => (
+                self.vault
+                    .withdraw_fees_from_mint(&Keeper::primary(self.actors[signer].pubkey())),
+                Some(signer),
+            ),
+            Op::Burn => (self.vault.burn_harvested_fees(), None),
+            Op::WithdrawAndReport {
+                signer,
+                full_amount,
+            } => {
+                let vault_token_account = self.vault.vault_token_account;
+                let balance = self.token_amount(&vault_token_account).await;
+                let plan = DistributionPlan {
+                    distribution_id: before.vault.distribution_id.wrapping_add(1),
+                    amount: if full_amount { balance } else { balance + 1 },
+                    expected_minimum_reward_amount: 0,
+                    distribution_hash: [0; 32],
+                };
+                (
+                    self.vault.withdraw_and_report_distribution_plan(
+                        &Keeper::primary(self.actors[signer].pubkey()),
+                        &plan,
+                        &self.token_accounts[signer],
+                        &self.reward_accounts[signer],
+                        &[],
+                    ),
+                    Some(signer),
+                )
+            }
+            Op::Settle { signer } => (
+                self.vault.settle_distribution(
+                    &Keeper::primary(self.actors[signer].pubkey()),
+                    before.vault.distribution_id,
+                    &self.reward_accounts[signer],
+                    &self.reward_mint,
+                    &spl_token_2022::id(),
+                    // The split initialized at launch pays the launch owner
+                    &[self.actors[OWNER].pubkey()],
+                ),
+                Some(signer),
+            ),
+            Op::ManageExclusion {
+                signer,
+                remove,
+                wallet,
+            } => {
+                let action = if remove {
+                    ExclusionAction::Remove
+                } else {
+                    ExclusionAction::Add
+                };
+                (
+                    self.vault.manage_exclusions(
+                        &self.actors[signer].pubkey(),
+                        action,
+                        self.wallet(wallet),
+                    ),
+                    Some(signer),
+                )
+            }
+            Op::UpdateConfig {
+                signer,
+                new_authority,
+                new_keeper,
+                burn_share_bps,
+            } => (
+                self.vault.update_config(
+                    &self.actors[signer].pubkey(),
+                    &ConfigUpdate {
+                        new_authority: new_authority.map(|i| self.actors[i].pubkey()),
+                        new_keeper_authority: new_keeper.map(|i| self.actors[i].pubkey()),
+                        new_burn_share_bps: burn_share_bps,
+                        ..ConfigUpdate::default()
+                    },
+                ),
+                Some(signer),
+            ),
+            Op::SetLaunchTime => (self.vault.set_launch_time(), None),
+            Op::DialUpdate {
+                signer,
+                cycle_day,
+                next_cycle_days,
+            } => {
+                let cycle_start =
+                    before.dial.launch_timestamp + INITIAL_UPDATE_DELAY + cycle_day * DAY;
+                (
+                    dial::update_reward_token(
+                        &self.actors[signer].pubkey(),
+                        Pubkey::new_unique(),
+                        cycle_start,
+                        cycle_start + next_cycle_days * 7 * DAY,
+                    ),
+                    Some(signer),
+                )
+            }
+            Op::DialSync {
+                signer,
+                launch_offset,
+            } => (
+                dial::sync_launch_timestamp(
+                    &self.actors[signer].pubkey(),
+                    before.dial.launch_timestamp + launch_offset,
+                ),
+                Some(signer),
+            ),
+            Op::DialUpdateAuthority {
+                signer,
+                new_authority,
+            } => (
+                dial::update_authority(
+                    &self.actors[signer].pubkey(),
+                    self.actors[new_authority].pubkey(),
+                ),
+                Some(signer),
+            ),
+            Op::Warp { seconds } => {
+                let before: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+                self.ctx.warp_to_slot(before.slot + 2).unwrap();
+                let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+                clock.unix_timestamp = before.unix_timestamp + seconds;
+                self.ctx.set_sysvar(&clock);
+                return true;
+            }
+        };
+
+        let signers: Vec<&Keypair> = signer.map(|i| &self.actors[i]).into_iter().collect();
+        send(&mut self.ctx, &[ix], &signers).await.is_ok()
+    }
+
+    fn signer_key(&self, index: usize) -> Pubkey {
+        self.actors[index].pubkey()
+    }
+}
+
+/// Every invariant that must hold across `op`, whether or not it succeeded
+fn check_step(
+    harness: &Harness,
+    op: &Op,
+    succeeded: bool,
+    before: &Snapshot,
+    after: &Snapshot,
+) -> Result<(), TestCaseError> {
+    let (old, new) = (&before.vault, &after.vault);
+
+    // Authority integrity: gated instructions only succeed for the current holder, and
+    // the holders change only through their own successful handover
+    match *op {
+        Op::ManageExclusion { signer, .. } | Op::UpdateConfig { signer, .. }
+            if harness.signer_key(signer) != old.authority =>
+        {
+            prop_assert!(!succeeded, "{op:?} succeeded without the vault authority");
+        }
+        Op::Harvest { signer }
+        | Op::WithdrawFees { signer }
+        | Op::WithdrawAndReport { signer, .. }
+        | Op::Settle { signer }
+            if harness.signer_key(signer) != old.keeper_authority =>
+        {
+            prop_assert!(!succeeded, "{op:?} succeeded without the keeper");
+        }
+        Op::DialUpdate { signer, .. }
+        | Op::DialSync { signer, .. }
+        | Op::DialUpdateAuthority { signer, .. }
+            if harness.signer_key(signer) != before.dial.authority =>
+        {
+            prop_assert!(!succeeded, "{op:?} succeeded without the dial authority");
+        }
+        _ => {}
+    }
+    let (new_authority, new_keeper) = match *op {
+        Op::UpdateConfig {
+            new_authority,
+            new_keeper,
+            ..
+        } if succeeded => (
+            new_authority.map(|i| harness.signer_key(i)),
+            new_keeper.map(|i| harness.signer_key(i)),
+        ),
+        _ => (None, None),
+    };
+    prop_assert_eq!(new.authority, new_authority.unwrap_or(old.authority));
+    prop_assert_eq!(
+        new.keeper_authority,
+        new_keeper.unwrap_or(old.keeper_authority)
+    );
+    prop_assert_eq!(new.owner_wallet, old.owner_wallet);
+    prop_assert_eq!(new.token_mint, old.token_mint);
+    let new_dial_authority = match *op {
+        Op::DialUpdateAuthority { new_authority, .. } if succeeded => {
+            harness.signer_key(new_authority)
+        }
+        _ => before.dial.authority,
+    };
+    prop_assert_eq!(after.dial.authority, new_dial_authority);
+
+    // Counters only move forward
+    prop_assert!(new.distribution_id >= old.distribution_id);
+    prop_assert!(new.total_fees_harvested >= old.total_fees_harvested);
+    prop_assert!(new.total_burned >= old.total_burned);
+    prop_assert!(new.total_rewards_distributed >= old.total_rewards_distributed);
+    prop_assert!(new.last_harvest_time >= old.last_harvest_time);
+    prop_assert!(after.dial.update_count >= before.dial.update_count);
+    prop_assert!(after.dial.last_update >= before.dial.last_update);
+    prop_assert!(after.dial.history_len().unwrap() <= MAX_UPDATE_HISTORY);
+
+    // Protocol wallets that were excluded stay excluded
+    let protected = [
+        old.authority,
+        old.keeper_authority,
+        old.owner_wallet,
+        harness.vault.vault,
+        absolute_vault::id(),
+    ];
+    for wallet in protected.iter().filter(|wallet| old.is_excluded(wallet)) {
+        prop_assert!(new.is_excluded(wallet), "{op:?} dropped {wallet}");
+    }
+
+    // Tokens are only created at setup and only destroyed by the burn
+    prop_assert_eq!(after.supply, after.accounted);
+    prop_assert_eq!(
+        after.supply + new.total_burned,
+        MINTED_PER_ACTOR * ACTORS as u64
+    );
+
+    Ok(())
+}
+
+fn run(ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let mut harness = Harness::start().await;
+        let mut before = harness.snapshot().await;
+        for op in &ops {
+            let succeeded = harness.apply(op, &before).await;
+            let after = harness.snapshot().await;
+            check_step(&harness, op, succeeded, &before, &after)?;
+            before = after;
+        }
+        Ok(())
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 48,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn instruction_sequences_preserve_invariants(ops in prop::collection::vec(op(), 1..40)) {
+        run(ops)?;
+    }
+}
+
+// Fixtures
+
+async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), solana_program_test::BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+fn set_time(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    ctx.set_sysvar(&Clock {
+        unix_timestamp,
+        ..Clock::default()
+    });
+}
+
+/// MIKO mint charging `FEE_BPS` on every transfer, withheld fees withdrawable only by
+/// the vault PDA
+async fn create_fee_mint(
+    ctx: &mut ProgramTestContext,
+    mint: &Keypair,
+    mint_authority: &Keypair,
+    withdraw_authority: &Pubkey,
+) {
+    let space =
+        ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &ctx.payer.pubkey(),
+            &mint.pubkey(),
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        transfer_fee_instruction::initialize_transfer_fee_config(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            Some(&mint_authority.pubkey()),
+            Some(withdraw_authority),
+            FEE_BPS,
+            u64::MAX,
+        )
+        .unwrap(),
+        token_instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &mint_authority.pubkey(),
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+    ];
+    send(ctx, &instructions, &[mint]).await.unwrap();
+}
+
+async fn create_token_account(
+    ctx: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let space = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &ctx.payer.pubkey(),
+            &account.pubkey(),
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        token_instruction::initialize_account3(
+            &spl_token_2022::id(),
+            &account.pubkey(),
+            mint,
+            owner,
+        )
+        .unwrap(),
+    ];
+    send(ctx, &instructions, &[&account]).await.unwrap();
+    account.pubkey()
+}
+
+fn reward_mint_account() -> Account {
+    let mut data = vec![0; MintState::LEN];
+    MintState {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        ..Account::default()
+    }
+}
+
+fn reward_token_account(mint: &Pubkey, owner: &Pubkey) -> Account {
+    let mut data = vec![0; TokenAccountState::LEN];
+    TokenAccountState {
+        mint: *mint,
+        owner: *owner,
+        state: AccountState::Initialized,
+        ..TokenAccountState::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token_2022::id(),
+        ..Account::default()
+    }
+}
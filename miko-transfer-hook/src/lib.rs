@@ -0,0 +1,302 @@
+// Anchor's generated IDL instructions still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_token_2022::{
+    extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+    state::Account as TokenAccountState,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+// Program ID is dynamically generated from keypair at compile time
+declare_id!("9WwxJQDzQh8C1jdnXJusYweZNf9eDKm1VHZBiDdYzxei");
+
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+pub const CHECKPOINT_SEED: &[u8] = b"holder_checkpoint";
+
+#[program]
+pub mod miko_transfer_hook {
+    use super::*;
+
+    /// Register the checkpoint accounts Token-2022 resolves on every MIKO transfer
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        let mut data = ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?;
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_account_metas()?)?;
+
+        msg!(
+            "Extra account metas initialized for {}",
+            ctx.accounts.mint.key()
+        );
+        Ok(())
+    }
+
+    /// Start tracking a holder token account of a registered mint (permissionless)
+    pub fn initialize_checkpoint(ctx: Context<InitializeCheckpoint>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let token_account = &ctx.accounts.token_account;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+
+        checkpoint.mint = token_account.mint;
+        checkpoint.token_account = token_account.key();
+        checkpoint.owner = token_account.owner;
+        checkpoint.balance = token_account.amount;
+        checkpoint.last_change = now;
+        checkpoint.accumulated_balance_seconds = 0;
+        checkpoint.initialized_at = now;
+
+        msg!(
+            "Checkpoint initialized for {} with balance {}",
+            checkpoint.token_account,
+            checkpoint.balance
+        );
+        Ok(())
+    }
+
+    /// Resync a checkpoint after balance changes that bypass the hook, such as burns
+    /// or withheld fee withdrawals (permissionless)
+    pub fn sync_checkpoint(ctx: Context<SyncCheckpoint>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let token_account = &ctx.accounts.token_account;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+
+        checkpoint.owner = token_account.owner;
+        checkpoint.record(token_account.amount, now);
+
+        msg!(
+            "Checkpoint synced for {} with balance {}",
+            checkpoint.token_account,
+            checkpoint.balance
+        );
+        Ok(())
+    }
+
+    /// Token-2022 transfer hook: roll both sides' balance-seconds forward
+    /// Untracked token accounts are skipped so the hook never blocks a transfer
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        require!(
+            is_transferring(&ctx.accounts.source_token)?,
+            HookError::NotTransferring
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let source_balance = token_amount(&ctx.accounts.source_token)?;
+        let destination_balance = token_amount(&ctx.accounts.destination_token)?;
+
+        update_checkpoint(&ctx.accounts.source_checkpoint, source_balance, now)?;
+        update_checkpoint(
+            &ctx.accounts.destination_checkpoint,
+            destination_balance,
+            now,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Checkpoints of the source and destination token accounts, in that order
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: CHECKPOINT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            true,
+        )?,
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: CHECKPOINT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            true,
+        )?,
+    ])
+}
+
+fn is_transferring(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    let extension = account.get_extension::<TransferHookAccount>()?;
+    Ok(bool::from(extension.transferring))
+}
+
+fn token_amount(token_account: &AccountInfo) -> Result<u64> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    Ok(account.base.amount)
+}
+
+fn update_checkpoint(checkpoint_info: &AccountInfo, balance: u64, now: i64) -> Result<()> {
+    if checkpoint_info.owner != &crate::ID || checkpoint_info.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut data = checkpoint_info.try_borrow_mut_data()?;
+    let mut checkpoint = HolderCheckpoint::try_deserialize(&mut &data[..])?;
+    checkpoint.record(balance, now);
+    checkpoint.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+// Account structures
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: ExtraAccountMetaList account, written by this instruction
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(extra_account_metas()?.len())?,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCheckpoint<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HolderCheckpoint::INIT_SPACE,
+        seeds = [CHECKPOINT_SEED, token_account.key().as_ref()],
+        bump
+    )]
+    pub checkpoint: Account<'info, HolderCheckpoint>,
+
+    #[account(
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: ExtraAccountMetaList account, only checked to exist so checkpoints are
+    /// limited to mints registered with this hook
+    #[account(
+        owner = crate::ID,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncCheckpoint<'info> {
+    #[account(
+        mut,
+        seeds = [CHECKPOINT_SEED, token_account.key().as_ref()],
+        bump
+    )]
+    pub checkpoint: Account<'info, HolderCheckpoint>,
+
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    /// CHECK: Source token account, validated by Token-2022
+    pub source_token: UncheckedAccount<'info>,
+
+    /// CHECK: MIKO mint
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Destination token account, validated by Token-2022
+    pub destination_token: UncheckedAccount<'info>,
+
+    /// CHECK: Source owner or delegate
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: ExtraAccountMetaList account
+    #[account(
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// CHECK: Source checkpoint, may not be initialized
+    #[account(
+        mut,
+        seeds = [CHECKPOINT_SEED, source_token.key().as_ref()],
+        bump
+    )]
+    pub source_checkpoint: UncheckedAccount<'info>,
+
+    /// CHECK: Destination checkpoint, may not be initialized
+    #[account(
+        mut,
+        seeds = [CHECKPOINT_SEED, destination_token.key().as_ref()],
+        bump
+    )]
+    pub destination_checkpoint: UncheckedAccount<'info>,
+}
+
+// State
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderCheckpoint {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub last_change: i64,
+    pub accumulated_balance_seconds: u128,
+    pub initialized_at: i64,
+}
+
+impl HolderCheckpoint {
+    /// Balance-seconds accrued up to `now`, including the interval since the last change
+    pub fn balance_seconds_at(&self, now: i64) -> u128 {
+        let elapsed = now.saturating_sub(self.last_change).max(0) as u128;
+        self.accumulated_balance_seconds
+            .saturating_add((self.balance as u128).saturating_mul(elapsed))
+    }
+
+    /// Close the current interval and start a new one at `balance`
+    /// Saturates rather than erroring so a checkpoint can never block a transfer
+    pub fn record(&mut self, balance: u64, now: i64) {
+        self.accumulated_balance_seconds = self.balance_seconds_at(now);
+        self.balance = balance;
+        self.last_change = now.max(self.last_change);
+    }
+}
+
+// Errors
+
+#[error_code]
+pub enum HookError {
+    #[msg("Hook invoked outside of a token transfer")]
+    NotTransferring,
+}
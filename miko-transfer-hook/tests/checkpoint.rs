@@ -0,0 +1,384 @@
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use miko_transfer_hook::{HolderCheckpoint, CHECKPOINT_SEED, EXTRA_ACCOUNT_METAS_SEED};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token_2022::{
+    extension::{transfer_hook, ExtensionType},
+    instruction as token_instruction,
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+
+const DECIMALS: u8 = 9;
+const START: i64 = 1_700_000_000;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint wants matching lifetimes on the slice and the infos
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    miko_transfer_hook::entry(program_id, accounts, data)
+}
+
+fn checkpoint_pda(token_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[CHECKPOINT_SEED, token_account.as_ref()],
+        &miko_transfer_hook::id(),
+    )
+    .0
+}
+
+async fn try_send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+async fn send(ctx: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) {
+    try_send(ctx, instructions, signers).await.unwrap();
+}
+
+fn set_time(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    ctx.set_sysvar(&Clock {
+        unix_timestamp,
+        ..Clock::default()
+    });
+}
+
+async fn fetch_checkpoint(
+    ctx: &mut ProgramTestContext,
+    token_account: &Pubkey,
+) -> HolderCheckpoint {
+    let account = ctx
+        .banks_client
+        .get_account(checkpoint_pda(token_account))
+        .await
+        .unwrap()
+        .unwrap();
+    HolderCheckpoint::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+struct HookFixture {
+    mint: Keypair,
+    mint_authority: Keypair,
+}
+
+impl HookFixture {
+    async fn start() -> (ProgramTestContext, Self) {
+        let mut pt = ProgramTest::new(
+            "miko_transfer_hook",
+            miko_transfer_hook::id(),
+            processor!(process_instruction),
+        );
+        pt.prefer_bpf(false);
+        let mut ctx = pt.start_with_context().await;
+        set_time(&mut ctx, START);
+
+        let fixture = Self {
+            mint: Keypair::new(),
+            mint_authority: Keypair::new(),
+        };
+        let mint = fixture.mint.pubkey();
+        let payer = ctx.payer.pubkey();
+
+        let space =
+            ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferHook])
+                .unwrap();
+        let create_mint = [
+            system_instruction::create_account(
+                &payer,
+                &mint,
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            transfer_hook::instruction::initialize(
+                &spl_token_2022::id(),
+                &mint,
+                Some(fixture.mint_authority.pubkey()),
+                Some(miko_transfer_hook::id()),
+            )
+            .unwrap(),
+            token_instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                &mint,
+                &fixture.mint_authority.pubkey(),
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+            Instruction {
+                program_id: miko_transfer_hook::id(),
+                accounts: miko_transfer_hook::accounts::InitializeExtraAccountMetaList {
+                    payer,
+                    extra_account_meta_list: fixture.extra_account_meta_list(),
+                    mint,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: miko_transfer_hook::instruction::InitializeExtraAccountMetaList {}.data(),
+            },
+        ];
+        send(&mut ctx, &create_mint, &[&fixture.mint]).await;
+
+        (ctx, fixture)
+    }
+
+    fn extra_account_meta_list(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[EXTRA_ACCOUNT_METAS_SEED, self.mint.pubkey().as_ref()],
+            &miko_transfer_hook::id(),
+        )
+        .0
+    }
+
+    /// Create a token account holding `amount`, optionally tracked by a checkpoint
+    async fn token_account(
+        &self,
+        ctx: &mut ProgramTestContext,
+        owner: &Pubkey,
+        amount: u64,
+        tracked: bool,
+    ) -> Pubkey {
+        let account = Keypair::new();
+        let payer = ctx.payer.pubkey();
+        let space = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[
+            ExtensionType::TransferHookAccount,
+        ])
+        .unwrap();
+
+        let mut instructions = vec![
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            token_instruction::initialize_account3(
+                &spl_token_2022::id(),
+                &account.pubkey(),
+                &self.mint.pubkey(),
+                owner,
+            )
+            .unwrap(),
+        ];
+        if amount > 0 {
+            instructions.push(
+                token_instruction::mint_to(
+                    &spl_token_2022::id(),
+                    &self.mint.pubkey(),
+                    &account.pubkey(),
+                    &self.mint_authority.pubkey(),
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            );
+        }
+        if tracked {
+            instructions.push(Instruction {
+                program_id: miko_transfer_hook::id(),
+                accounts: miko_transfer_hook::accounts::InitializeCheckpoint {
+                    checkpoint: checkpoint_pda(&account.pubkey()),
+                    token_account: account.pubkey(),
+                    mint: self.mint.pubkey(),
+                    extra_account_meta_list: self.extra_account_meta_list(),
+                    payer,
+                    token_program: spl_token_2022::id(),
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: miko_transfer_hook::instruction::InitializeCheckpoint {}.data(),
+            });
+        }
+        if amount > 0 {
+            send(ctx, &instructions, &[&account, &self.mint_authority]).await;
+        } else {
+            send(ctx, &instructions, &[&account]).await;
+        }
+
+        account.pubkey()
+    }
+
+    fn transfer_ix(
+        &self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let mut ix = token_instruction::transfer_checked(
+            &spl_token_2022::id(),
+            source,
+            &self.mint.pubkey(),
+            destination,
+            owner,
+            &[],
+            amount,
+            DECIMALS,
+        )
+        .unwrap();
+        ix.accounts.extend([
+            AccountMeta::new(checkpoint_pda(source), false),
+            AccountMeta::new(checkpoint_pda(destination), false),
+            AccountMeta::new_readonly(miko_transfer_hook::id(), false),
+            AccountMeta::new_readonly(self.extra_account_meta_list(), false),
+        ]);
+        ix
+    }
+}
+
+#[tokio::test]
+async fn transfers_roll_balance_seconds_forward_on_both_sides() {
+    let (mut ctx, fixture) = HookFixture::start().await;
+    let alice = Keypair::new();
+    let bob = Pubkey::new_unique();
+    let source = fixture
+        .token_account(&mut ctx, &alice.pubkey(), 1_000, true)
+        .await;
+    let destination = fixture.token_account(&mut ctx, &bob, 0, true).await;
+
+    set_time(&mut ctx, START + 100);
+    let transfer = fixture.transfer_ix(&source, &destination, &alice.pubkey(), 400);
+    send(&mut ctx, &[transfer], &[&alice]).await;
+
+    let source_checkpoint = fetch_checkpoint(&mut ctx, &source).await;
+    assert_eq!(source_checkpoint.owner, alice.pubkey());
+    assert_eq!(source_checkpoint.balance, 600);
+    assert_eq!(source_checkpoint.last_change, START + 100);
+    assert_eq!(source_checkpoint.accumulated_balance_seconds, 100_000);
+
+    let destination_checkpoint = fetch_checkpoint(&mut ctx, &destination).await;
+    assert_eq!(destination_checkpoint.balance, 400);
+    assert_eq!(destination_checkpoint.accumulated_balance_seconds, 0);
+    assert_eq!(
+        destination_checkpoint.balance_seconds_at(START + 150),
+        20_000
+    );
+}
+
+#[tokio::test]
+async fn transfers_to_untracked_accounts_are_not_blocked() {
+    let (mut ctx, fixture) = HookFixture::start().await;
+    let alice = Keypair::new();
+    let source = fixture
+        .token_account(&mut ctx, &alice.pubkey(), 1_000, true)
+        .await;
+    let untracked = fixture
+        .token_account(&mut ctx, &Pubkey::new_unique(), 0, false)
+        .await;
+
+    set_time(&mut ctx, START + 10);
+    let transfer = fixture.transfer_ix(&source, &untracked, &alice.pubkey(), 250);
+    send(&mut ctx, &[transfer], &[&alice]).await;
+
+    let source_checkpoint = fetch_checkpoint(&mut ctx, &source).await;
+    assert_eq!(source_checkpoint.balance, 750);
+    assert_eq!(source_checkpoint.accumulated_balance_seconds, 10_000);
+    assert!(ctx
+        .banks_client
+        .get_account(checkpoint_pda(&untracked))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn checkpoints_are_limited_to_registered_mints() {
+    let (mut ctx, fixture) = HookFixture::start().await;
+    let payer = ctx.payer.pubkey();
+    let other_mint = Keypair::new();
+    let account = Keypair::new();
+    let mint_space = ExtensionType::try_calculate_account_len::<MintState>(&[]).unwrap();
+    let account_space = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[]).unwrap();
+    let setup = [
+        system_instruction::create_account(
+            &payer,
+            &other_mint.pubkey(),
+            Rent::default().minimum_balance(mint_space),
+            mint_space as u64,
+            &spl_token_2022::id(),
+        ),
+        token_instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &other_mint.pubkey(),
+            &fixture.mint_authority.pubkey(),
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+        system_instruction::create_account(
+            &payer,
+            &account.pubkey(),
+            Rent::default().minimum_balance(account_space),
+            account_space as u64,
+            &spl_token_2022::id(),
+        ),
+        token_instruction::initialize_account3(
+            &spl_token_2022::id(),
+            &account.pubkey(),
+            &other_mint.pubkey(),
+            &Pubkey::new_unique(),
+        )
+        .unwrap(),
+    ];
+    send(&mut ctx, &setup, &[&other_mint, &account]).await;
+
+    let initialize_checkpoint = |mint: Pubkey, extra_account_meta_list: Pubkey| Instruction {
+        program_id: miko_transfer_hook::id(),
+        accounts: miko_transfer_hook::accounts::InitializeCheckpoint {
+            checkpoint: checkpoint_pda(&account.pubkey()),
+            token_account: account.pubkey(),
+            mint,
+            extra_account_meta_list,
+            payer,
+            token_program: spl_token_2022::id(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: miko_transfer_hook::instruction::InitializeCheckpoint {}.data(),
+    };
+
+    // The mint has no extra account metas registered with the hook
+    let unregistered_meta_list = Pubkey::find_program_address(
+        &[EXTRA_ACCOUNT_METAS_SEED, other_mint.pubkey().as_ref()],
+        &miko_transfer_hook::id(),
+    )
+    .0;
+    let unregistered = initialize_checkpoint(other_mint.pubkey(), unregistered_meta_list);
+    assert!(try_send(&mut ctx, &[unregistered], &[]).await.is_err());
+
+    // Nor can the token account borrow the registered mint
+    let borrowed = initialize_checkpoint(fixture.mint.pubkey(), fixture.extra_account_meta_list());
+    assert!(try_send(&mut ctx, &[borrowed], &[]).await.is_err());
+
+    assert!(ctx
+        .banks_client
+        .get_account(checkpoint_pda(&account.pubkey()))
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -1,4 +1,8 @@
+// Anchor's generated IDL instructions still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 // Program ID is dynamically generated from keypair at compile time
 declare_id!("423KiBKFusrnh8QGcmj6rE9ntPWH7FzRQCJ5Z5kNeRmp");
@@ -6,6 +10,14 @@ declare_id!("423KiBKFusrnh8QGcmj6rE9ntPWH7FzRQCJ5Z5kNeRmp");
 pub const DIAL_STATE_SEED: &[u8] = b"dial_state";
 pub const SOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 pub const INITIAL_UPDATE_DELAY: i64 = 24 * 60 * 60; // 24 hours after launch
+pub const DIAL_STATE_VERSION: u8 = 2;
+pub const DIAL_STATE_LEN: usize = 8 + std::mem::size_of::<DialState>();
+/// Update records the dial keeps, a year of weekly updates
+pub const MAX_UPDATE_HISTORY: usize = 52;
+// Legacy layouts are recognized by the size they were allocated with; a dial of any
+// other size must be migrated before it is loaded
+pub const DIAL_STATE_LAYOUT_V0_LEN: usize = 8 + DialStateLayoutV0::INIT_SPACE;
+pub const DIAL_STATE_LAYOUT_V1_LEN: usize = 8 + DialStateLayoutV1::INIT_SPACE;
 
 #[program]
 pub mod smart_dial {
@@ -16,17 +28,17 @@ pub mod smart_dial {
         ctx: Context<Initialize>,
         launch_timestamp: i64,
     ) -> Result<()> {
-        let dial = &mut ctx.accounts.dial_state;
-        
+        let mut dial = ctx.accounts.dial_state.load_init()?;
+
+        dial.version = DIAL_STATE_VERSION;
         dial.authority = ctx.accounts.authority.key();
         dial.current_reward_token = SOL_MINT; // SOL is default reward token
         dial.last_update = 0;
         dial.update_count = 0;
         dial.launch_timestamp = launch_timestamp;
 
-        // Initialize update history
-        dial.update_history = Vec::new();
-        
+        // The update history starts empty: a new account is zeroed
+
         msg!("Smart Dial initialized");
         msg!("Authority: {}", dial.authority);
         msg!("Initial reward token: SOL");
@@ -42,10 +54,10 @@ pub mod smart_dial {
         cycle_start: i64,
         next_cycle_start: i64,
     ) -> Result<()> {
-        let dial = &mut ctx.accounts.dial_state;
+        let mut dial = ctx.accounts.dial_state.load_mut()?;
         let current_time = Clock::get()?.unix_timestamp;
 
-        let earliest_update = earliest_update_time(dial.launch_timestamp);
+        let earliest_update = earliest_update_time(dial.launch_timestamp)?;
 
         require!(
             current_time >= earliest_update,
@@ -72,26 +84,8 @@ pub mod smart_dial {
             DialError::InvalidNextCycleStart
         );
 
-        // Store in update history
-        if dial.update_history.len() >= 52 { // Keep last year of history
-            dial.update_history.remove(0);
-        }
-        
-        // Store values before mutable borrow
-        let old_token = dial.current_reward_token;
-        let update_number = dial.update_count;
-        
-        dial.update_history.push(UpdateRecord {
-            timestamp: current_time,
-            old_token,
-            new_token: new_reward_token,
-            update_number,
-        });
-        
-        // Update reward token
-        dial.current_reward_token = new_reward_token;
-        dial.last_update = cycle_start;
-        dial.update_count += 1;
+        // Store in update history and update reward token
+        dial.record_update(new_reward_token, cycle_start, current_time)?;
 
         msg!("Reward token updated to: {}", new_reward_token);
         msg!("Update count: {}", dial.update_count);
@@ -106,7 +100,7 @@ pub mod smart_dial {
         ctx: Context<UpdateAuthority>,
         new_authority: Pubkey,
     ) -> Result<()> {
-        ctx.accounts.dial_state.authority = new_authority;
+        ctx.accounts.dial_state.load_mut()?.authority = new_authority;
 
         msg!("Authority updated to: {}", new_authority);
 
@@ -118,7 +112,7 @@ pub mod smart_dial {
         ctx: Context<SyncLaunchTimestamp>,
         new_launch_timestamp: i64,
     ) -> Result<()> {
-        let dial = &mut ctx.accounts.dial_state;
+        let mut dial = ctx.accounts.dial_state.load_mut()?;
 
         require!(
             dial.update_count == 0,
@@ -137,11 +131,82 @@ pub mod smart_dial {
 
         Ok(())
     }
+
+    /// Reward token for the current cycle, returned as a Pubkey (view)
+    pub fn get_current_reward_token(ctx: Context<ViewDial>) -> Result<Pubkey> {
+        Ok(ctx.accounts.dial_state.load()?.current_reward_token)
+    }
+
+    /// Current cycle and whether the dial can be updated yet, returned as a
+    /// `CycleInfo` (view)
+    pub fn get_cycle_info(ctx: Context<ViewDial>) -> Result<CycleInfo> {
+        let current_time = Clock::get()?.unix_timestamp;
+        ctx.accounts.dial_state.load()?.cycle_info(current_time)
+    }
+
+    /// Rewrite a dial in a legacy Borsh layout into the current zero-copy layout,
+    /// reallocating in place (authority only)
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        let dial_info = ctx.accounts.dial_state.to_account_info();
+        let legacy = read_legacy_dial(&dial_info.try_borrow_data()?)?;
+
+        require_keys_eq!(
+            legacy.authority,
+            ctx.accounts.authority.key(),
+            DialError::Unauthorized
+        );
+
+        let shortfall = Rent::get()?
+            .minimum_balance(DIAL_STATE_LEN)
+            .saturating_sub(dial_info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: dial_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        dial_info.resize(DIAL_STATE_LEN)?;
+        let mut data = dial_info.try_borrow_mut_data()?;
+        data.fill(0);
+        data[..8].copy_from_slice(DialState::DISCRIMINATOR);
+        legacy.migrate_into(bytemuck::from_bytes_mut(&mut data[8..]))?;
+
+        msg!("Smart Dial migrated to layout {}", DIAL_STATE_VERSION);
+
+        Ok(())
+    }
+}
+
+/// Decode a dial still in one of the Borsh layouts, as the last of them
+pub fn read_legacy_dial(data: &[u8]) -> Result<DialStateLayoutV1> {
+    require!(
+        data.starts_with(DialState::DISCRIMINATOR),
+        DialError::UnsupportedAccountLayout
+    );
+    let mut fields = &data[DialState::DISCRIMINATOR.len()..];
+    match data.len() {
+        DIAL_STATE_LAYOUT_V0_LEN => Ok(DialStateLayoutV0::deserialize(&mut fields)?.into()),
+        DIAL_STATE_LAYOUT_V1_LEN => Ok(DialStateLayoutV1::deserialize(&mut fields)?),
+        _ => err!(DialError::UnsupportedAccountLayout),
+    }
+}
+
+fn history_slot(sequence: u64) -> usize {
+    (sequence % MAX_UPDATE_HISTORY as u64) as usize
 }
 
 // Helper function to calculate first Monday after launch
-fn earliest_update_time(launch_timestamp: i64) -> i64 {
-    launch_timestamp + INITIAL_UPDATE_DELAY
+fn earliest_update_time(launch_timestamp: i64) -> Result<i64> {
+    launch_timestamp
+        .checked_add(INITIAL_UPDATE_DELAY)
+        .ok_or_else(|| error!(DialError::MathOverflow))
 }
 
 // Account structures
@@ -151,11 +216,11 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + DialState::INIT_SPACE,
+        space = DIAL_STATE_LEN,
         seeds = [DIAL_STATE_SEED],
         bump
     )]
-    pub dial_state: Account<'info, DialState>,
+    pub dial_state: AccountLoader<'info, DialState>,
     
     pub authority: Signer<'info>,
     
@@ -171,9 +236,10 @@ pub struct UpdateRewardToken<'info> {
         mut,
         seeds = [DIAL_STATE_SEED],
         bump,
-        constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
+        constraint = dial_state.as_ref().data_len() == DIAL_STATE_LEN @ DialError::AccountNotMigrated,
+        constraint = dial_state.load()?.authority == authority.key() @ DialError::Unauthorized
     )]
-    pub dial_state: Account<'info, DialState>,
+    pub dial_state: AccountLoader<'info, DialState>,
     
     pub authority: Signer<'info>,
 }
@@ -184,9 +250,10 @@ pub struct UpdateAuthority<'info> {
         mut,
         seeds = [DIAL_STATE_SEED],
         bump,
-        constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
+        constraint = dial_state.as_ref().data_len() == DIAL_STATE_LEN @ DialError::AccountNotMigrated,
+        constraint = dial_state.load()?.authority == authority.key() @ DialError::Unauthorized
     )]
-    pub dial_state: Account<'info, DialState>,
+    pub dial_state: AccountLoader<'info, DialState>,
 
     pub authority: Signer<'info>,
 }
@@ -197,28 +264,147 @@ pub struct SyncLaunchTimestamp<'info> {
         mut,
         seeds = [DIAL_STATE_SEED],
         bump,
-        constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
+        constraint = dial_state.as_ref().data_len() == DIAL_STATE_LEN @ DialError::AccountNotMigrated,
+        constraint = dial_state.load()?.authority == authority.key() @ DialError::Unauthorized
+    )]
+    pub dial_state: AccountLoader<'info, DialState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewDial<'info> {
+    #[account(
+        seeds = [DIAL_STATE_SEED],
+        bump,
+        constraint = dial_state.as_ref().data_len() == DIAL_STATE_LEN @ DialError::AccountNotMigrated
+    )]
+    pub dial_state: AccountLoader<'info, DialState>,
+}
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    /// CHECK: Dial in a legacy layout; discriminator, size and authority are checked
+    /// by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [DIAL_STATE_SEED],
+        bump
     )]
-    pub dial_state: Account<'info, DialState>,
+    pub dial_state: UncheckedAccount<'info>,
 
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 // State
 
-#[account]
-#[derive(InitSpace)]
+#[account(zero_copy)]
 pub struct DialState {
     pub authority: Pubkey,
     pub current_reward_token: Pubkey,
     pub last_update: i64,
     pub update_count: u64,
     pub launch_timestamp: i64,
-    #[max_len(52)] // Keep last year of updates
-    pub update_history: Vec<UpdateRecord>,
+    /// Sequence number of the oldest kept record; record `n` lives in slot
+    /// `n % MAX_UPDATE_HISTORY` of `history`
+    pub history_head: u64,
+    /// Sequence number the next record is written at
+    pub history_tail: u64,
+    pub history: [UpdateRecord; 52], // Keep last year of updates
+    pub version: u8,
+    pub reserved: [u8; 63], // Zeroed space for future fields
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+impl DialState {
+    /// Switch to `new_reward_token` and append the change to the update history
+    /// Nothing is written if the update counter would overflow
+    pub fn record_update(
+        &mut self,
+        new_reward_token: Pubkey,
+        cycle_start: i64,
+        now: i64,
+    ) -> Result<()> {
+        let update_count = self
+            .update_count
+            .checked_add(1)
+            .ok_or(DialError::MathOverflow)?;
+
+        self.push_history(UpdateRecord {
+            timestamp: now,
+            old_token: self.current_reward_token,
+            new_token: new_reward_token,
+            update_number: self.update_count,
+        })?;
+
+        self.current_reward_token = new_reward_token;
+        self.last_update = cycle_start;
+        self.update_count = update_count;
+        Ok(())
+    }
+
+    /// Kept update records, oldest first
+    pub fn update_history(&self) -> impl Iterator<Item = &UpdateRecord> {
+        (self.history_head..self.history_tail)
+            .map(|sequence| &self.history[history_slot(sequence)])
+    }
+
+    pub fn history_len(&self) -> Result<usize> {
+        let len = self
+            .history_tail
+            .checked_sub(self.history_head)
+            .ok_or(DialError::MathOverflow)?;
+        Ok(len as usize)
+    }
+
+    /// Overwrite the oldest record once the history is full
+    fn push_history(&mut self, record: UpdateRecord) -> Result<()> {
+        self.history[history_slot(self.history_tail)] = record;
+        self.history_tail = self
+            .history_tail
+            .checked_add(1)
+            .ok_or(DialError::MathOverflow)?;
+        if self.history_len()? > MAX_UPDATE_HISTORY {
+            self.history_head = self
+                .history_head
+                .checked_add(1)
+                .ok_or(DialError::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot returned by `get_cycle_info`
+    pub fn cycle_info(&self, now: i64) -> Result<CycleInfo> {
+        let earliest_update = earliest_update_time(self.launch_timestamp)?;
+
+        Ok(CycleInfo {
+            current_reward_token: self.current_reward_token,
+            cycle_start: self.last_update,
+            update_count: self.update_count,
+            launch_timestamp: self.launch_timestamp,
+            earliest_update,
+            updates_open: now >= earliest_update,
+        })
+    }
+}
+
+/// Return data of `get_cycle_info`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CycleInfo {
+    pub current_reward_token: Pubkey,
+    /// Start of the cycle the current reward token was set for, 0 before the first update
+    pub cycle_start: i64,
+    pub update_count: u64,
+    pub launch_timestamp: i64,
+    pub earliest_update: i64,
+    /// Whether the initial delay after launch has passed
+    pub updates_open: bool,
+}
+
+#[zero_copy]
 pub struct UpdateRecord {
     pub timestamp: i64,
     pub old_token: Pubkey,
@@ -226,6 +412,79 @@ pub struct UpdateRecord {
     pub update_number: u64,
 }
 
+// Legacy layouts
+
+/// `DialState` before it carried a version
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DialStateLayoutV0 {
+    pub authority: Pubkey,
+    pub current_reward_token: Pubkey,
+    pub last_update: i64,
+    pub update_count: u64,
+    pub launch_timestamp: i64,
+    #[max_len(52)]
+    pub update_history: Vec<LegacyUpdateRecord>,
+}
+
+/// `DialState` while it was Borsh encoded and its history shifted on every update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DialStateLayoutV1 {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub current_reward_token: Pubkey,
+    pub last_update: i64,
+    pub update_count: u64,
+    pub launch_timestamp: i64,
+    #[max_len(52)]
+    pub update_history: Vec<LegacyUpdateRecord>,
+    pub reserved: [u8; 64],
+}
+
+/// `UpdateRecord` as the Borsh layouts stored it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyUpdateRecord {
+    pub timestamp: i64,
+    pub old_token: Pubkey,
+    pub new_token: Pubkey,
+    pub update_number: u64,
+}
+
+impl From<DialStateLayoutV0> for DialStateLayoutV1 {
+    fn from(v0: DialStateLayoutV0) -> Self {
+        Self {
+            version: 1,
+            authority: v0.authority,
+            current_reward_token: v0.current_reward_token,
+            last_update: v0.last_update,
+            update_count: v0.update_count,
+            launch_timestamp: v0.launch_timestamp,
+            update_history: v0.update_history,
+            reserved: [0; 64],
+        }
+    }
+}
+
+impl DialStateLayoutV1 {
+    /// Write the legacy fields into a zeroed dial in the current layout
+    pub fn migrate_into(&self, dial: &mut DialState) -> Result<()> {
+        dial.version = DIAL_STATE_VERSION;
+        dial.authority = self.authority;
+        dial.current_reward_token = self.current_reward_token;
+        dial.last_update = self.last_update;
+        dial.update_count = self.update_count;
+        dial.launch_timestamp = self.launch_timestamp;
+        for record in &self.update_history {
+            dial.push_history(UpdateRecord {
+                timestamp: record.timestamp,
+                old_token: record.old_token,
+                new_token: record.new_token,
+                update_number: record.update_number,
+            })?;
+        }
+        Ok(())
+    }
+}
+
 // Errors
 
 #[error_code]
@@ -250,4 +509,13 @@ pub enum DialError {
 
     #[msg("Invalid launch timestamp provided")]
     InvalidLaunchTimestamp,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+
+    #[msg("Account is not in a migratable legacy layout")]
+    UnsupportedAccountLayout,
+
+    #[msg("Account must be migrated to the current layout first")]
+    AccountNotMigrated,
 }
@@ -0,0 +1,378 @@
+use anchor_lang::{
+    system_program, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use smart_dial::{
+    DialError, DialState, DialStateLayoutV1, DIAL_STATE_LAYOUT_V1_LEN, DIAL_STATE_SEED,
+    INITIAL_UPDATE_DELAY, SOL_MINT,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+const LAUNCH: i64 = 1_700_000_000;
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint wants matching lifetimes on the slice and the infos
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    smart_dial::entry(program_id, accounts, data)
+}
+
+fn dial_pda() -> Pubkey {
+    Pubkey::find_program_address(&[DIAL_STATE_SEED], &smart_dial::id()).0
+}
+
+fn dial_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: smart_dial::id(),
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn update_ix(
+    authority: &Pubkey,
+    new_reward_token: Pubkey,
+    cycle_start: i64,
+    next_cycle_start: i64,
+) -> Instruction {
+    dial_ix(
+        smart_dial::accounts::UpdateRewardToken {
+            dial_state: dial_pda(),
+            authority: *authority,
+        },
+        smart_dial::instruction::UpdateRewardToken {
+            new_reward_token,
+            cycle_start,
+            next_cycle_start,
+        },
+    )
+}
+
+fn sync_ix(authority: &Pubkey, new_launch_timestamp: i64) -> Instruction {
+    dial_ix(
+        smart_dial::accounts::SyncLaunchTimestamp {
+            dial_state: dial_pda(),
+            authority: *authority,
+        },
+        smart_dial::instruction::SyncLaunchTimestamp {
+            new_launch_timestamp,
+        },
+    )
+}
+
+fn view_ix() -> Instruction {
+    dial_ix(
+        smart_dial::accounts::ViewDial {
+            dial_state: dial_pda(),
+        },
+        smart_dial::instruction::GetCurrentRewardToken {},
+    )
+}
+
+async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+fn set_time(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    ctx.set_sysvar(&Clock {
+        unix_timestamp,
+        ..Clock::default()
+    });
+}
+
+/// Custom program error code carried by a failed transaction
+fn error_code(err: BanksClientError) -> Option<u32> {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+async fn fetch_dial(ctx: &mut ProgramTestContext) -> DialState {
+    let account = ctx
+        .banks_client
+        .get_account(dial_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let fields = account.data.strip_prefix(DialState::DISCRIMINATOR).unwrap();
+    bytemuck::pod_read_unaligned(fields)
+}
+
+fn program_test() -> ProgramTest {
+    let mut pt = ProgramTest::new(
+        "smart_dial",
+        smart_dial::id(),
+        processor!(process_instruction),
+    );
+    pt.prefer_bpf(false);
+    pt
+}
+
+/// Dial initialized for a launch at `launch_timestamp`, with the clock at launch
+async fn start(launch_timestamp: i64) -> (ProgramTestContext, Keypair) {
+    let pt = program_test();
+    let mut ctx = pt.start_with_context().await;
+    set_time(&mut ctx, LAUNCH);
+
+    let authority = Keypair::new();
+    let initialize = dial_ix(
+        smart_dial::accounts::Initialize {
+            dial_state: dial_pda(),
+            authority: authority.pubkey(),
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_dial::instruction::Initialize { launch_timestamp },
+    );
+    send(&mut ctx, &[initialize], &[&authority]).await.unwrap();
+
+    (ctx, authority)
+}
+
+#[tokio::test]
+async fn updates_follow_the_weekly_cycle() {
+    let (mut ctx, authority) = start(LAUNCH).await;
+    let key = authority.pubkey();
+    let first_cycle = LAUNCH + INITIAL_UPDATE_DELAY;
+    let token = Pubkey::new_unique();
+
+    set_time(&mut ctx, first_cycle - 1);
+    let err = send(
+        &mut ctx,
+        &[update_ix(&key, token, first_cycle, first_cycle + WEEK)],
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(DialError::TooEarlyToUpdate.into()));
+
+    set_time(&mut ctx, first_cycle + 60);
+    let cases = [
+        (
+            update_ix(&key, token, first_cycle - 1, first_cycle + WEEK),
+            DialError::TooEarlyToUpdate,
+        ),
+        (
+            update_ix(&key, token, first_cycle + 61, first_cycle + WEEK),
+            DialError::CycleNotReached,
+        ),
+        (
+            update_ix(&key, token, first_cycle, first_cycle),
+            DialError::InvalidNextCycleStart,
+        ),
+    ];
+    for (ix, expected) in cases {
+        let err = send(&mut ctx, &[ix], &[&authority]).await.unwrap_err();
+        assert_eq!(error_code(err), Some(expected.into()));
+    }
+
+    send(
+        &mut ctx,
+        &[update_ix(&key, token, first_cycle, first_cycle + WEEK)],
+        &[&authority],
+    )
+    .await
+    .unwrap();
+    let dial = fetch_dial(&mut ctx).await;
+    assert_eq!(dial.current_reward_token, token);
+    assert_eq!(dial.last_update, first_cycle);
+    assert_eq!(dial.update_count, 1);
+    assert_eq!(dial.history_len().unwrap(), 1);
+    assert_eq!(dial.update_history().next().unwrap().old_token, SOL_MINT);
+
+    let err = send(
+        &mut ctx,
+        &[update_ix(
+            &key,
+            Pubkey::new_unique(),
+            first_cycle,
+            first_cycle + WEEK,
+        )],
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(DialError::CycleAlreadyProcessed.into())
+    );
+}
+
+#[tokio::test]
+async fn only_the_authority_can_change_the_dial() {
+    let (mut ctx, _authority) = start(LAUNCH).await;
+    let intruder = Keypair::new();
+    let key = intruder.pubkey();
+    let first_cycle = LAUNCH + INITIAL_UPDATE_DELAY;
+    set_time(&mut ctx, first_cycle);
+
+    let update_authority = dial_ix(
+        smart_dial::accounts::UpdateAuthority {
+            dial_state: dial_pda(),
+            authority: key,
+        },
+        smart_dial::instruction::UpdateAuthority { new_authority: key },
+    );
+    for ix in [
+        update_ix(&key, Pubkey::new_unique(), first_cycle, first_cycle + WEEK),
+        update_authority,
+        sync_ix(&key, LAUNCH + 1),
+    ] {
+        let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+        assert_eq!(error_code(err), Some(DialError::Unauthorized.into()));
+    }
+}
+
+#[tokio::test]
+async fn launch_timestamp_only_moves_forward_before_the_first_update() {
+    let (mut ctx, authority) = start(LAUNCH).await;
+    let key = authority.pubkey();
+
+    let err = send(&mut ctx, &[sync_ix(&key, LAUNCH - 1)], &[&authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(DialError::InvalidLaunchTimestamp.into())
+    );
+
+    let launch = LAUNCH + 3_600;
+    send(&mut ctx, &[sync_ix(&key, launch)], &[&authority])
+        .await
+        .unwrap();
+    assert_eq!(fetch_dial(&mut ctx).await.launch_timestamp, launch);
+
+    let first_cycle = launch + INITIAL_UPDATE_DELAY;
+    set_time(&mut ctx, first_cycle);
+    send(
+        &mut ctx,
+        &[update_ix(
+            &key,
+            Pubkey::new_unique(),
+            first_cycle,
+            first_cycle + WEEK,
+        )],
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    let err = send(&mut ctx, &[sync_ix(&key, launch + 1)], &[&authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error_code(err),
+        Some(DialError::CannotSyncAfterUpdates.into())
+    );
+}
+
+#[tokio::test]
+async fn update_window_overflow_is_an_error() {
+    let (mut ctx, authority) = start(i64::MAX).await;
+
+    let err = send(
+        &mut ctx,
+        &[update_ix(
+            &authority.pubkey(),
+            Pubkey::new_unique(),
+            LAUNCH,
+            LAUNCH + WEEK,
+        )],
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error_code(err), Some(DialError::MathOverflow.into()));
+}
+
+#[tokio::test]
+async fn dial_in_a_legacy_layout_must_be_migrated_first() {
+    let authority = Keypair::new();
+    let legacy = DialStateLayoutV1 {
+        version: 1,
+        authority: authority.pubkey(),
+        current_reward_token: SOL_MINT,
+        last_update: 0,
+        update_count: 0,
+        launch_timestamp: LAUNCH,
+        update_history: Vec::new(),
+        reserved: [0; 64],
+    };
+    let mut data = DialState::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    // Shorter than the zero-copy account, so loading it as one would read past its end
+    data.resize(DIAL_STATE_LAYOUT_V1_LEN, 0);
+
+    let mut pt = program_test();
+    pt.add_account(
+        dial_pda(),
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: smart_dial::id(),
+            ..Account::default()
+        },
+    );
+    pt.add_account(
+        authority.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+    let mut ctx = pt.start_with_context().await;
+    set_time(&mut ctx, LAUNCH + INITIAL_UPDATE_DELAY);
+
+    let update = update_ix(
+        &authority.pubkey(),
+        Pubkey::new_unique(),
+        LAUNCH + INITIAL_UPDATE_DELAY,
+        LAUNCH + INITIAL_UPDATE_DELAY + WEEK,
+    );
+    let err = send(&mut ctx, std::slice::from_ref(&update), &[&authority])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), Some(DialError::AccountNotMigrated.into()));
+    let err = send(&mut ctx, &[view_ix()], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), Some(DialError::AccountNotMigrated.into()));
+
+    let migrate = dial_ix(
+        smart_dial::accounts::Migrate {
+            dial_state: dial_pda(),
+            authority: authority.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_dial::instruction::Migrate {},
+    );
+    send(&mut ctx, &[migrate, update], &[&authority])
+        .await
+        .unwrap();
+    assert_eq!(fetch_dial(&mut ctx).await.update_count, 1);
+}
@@ -0,0 +1,94 @@
+use anchor_lang::{error::Error, prelude::Pubkey, AnchorSerialize, Discriminator};
+use bytemuck::Zeroable;
+use smart_dial::{
+    read_legacy_dial, DialError, DialState, DialStateLayoutV0, DialStateLayoutV1,
+    LegacyUpdateRecord, DIAL_STATE_LAYOUT_V0_LEN, DIAL_STATE_LAYOUT_V1_LEN, DIAL_STATE_LEN,
+    DIAL_STATE_VERSION,
+};
+
+/// Account data of a dial written in a Borsh layout
+fn legacy_fixture(layout: &impl AnchorSerialize, len: usize) -> Vec<u8> {
+    let mut data = DialState::DISCRIMINATOR.to_vec();
+    layout.serialize(&mut data).unwrap();
+    data.resize(len, 0);
+    data
+}
+
+fn legacy_dial() -> DialStateLayoutV0 {
+    let old_token = Pubkey::new_unique();
+    let new_token = Pubkey::new_unique();
+    DialStateLayoutV0 {
+        authority: Pubkey::new_unique(),
+        current_reward_token: new_token,
+        last_update: 1_700_086_400,
+        update_count: 1,
+        launch_timestamp: 1_700_000_000,
+        update_history: vec![LegacyUpdateRecord {
+            timestamp: 1_700_086_401,
+            old_token,
+            new_token,
+            update_number: 0,
+        }],
+    }
+}
+
+fn migrate(data: &[u8]) -> DialState {
+    let mut dial = DialState::zeroed();
+    read_legacy_dial(data)
+        .unwrap()
+        .migrate_into(&mut dial)
+        .unwrap();
+    dial
+}
+
+#[test]
+fn legacy_dial_migrates_to_current_layout() {
+    let legacy = legacy_dial();
+    let dial = migrate(&legacy_fixture(&legacy, DIAL_STATE_LAYOUT_V0_LEN));
+
+    assert_eq!(dial.version, DIAL_STATE_VERSION);
+    assert_eq!(dial.authority, legacy.authority);
+    assert_eq!(dial.current_reward_token, legacy.current_reward_token);
+    assert_eq!(dial.last_update, legacy.last_update);
+    assert_eq!(dial.update_count, legacy.update_count);
+    assert_eq!(dial.launch_timestamp, legacy.launch_timestamp);
+    assert_eq!(dial.history_len().unwrap(), 1);
+    assert_eq!(
+        dial.update_history().next().unwrap().new_token,
+        legacy.update_history[0].new_token
+    );
+    assert_eq!(dial.reserved, [0; 63]);
+}
+
+#[test]
+fn full_borsh_history_migrates_in_order() {
+    let mut legacy: DialStateLayoutV1 = legacy_dial().into();
+    legacy.update_history = (0..52)
+        .map(|update_number| LegacyUpdateRecord {
+            timestamp: 1_700_086_400 + update_number as i64,
+            old_token: Pubkey::new_unique(),
+            new_token: Pubkey::new_unique(),
+            update_number,
+        })
+        .collect();
+    legacy.update_count = 52;
+    let mut dial = migrate(&legacy_fixture(&legacy, DIAL_STATE_LAYOUT_V1_LEN));
+
+    let numbers: Vec<u64> = dial.update_history().map(|r| r.update_number).collect();
+    assert_eq!(numbers, (0..52).collect::<Vec<_>>());
+
+    // The next update replaces the oldest migrated record
+    dial.record_update(Pubkey::new_unique(), 1_700_200_000, 1_700_200_000)
+        .unwrap();
+    let numbers: Vec<u64> = dial.update_history().map(|r| r.update_number).collect();
+    assert_eq!(numbers, (1..53).collect::<Vec<_>>());
+}
+
+#[test]
+fn current_layout_is_not_migrated_again() {
+    let mut current = DialState::DISCRIMINATOR.to_vec();
+    current.resize(DIAL_STATE_LEN, 0);
+
+    let err = read_legacy_dial(&current).map(|_| ()).unwrap_err();
+    assert_eq!(err, Error::from(DialError::UnsupportedAccountLayout));
+}
@@ -0,0 +1,37 @@
+use anchor_lang::{error::Error, prelude::Pubkey};
+use bytemuck::Zeroable;
+use proptest::prelude::*;
+use smart_dial::{DialError, DialState};
+
+/// Freshly allocated dial: every counter at zero and an empty history
+fn zeroed() -> DialState {
+    DialState::zeroed()
+}
+
+proptest! {
+    #[test]
+    fn update_count_fails_instead_of_wrapping(
+        update_count in (u64::MAX - 1_000)..=u64::MAX,
+        updates in 1usize..60,
+    ) {
+        let mut dial = zeroed();
+        dial.update_count = update_count;
+
+        for i in 0..updates {
+            let expected = dial.update_count.checked_add(1);
+            let history_len = dial.history_len().unwrap();
+            match dial.record_update(Pubkey::new_unique(), i as i64 + 1, i as i64 + 1) {
+                Ok(()) => {
+                    prop_assert_eq!(Some(dial.update_count), expected);
+                    prop_assert_eq!(dial.history_len().unwrap(), (history_len + 1).min(52));
+                }
+                Err(err) => {
+                    prop_assert!(expected.is_none());
+                    prop_assert_eq!(err, Error::from(DialError::MathOverflow));
+                    prop_assert_eq!(dial.update_count, u64::MAX);
+                    prop_assert_eq!(dial.history_len().unwrap(), history_len);
+                }
+            }
+        }
+    }
+}
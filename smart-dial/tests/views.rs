@@ -0,0 +1,60 @@
+use anchor_lang::prelude::Pubkey;
+use bytemuck::Zeroable;
+use smart_dial::{
+    DialState, DIAL_STATE_VERSION, INITIAL_UPDATE_DELAY, MAX_UPDATE_HISTORY, SOL_MINT,
+};
+
+const LAUNCH: i64 = 1_700_000_000;
+
+fn dial() -> DialState {
+    DialState {
+        version: DIAL_STATE_VERSION,
+        authority: Pubkey::new_unique(),
+        current_reward_token: SOL_MINT,
+        launch_timestamp: LAUNCH,
+        ..DialState::zeroed()
+    }
+}
+
+#[test]
+fn cycle_info_opens_updates_after_the_initial_delay() {
+    let dial = dial();
+    let earliest_update = LAUNCH + INITIAL_UPDATE_DELAY;
+
+    let info = dial.cycle_info(earliest_update - 1).unwrap();
+    assert_eq!(info.current_reward_token, SOL_MINT);
+    assert_eq!(info.earliest_update, earliest_update);
+    assert_eq!(info.cycle_start, 0);
+    assert!(!info.updates_open);
+
+    assert!(dial.cycle_info(earliest_update).unwrap().updates_open);
+}
+
+#[test]
+fn cycle_info_reports_the_latest_update() {
+    let mut dial = dial();
+    let new_token = Pubkey::new_unique();
+    let cycle_start = LAUNCH + INITIAL_UPDATE_DELAY;
+    dial.record_update(new_token, cycle_start, cycle_start + 5)
+        .unwrap();
+
+    let info = dial.cycle_info(cycle_start + 5).unwrap();
+    assert_eq!(info.current_reward_token, new_token);
+    assert_eq!(info.cycle_start, cycle_start);
+    assert_eq!(info.update_count, 1);
+}
+
+#[test]
+fn update_history_keeps_the_latest_year_in_order() {
+    let mut dial = dial();
+    let first_cycle = LAUNCH + INITIAL_UPDATE_DELAY;
+    for week in 0..60 {
+        let cycle_start = first_cycle + week * 7 * 24 * 60 * 60;
+        dial.record_update(Pubkey::new_unique(), cycle_start, cycle_start)
+            .unwrap();
+    }
+
+    assert_eq!(dial.history_len().unwrap(), MAX_UPDATE_HISTORY);
+    let numbers: Vec<u64> = dial.update_history().map(|r| r.update_number).collect();
+    assert_eq!(numbers, (8..60).collect::<Vec<_>>());
+}